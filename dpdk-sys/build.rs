@@ -82,6 +82,7 @@ impl BindgenBuilderExtensions for bindgen::Builder {
             .blocklist_function(".*scanf.*")
             .blocklist_function("strerror_r")
             .blocklist_item("per_lcore__lcore_id")
+            .blocklist_item("per_lcore_trace_point_sz")
     }
 
     fn add_headers(self, include_path: &mut PathBuf) -> Self {
@@ -5924,9 +5924,6 @@ extern "C" {
         register_fn: ::std::option::Option<unsafe extern "C" fn()>,
     ) -> ::std::os::raw::c_int;
 }
-extern "C" {
-    pub static mut per_lcore_trace_point_sz: ::std::os::raw::c_int;
-}
 pub type __gwchar_t = ::std::os::raw::c_int;
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
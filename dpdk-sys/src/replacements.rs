@@ -1,6 +1,6 @@
 use crate::{
     bindings_meson::{__BindgenBitfieldUnit, rte_get_next_lcore, RTE_MAX_LCORE},
-    rte_mbuf, rte_mempool,
+    cpu_set_t, rte_mbuf, rte_mempool,
 };
 
 extern "C" {
@@ -12,6 +12,12 @@ extern "C" {
 
     #[thread_local]
     pub static mut per_lcore__thread_id: libc::c_int;
+
+    #[thread_local]
+    pub static mut per_lcore_trace_point_sz: libc::c_int;
+
+    #[thread_local]
+    pub static mut per_lcore_trace_mem: *mut __rte_trace_header;
 }
 
 #[macro_export]
@@ -325,3 +331,170 @@ extern "C" {
     pub fn rte_pktmbuf_alloc_bulk(mp: *mut rte_mempool, mbufs: *mut *mut rte_mbuf, count: u32) -> *mut rte_mbuf;
 }
 
+extern "C" {
+    /// Get the cpuset of an lcore. bindgen skips this one since `rte_cpuset_t` is a typedef of
+    /// `cpu_set_t` behind a platform `#ifdef`.
+    pub fn rte_lcore_cpuset(lcore_id: libc::c_uint) -> cpu_set_t;
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Default)]
+pub struct cookie_io_functions_t {
+    pub read: Option<
+        unsafe extern "C" fn(cookie: *mut libc::c_void, buf: *mut libc::c_char, size: libc::size_t) -> libc::ssize_t,
+    >,
+    pub write: Option<
+        unsafe extern "C" fn(cookie: *mut libc::c_void, buf: *const libc::c_char, size: libc::size_t) -> libc::ssize_t,
+    >,
+    pub seek: Option<
+        unsafe extern "C" fn(cookie: *mut libc::c_void, offset: *mut libc::off64_t, whence: libc::c_int) -> libc::c_int,
+    >,
+    pub close: Option<unsafe extern "C" fn(cookie: *mut libc::c_void) -> libc::c_int>,
+}
+
+extern "C" {
+    /// glibc extension used to hand `rte_openlog_stream` a FILE backed by Rust callbacks. It is
+    /// not part of the DPDK headers, so bindgen never sees it.
+    pub fn fopencookie(
+        cookie: *mut libc::c_void,
+        mode: *const libc::c_char,
+        io_funcs: cookie_io_functions_t,
+    ) -> *mut crate::FILE;
+}
+
+/// Leading fields of `struct rte_pci_device` from the driver-only `rte_bus_pci.h`, which bindgen
+/// does not see. The struct is longer than this, only ever use it behind a pointer handed out by
+/// the PCI bus.
+#[repr(C)]
+#[derive(Debug)]
+pub struct rte_pci_device {
+    pub next: rte_pci_device_tailq_entry,
+    pub device: crate::rte_device,
+    pub addr: crate::rte_pci_addr,
+    pub id: crate::rte_pci_id,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct rte_pci_device_tailq_entry {
+    pub tqe_next: *mut rte_pci_device,
+    pub tqe_prev: *mut *mut rte_pci_device,
+}
+
+/// `RTE_DEV_TO_PCI`, the device must be on the PCI bus.
+#[inline]
+pub unsafe fn rte_dev_to_pci(dev: *const crate::rte_device) -> *const rte_pci_device {
+    let offset = std::mem::size_of::<rte_pci_device_tailq_entry>();
+    (dev as *const u8).sub(offset) as *const rte_pci_device
+}
+
+/// Per thread trace buffer from `rte_trace_point.h`, only used through `per_lcore_trace_mem`.
+#[repr(C)]
+#[derive(Debug)]
+pub struct __rte_trace_header {
+    pub offset: u32,
+    pub len: u32,
+    pub stream_header: __rte_trace_stream_header,
+    pub mem: [u8; 0],
+}
+
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct __rte_trace_stream_header {
+    pub magic: u32,
+    pub uuid: [u8; 16],
+    pub lcore_id: u32,
+    pub thread_name: [libc::c_char; crate::__RTE_TRACE_EMIT_STRING_LEN_MAX as usize],
+}
+
+// Layout of a tracepoint handle and of the event header, also from `rte_trace_point.h`
+pub const __RTE_TRACE_EVENT_HEADER_SZ: usize = std::mem::size_of::<u64>();
+pub const __RTE_TRACE_EVENT_HEADER_ID_SHIFT: u64 = 48;
+pub const __RTE_TRACE_FIELD_SIZE_MASK: u64 = 0xffff;
+pub const __RTE_TRACE_FIELD_ID_SHIFT: u64 = 16;
+pub const __RTE_TRACE_FIELD_ID_MASK: u64 = 0xffff << __RTE_TRACE_FIELD_ID_SHIFT;
+pub const __RTE_TRACE_FIELD_ENABLE_MASK: u64 = 1 << 63;
+pub const __RTE_TRACE_FIELD_ENABLE_DISCARD: u64 = 1 << 62;
+
+// Flags from `rte_ethdev.h` defined with `RTE_BIT64`/`RTE_BIT32`, which bindgen does not expand
+pub const RTE_ETH_LINK_SPEED_FIXED: u32 = 1 << 0;
+pub const RTE_ETH_LINK_SPEED_10M_HD: u32 = 1 << 1;
+pub const RTE_ETH_LINK_SPEED_10M: u32 = 1 << 2;
+pub const RTE_ETH_LINK_SPEED_100M_HD: u32 = 1 << 3;
+pub const RTE_ETH_LINK_SPEED_100M: u32 = 1 << 4;
+pub const RTE_ETH_LINK_SPEED_1G: u32 = 1 << 5;
+pub const RTE_ETH_LINK_SPEED_2_5G: u32 = 1 << 6;
+pub const RTE_ETH_LINK_SPEED_5G: u32 = 1 << 7;
+pub const RTE_ETH_LINK_SPEED_10G: u32 = 1 << 8;
+pub const RTE_ETH_LINK_SPEED_20G: u32 = 1 << 9;
+pub const RTE_ETH_LINK_SPEED_25G: u32 = 1 << 10;
+pub const RTE_ETH_LINK_SPEED_40G: u32 = 1 << 11;
+pub const RTE_ETH_LINK_SPEED_50G: u32 = 1 << 12;
+pub const RTE_ETH_LINK_SPEED_56G: u32 = 1 << 13;
+pub const RTE_ETH_LINK_SPEED_100G: u32 = 1 << 14;
+pub const RTE_ETH_LINK_SPEED_200G: u32 = 1 << 15;
+
+pub const RTE_ETH_RX_OFFLOAD_VLAN_STRIP: u64 = 1 << 0;
+pub const RTE_ETH_RX_OFFLOAD_IPV4_CKSUM: u64 = 1 << 1;
+pub const RTE_ETH_RX_OFFLOAD_UDP_CKSUM: u64 = 1 << 2;
+pub const RTE_ETH_RX_OFFLOAD_TCP_CKSUM: u64 = 1 << 3;
+pub const RTE_ETH_RX_OFFLOAD_TCP_LRO: u64 = 1 << 4;
+pub const RTE_ETH_RX_OFFLOAD_QINQ_STRIP: u64 = 1 << 5;
+pub const RTE_ETH_RX_OFFLOAD_OUTER_IPV4_CKSUM: u64 = 1 << 6;
+pub const RTE_ETH_RX_OFFLOAD_MACSEC_STRIP: u64 = 1 << 7;
+pub const RTE_ETH_RX_OFFLOAD_VLAN_FILTER: u64 = 1 << 9;
+pub const RTE_ETH_RX_OFFLOAD_VLAN_EXTEND: u64 = 1 << 10;
+pub const RTE_ETH_RX_OFFLOAD_SCATTER: u64 = 1 << 13;
+pub const RTE_ETH_RX_OFFLOAD_TIMESTAMP: u64 = 1 << 14;
+pub const RTE_ETH_RX_OFFLOAD_SECURITY: u64 = 1 << 15;
+pub const RTE_ETH_RX_OFFLOAD_KEEP_CRC: u64 = 1 << 16;
+pub const RTE_ETH_RX_OFFLOAD_SCTP_CKSUM: u64 = 1 << 17;
+pub const RTE_ETH_RX_OFFLOAD_OUTER_UDP_CKSUM: u64 = 1 << 18;
+pub const RTE_ETH_RX_OFFLOAD_RSS_HASH: u64 = 1 << 19;
+pub const RTE_ETH_RX_OFFLOAD_BUFFER_SPLIT: u64 = 1 << 20;
+
+pub const RTE_ETH_TX_OFFLOAD_VLAN_INSERT: u64 = 1 << 0;
+pub const RTE_ETH_TX_OFFLOAD_IPV4_CKSUM: u64 = 1 << 1;
+pub const RTE_ETH_TX_OFFLOAD_UDP_CKSUM: u64 = 1 << 2;
+pub const RTE_ETH_TX_OFFLOAD_TCP_CKSUM: u64 = 1 << 3;
+pub const RTE_ETH_TX_OFFLOAD_SCTP_CKSUM: u64 = 1 << 4;
+pub const RTE_ETH_TX_OFFLOAD_TCP_TSO: u64 = 1 << 5;
+pub const RTE_ETH_TX_OFFLOAD_UDP_TSO: u64 = 1 << 6;
+pub const RTE_ETH_TX_OFFLOAD_OUTER_IPV4_CKSUM: u64 = 1 << 7;
+pub const RTE_ETH_TX_OFFLOAD_QINQ_INSERT: u64 = 1 << 8;
+pub const RTE_ETH_TX_OFFLOAD_VXLAN_TNL_TSO: u64 = 1 << 9;
+pub const RTE_ETH_TX_OFFLOAD_GRE_TNL_TSO: u64 = 1 << 10;
+pub const RTE_ETH_TX_OFFLOAD_IPIP_TNL_TSO: u64 = 1 << 11;
+pub const RTE_ETH_TX_OFFLOAD_GENEVE_TNL_TSO: u64 = 1 << 12;
+pub const RTE_ETH_TX_OFFLOAD_MACSEC_INSERT: u64 = 1 << 13;
+pub const RTE_ETH_TX_OFFLOAD_MT_LOCKFREE: u64 = 1 << 14;
+pub const RTE_ETH_TX_OFFLOAD_MULTI_SEGS: u64 = 1 << 15;
+pub const RTE_ETH_TX_OFFLOAD_MBUF_FAST_FREE: u64 = 1 << 16;
+pub const RTE_ETH_TX_OFFLOAD_SECURITY: u64 = 1 << 17;
+pub const RTE_ETH_TX_OFFLOAD_UDP_TNL_TSO: u64 = 1 << 18;
+pub const RTE_ETH_TX_OFFLOAD_IP_TNL_TSO: u64 = 1 << 19;
+pub const RTE_ETH_TX_OFFLOAD_OUTER_UDP_CKSUM: u64 = 1 << 20;
+pub const RTE_ETH_TX_OFFLOAD_SEND_ON_TIMESTAMP: u64 = 1 << 21;
+
+pub const RTE_ETH_RSS_IPV4: u64 = 1 << 2;
+pub const RTE_ETH_RSS_FRAG_IPV4: u64 = 1 << 3;
+pub const RTE_ETH_RSS_NONFRAG_IPV4_TCP: u64 = 1 << 4;
+pub const RTE_ETH_RSS_NONFRAG_IPV4_UDP: u64 = 1 << 5;
+pub const RTE_ETH_RSS_NONFRAG_IPV4_SCTP: u64 = 1 << 6;
+pub const RTE_ETH_RSS_NONFRAG_IPV4_OTHER: u64 = 1 << 7;
+pub const RTE_ETH_RSS_IPV6: u64 = 1 << 8;
+pub const RTE_ETH_RSS_FRAG_IPV6: u64 = 1 << 9;
+pub const RTE_ETH_RSS_NONFRAG_IPV6_TCP: u64 = 1 << 10;
+pub const RTE_ETH_RSS_NONFRAG_IPV6_UDP: u64 = 1 << 11;
+pub const RTE_ETH_RSS_NONFRAG_IPV6_SCTP: u64 = 1 << 12;
+pub const RTE_ETH_RSS_NONFRAG_IPV6_OTHER: u64 = 1 << 13;
+pub const RTE_ETH_RSS_L2_PAYLOAD: u64 = 1 << 14;
+pub const RTE_ETH_RSS_IPV6_EX: u64 = 1 << 15;
+pub const RTE_ETH_RSS_IPV6_TCP_EX: u64 = 1 << 16;
+pub const RTE_ETH_RSS_IPV6_UDP_EX: u64 = 1 << 17;
+pub const RTE_ETH_RSS_PORT: u64 = 1 << 18;
+pub const RTE_ETH_RSS_VXLAN: u64 = 1 << 19;
+pub const RTE_ETH_RSS_GENEVE: u64 = 1 << 20;
+pub const RTE_ETH_RSS_NVGRE: u64 = 1 << 21;
+pub const RTE_ETH_RSS_GTPU: u64 = 1 << 23;
+pub const RTE_ETH_RSS_ETH: u64 = 1 << 24;
@@ -0,0 +1,96 @@
+//! Lists network devices and moves them between kernel drivers, like `dpdk-devbind.py`.
+//!
+//! ```text
+//! dpdk-devbind [--root ROOT] --status
+//! dpdk-devbind [--root ROOT] --bind DRIVER ADDRESS...
+//! dpdk-devbind [--root ROOT] --unbind ADDRESS...
+//! ```
+//!
+//! `--root` is the directory holding `sys`, for trying things out on a copy of the tree.
+
+use dpdk::{
+    config::PCIAddress,
+    device::pci::{bind, scan_network_devices, set_driver_override, unbind},
+    sysfs::Host,
+};
+
+const USAGE: &str = "usage: dpdk-devbind [--root ROOT] (--status | --bind DRIVER ADDRESS... | --unbind ADDRESS...)";
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut host = Host::default();
+    let mut status = false;
+    let mut bind_driver = None;
+    let mut do_unbind = false;
+    let mut addresses: Vec<PCIAddress> = vec![];
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .ok_or_else(|| format!("{name} needs a value\n{USAGE}"))
+        };
+        match arg.as_str() {
+            "--root" => host = Host::at(value("--root")?),
+            "--status" => status = true,
+            "-b" | "--bind" => bind_driver = Some(value("--bind")?),
+            "-u" | "--unbind" => do_unbind = true,
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return Ok(());
+            }
+            _ if arg.starts_with('-') => {
+                return Err(format!("Unknown option {arg}\n{USAGE}").into())
+            }
+            _ => addresses.push(arg.parse()?),
+        }
+    }
+
+    let rebind = bind_driver.is_some() || do_unbind;
+    if bind_driver.is_some() && do_unbind || status && rebind {
+        let err = format!("Only one of --status, --bind and --unbind can be given\n{USAGE}");
+        return Err(err.into());
+    }
+    if rebind && addresses.is_empty() {
+        return Err(format!("No PCI addresses given\n{USAGE}").into());
+    }
+    if !rebind && !addresses.is_empty() {
+        return Err(format!("PCI addresses need --bind or --unbind\n{USAGE}").into());
+    }
+
+    if let Some(driver) = bind_driver {
+        for &address in &addresses {
+            bind(&host, address, &driver)?;
+            println!("Bound {address} to {driver}");
+        }
+        return Ok(());
+    }
+
+    if do_unbind {
+        for &address in &addresses {
+            unbind(&host, address)?;
+            set_driver_override(&host, address, None)?;
+            println!("Unbound {address}");
+        }
+        return Ok(());
+    }
+
+    for nic in scan_network_devices(&host)? {
+        println!(
+            "{} {:04x}:{:04x} numa={} driver={} if={}{}",
+            nic.address,
+            nic.vendor_id,
+            nic.device_id,
+            nic.numa_node
+                .map(|node| node.to_string())
+                .unwrap_or_else(|| "-".to_string()),
+            nic.driver.as_deref().unwrap_or("-"),
+            if nic.interfaces.is_empty() {
+                "-".to_string()
+            } else {
+                nic.interfaces.join(",")
+            },
+            if nic.is_dpdk_compatible() { " *dpdk*" } else { "" },
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,70 @@
+//! Queries the telemetry socket of a running DPDK process.
+//!
+//! ```text
+//! dpdk-telemetry [--file-prefix PREFIX] --list
+//! dpdk-telemetry [--file-prefix PREFIX] COMMAND [PARAMS]
+//! dpdk-telemetry [--file-prefix PREFIX] --interval SECONDS [--count N] COMMAND [PARAMS]
+//! ```
+//!
+//! Single queries print the reply as JSON, with `--interval` the reply is written as CSV rows.
+
+use std::time::Duration;
+
+use dpdk::telemetry::client::{TelemetryClient, DEFAULT_FILE_PREFIX};
+
+const USAGE: &str = "usage: dpdk-telemetry [--file-prefix PREFIX] [--list] [--interval SECONDS [--count N]] [COMMAND [PARAMS]]";
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut file_prefix = DEFAULT_FILE_PREFIX.to_string();
+    let mut list = false;
+    let mut interval = None;
+    let mut count = None;
+    let mut positional = vec![];
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .ok_or_else(|| format!("{name} needs a value\n{USAGE}"))
+        };
+        match arg.as_str() {
+            "--file-prefix" => file_prefix = value("--file-prefix")?,
+            "--list" => list = true,
+            "--interval" => interval = Some(Duration::from_secs_f64(value("--interval")?.parse()?)),
+            "--count" => count = Some(value("--count")?.parse()?),
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return Ok(());
+            }
+            _ => positional.push(arg),
+        }
+    }
+
+    let mut client = TelemetryClient::connect(&file_prefix)?;
+    let info = client.info();
+    eprintln!("Connected to {} (pid {})", info.version, info.pid);
+
+    if list {
+        for command in client.commands()? {
+            println!("{command}");
+        }
+        return Ok(());
+    }
+
+    let command = match positional.first() {
+        Some(command) => command.as_str(),
+        None => return Err(USAGE.into()),
+    };
+    let params = positional.get(1).map(String::as_str);
+
+    match interval {
+        Some(interval) => {
+            client.poll_csv(command, params, interval, count, std::io::stdout().lock())?
+        }
+        None => println!(
+            "{}",
+            serde_json::to_string_pretty(&client.raw(command, params)?)?
+        ),
+    }
+    Ok(())
+}
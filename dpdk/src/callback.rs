@@ -0,0 +1,77 @@
+//! Rust closures called back by DPDK.
+//!
+//! Callbacks run on threads owned by the EAL: the interrupt thread, the IPC and telemetry
+//! threads, service lcores and lcores calling `rte_timer_manage`. A panic may not unwind into
+//! the C code calling them, and there is nobody to hand it to. Every trampoline therefore runs
+//! its closure through [`catch_panic`], which logs the panic and lets the EAL carry on as if
+//! the callback had failed.
+
+use std::{
+    fmt::Arguments,
+    panic::{self, AssertUnwindSafe},
+};
+
+use dpdk_sys::EAGAIN;
+use libc::c_void;
+use parking_lot::Mutex;
+
+/// Runs `f`, returning None and logging `what` if it panicked.
+pub(crate) fn catch_panic<T>(what: Arguments, f: impl FnOnce() -> T) -> Option<T> {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(ret) => Some(ret),
+        Err(_) => {
+            log::error!("{what} panicked");
+            None
+        }
+    }
+}
+
+/// A boxed closure passed to a DPDK register function as its `cb_arg`. The closure is freed
+/// when this is dropped, so the owner has to [`unregister`](Self::unregister) it first.
+pub(crate) struct RegisteredCallback<F: ?Sized> {
+    callback: *mut Mutex<Box<F>>,
+}
+
+// The closure is Send and only ever called behind its mutex
+unsafe impl<F: ?Sized + Send> Send for RegisteredCallback<F> {}
+
+impl<F: ?Sized> RegisteredCallback<F> {
+    pub(crate) fn new(f: Box<F>) -> Self {
+        Self {
+            callback: Box::into_raw(Box::new(Mutex::new(f))),
+        }
+    }
+
+    pub(crate) fn arg(&self) -> *mut c_void {
+        self.callback as *mut c_void
+    }
+
+    /// Locks the closure behind `arg` and hands it to `call`, see [`catch_panic`].
+    ///
+    /// # Safety
+    ///
+    /// `arg` has to come from [`RegisteredCallback::arg`] of a callback that is still alive.
+    pub(crate) unsafe fn call<T>(
+        arg: *mut c_void,
+        what: Arguments,
+        call: impl FnOnce(&mut F) -> T,
+    ) -> Option<T> {
+        let callback = unsafe { &*(arg as *const Mutex<Box<F>>) };
+        let mut f = callback.lock();
+        catch_panic(what, || call(&mut **f))
+    }
+
+    /// Calls `unregister` with [`arg`](Self::arg) until it stops failing with `EAGAIN`, which
+    /// DPDK returns while the callback is running.
+    pub(crate) fn unregister(&self, mut unregister: impl FnMut(*mut c_void) -> i32) {
+        while unregister(self.arg()) == -(EAGAIN as i32) {
+            std::hint::spin_loop();
+        }
+    }
+}
+
+impl<F: ?Sized> Drop for RegisteredCallback<F> {
+    fn drop(&mut self) {
+        drop(unsafe { Box::from_raw(self.callback) });
+    }
+}
@@ -0,0 +1,366 @@
+use std::{
+    backtrace::Backtrace,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::device::eth::dev::EthdevPortId;
+
+use super::{parse_int, ConfigError, DPDKConfig, PCIAddress, PCIOptions, PortConfig};
+
+/// Prefix of the environment variables read by [`ConfigFile::with_env_overrides`].
+pub const ENV_PREFIX: &str = "DPDK_";
+
+/// An EAL config and the ports to set up, as loaded from a TOML or JSON file:
+///
+/// ```toml
+/// [eal]
+/// cores = "1-3"
+/// service_core_mask = 3
+/// iova_mode = "pa"
+/// pci_options = { mode = "pci", allowed_devices = ["0000:02:00.0"] }
+/// virtual_devices = [{ driver = "event_sw", options = { credit_quanta = "64" } }]
+///
+/// [[ports]]
+/// port = 0
+/// rx_queues = 2
+/// rx_offloads = ["IPV4_CKSUM"]
+/// ```
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConfigFile {
+    pub eal: DPDKConfig,
+    pub ports: Vec<PortConfig>,
+}
+
+impl ConfigFile {
+    pub fn from_toml(toml: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(toml)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Loads a `.toml` or `.json` file, without applying environment overrides.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        let extension = path.extension().and_then(|ext| ext.to_str());
+        if !matches!(extension, Some("toml") | Some("json")) {
+            return Err(ConfigError::UnknownFormat {
+                path: path.to_path_buf(),
+                backtrace: Backtrace::capture(),
+            });
+        }
+
+        let contents = fs::read_to_string(path).map_err(|source| ConfigError::Io {
+            path: path.to_path_buf(),
+            source,
+            backtrace: Backtrace::capture(),
+        })?;
+        if extension == Some("toml") {
+            Self::from_toml(&contents).map_err(|source| ConfigError::Toml {
+                path: path.to_path_buf(),
+                source,
+                backtrace: Backtrace::capture(),
+            })
+        } else {
+            Self::from_json(&contents).map_err(|source| ConfigError::Json {
+                path: path.to_path_buf(),
+                source,
+                backtrace: Backtrace::capture(),
+            })
+        }
+    }
+
+    /// Applies the `DPDK_*` variables of the process environment, see
+    /// [`ConfigFile::apply_overrides`].
+    pub fn with_env_overrides(mut self) -> Result<Self, ConfigError> {
+        self.apply_overrides(std::env::vars())?;
+        Ok(self)
+    }
+
+    /// Overrides single settings, so core lists and devices can change between runs without
+    /// editing the file. Variables without the `DPDK_` prefix are ignored.
+    ///
+    /// | Variable | Example |
+    /// |---|---|
+    /// | `DPDK_CORES` | `1-3` or `0xe` |
+    /// | `DPDK_MAIN_LCORE` | `1` |
+    /// | `DPDK_SERVICE_CORE_MASK` | `0x3` |
+    /// | `DPDK_NO_PCI` | `true` |
+    /// | `DPDK_ALLOW`, `DPDK_BLOCK` | `0000:02:00.0,0000:03:00.0` |
+    /// | `DPDK_VDEVS` | `event_sw0;net_ring0` |
+    /// | `DPDK_MEMORY_CHANNELS` | `4` |
+    /// | `DPDK_TELEMETRY` | `false` |
+    /// | `DPDK_TRACE` | `lib.eal.*` |
+    /// | `DPDK_TRACE_DIR` | `/tmp/traces` |
+    /// | `DPDK_TRACE_BUFFER_SIZE` | `0x100000` |
+    /// | `DPDK_TRACE_MODE` | `discard` |
+    /// | `DPDK_IOVA_MODE` | `va` |
+    /// | `DPDK_PROC_TYPE` | `secondary` |
+    /// | `DPDK_NO_HUGE` | `true` |
+    /// | `DPDK_MEMORY_MB` | `512` |
+    /// | `DPDK_SOCKET_MEM` | `1024,0` |
+    /// | `DPDK_FILE_PREFIX` | `ci-1234` |
+    /// | `DPDK_HUGE_DIR` | `/mnt/huge` |
+    /// | `DPDK_IN_MEMORY` | `true` |
+    /// | `DPDK_LOG_LEVEL` | `pmd.net.*:debug;info` |
+    /// | `DPDK_BASE_VIRTADDR` | `0x100000000` |
+    /// | `DPDK_PORT<id>_<SETTING>` | `DPDK_PORT0_RX_QUEUES=4` |
+    ///
+    /// Port settings are `RX_QUEUES`, `TX_QUEUES`, `RX_RING_SIZE`, `TX_RING_SIZE`, `MTU`,
+    /// `PROMISCUOUS`, `RSS`, `RX_OFFLOADS`, `TX_OFFLOADS`, `NUM_MBUFS` and `MBUF_CACHE_SIZE`.
+    /// Setting one for a port missing from the file adds that port.
+    pub fn apply_overrides<I, K, V>(&mut self, vars: I) -> Result<(), ConfigError>
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        for (var, value) in vars {
+            let (var, value) = (var.as_ref(), value.as_ref());
+            let name = match var.strip_prefix(ENV_PREFIX) {
+                Some(name) => name,
+                None => continue,
+            };
+
+            let applied = match name.strip_prefix("PORT") {
+                Some(port_setting) => self.apply_port_override(port_setting, value),
+                None => apply_eal_override(&mut self.eal, name, value),
+            }
+            .map_err(|source| ConfigError::Override {
+                var: var.to_string(),
+                source: Box::new(source),
+                backtrace: Backtrace::capture(),
+            })?;
+            if !applied {
+                log::warn!("Ignoring unknown config variable {var}");
+            }
+        }
+        Ok(())
+    }
+
+    /// `setting` is the part after `DPDK_PORT`, for example `0_RX_QUEUES`.
+    fn apply_port_override(&mut self, setting: &str, value: &str) -> Result<bool, ConfigError> {
+        let (port, setting) = match setting.split_once('_') {
+            Some(split) => split,
+            None => return Ok(false),
+        };
+        let port: EthdevPortId = parse_int("port id", port)?;
+
+        let index = match self.ports.iter().position(|config| config.port == port) {
+            Some(index) => index,
+            None => {
+                self.ports.push(PortConfig::with_port(port));
+                self.ports.len() - 1
+            }
+        };
+        let config = &mut self.ports[index];
+
+        match setting {
+            "RX_QUEUES" => config.rx_queues = parse_int("queue count", value)?,
+            "TX_QUEUES" => config.tx_queues = parse_int("queue count", value)?,
+            "RX_RING_SIZE" => config.rx_ring_size = parse_int("ring size", value)?,
+            "TX_RING_SIZE" => config.tx_ring_size = parse_int("ring size", value)?,
+            "MTU" => config.mtu = Some(parse_int("MTU", value)?),
+            "PROMISCUOUS" => config.promiscuous = parse_bool(value)?,
+            "RSS" => config.rss = parse_bool(value)?,
+            "RX_OFFLOADS" => config.rx_offloads = parse_list(value),
+            "TX_OFFLOADS" => config.tx_offloads = parse_list(value),
+            "NUM_MBUFS" => config.num_mbufs = parse_int("mbuf count", value)?,
+            "MBUF_CACHE_SIZE" => config.mbuf_cache_size = parse_int("cache size", value)?,
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+/// Returns false if `name` is not an EAL setting.
+fn apply_eal_override(
+    eal: &mut DPDKConfig,
+    name: &str,
+    value: &str,
+) -> Result<bool, ConfigError> {
+    match name {
+        "CORES" => eal.cores = value.parse()?,
+        "MAIN_LCORE" => {
+            eal.main_lcore = optional(value).map(|v| parse_int("lcore", v)).transpose()?
+        }
+        "SERVICE_CORE_MASK" => {
+            eal.service_core_mask = optional(value)
+                .map(|v| parse_int("service core mask", v))
+                .transpose()?
+        }
+        "NO_PCI" => {
+            if parse_bool(value)? {
+                eal.pci_options = PCIOptions::NoPCI;
+            } else if let PCIOptions::NoPCI = eal.pci_options {
+                eal.pci_options = PCIOptions::PCI {
+                    blocked_devices: vec![],
+                    allowed_devices: vec![],
+                };
+            }
+        }
+        "ALLOW" | "BLOCK" => {
+            let addresses = parse_list(value)
+                .iter()
+                .map(|addr| addr.parse())
+                .collect::<Result<Vec<PCIAddress>, _>>()?;
+            if let PCIOptions::NoPCI = eal.pci_options {
+                eal.pci_options = PCIOptions::PCI {
+                    blocked_devices: vec![],
+                    allowed_devices: vec![],
+                };
+            }
+            if let PCIOptions::PCI {
+                blocked_devices,
+                allowed_devices,
+            } = &mut eal.pci_options
+            {
+                if name == "ALLOW" {
+                    *allowed_devices = addresses;
+                } else {
+                    *blocked_devices = addresses;
+                }
+            }
+        }
+        "VDEVS" => {
+            eal.virtual_devices = value
+                .split(';')
+                .filter(|vdev| !vdev.trim().is_empty())
+                .map(|vdev| vdev.parse())
+                .collect::<Result<_, _>>()?
+        }
+        "MEMORY_CHANNELS" => {
+            eal.num_memory_channels = optional(value)
+                .map(|v| parse_int("memory channel count", v))
+                .transpose()?
+        }
+        "TELEMETRY" => eal.enable_telemetry = parse_bool(value)?,
+        "TRACE" => eal.trace = optional(value).map(str::to_string),
+        "TRACE_DIR" => eal.trace_dir = optional(value).map(PathBuf::from),
+        "TRACE_BUFFER_SIZE" => {
+            eal.trace_buffer_size = optional(value)
+                .map(|v| parse_int("trace buffer size", v))
+                .transpose()?
+        }
+        "TRACE_MODE" => eal.trace_mode = optional(value).map(str::parse).transpose()?,
+        "IOVA_MODE" => eal.iova_mode = optional(value).map(str::parse).transpose()?,
+        "PROC_TYPE" => eal.proc_type = optional(value).map(str::parse).transpose()?,
+        "NO_HUGE" => eal.no_huge = parse_bool(value)?,
+        "MEMORY_MB" => {
+            eal.memory_mb = optional(value)
+                .map(|v| parse_int("memory size", v))
+                .transpose()?
+        }
+        "SOCKET_MEM" => {
+            eal.socket_mem = optional(value)
+                .map(|v| {
+                    parse_list(v)
+                        .iter()
+                        .map(|mb| parse_int("socket memory", mb))
+                        .collect::<Result<_, _>>()
+                })
+                .transpose()?
+        }
+        "FILE_PREFIX" => eal.file_prefix = optional(value).map(str::to_string),
+        "HUGE_DIR" => eal.huge_dir = optional(value).map(PathBuf::from),
+        "IN_MEMORY" => eal.in_memory = parse_bool(value)?,
+        "LOG_LEVEL" => {
+            eal.log_levels = value
+                .split(';')
+                .filter(|level| !level.trim().is_empty())
+                .map(|level| level.trim().parse())
+                .collect::<Result<_, _>>()?
+        }
+        "BASE_VIRTADDR" => {
+            eal.base_virtaddr = optional(value)
+                .map(|v| parse_int("base address", v))
+                .transpose()?
+        }
+        _ => return Ok(false),
+    }
+    Ok(true)
+}
+
+/// An empty variable unsets an optional setting.
+fn optional(value: &str) -> Option<&str> {
+    Some(value).filter(|value| !value.trim().is_empty())
+}
+
+fn parse_bool(value: &str) -> Result<bool, ConfigError> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Ok(true),
+        "0" | "false" | "no" | "off" => Ok(false),
+        _ => Err(ConfigError::invalid("boolean", value, "expected true or false")),
+    }
+}
+
+fn parse_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::config::{CoreConfig, IOVAMode};
+
+    const CONFIG: &str = r#"
+        [eal]
+        cores = "1-3"
+        service_core_mask = 3
+        iova_mode = "pa"
+        pci_options = { mode = "pci", allowed_devices = ["0000:02:00.0"] }
+        virtual_devices = [{ driver = "event_sw", options = { credit_quanta = "64" } }]
+
+        [[ports]]
+        port = 0
+        rx_queues = 2
+        rx_offloads = ["IPV4_CKSUM"]
+    "#;
+
+    #[test]
+    fn toml_with_overrides() {
+        let mut config = ConfigFile::from_toml(CONFIG).unwrap();
+        assert!(matches!(&config.eal.cores, CoreConfig::List(cores) if cores == &[1, 2, 3]));
+        assert!(matches!(config.eal.iova_mode, Some(IOVAMode::PA)));
+        assert_eq!(config.ports[0].rx_queues, 2);
+        assert_eq!(config.ports[0].rx_ring_size, 1024);
+        assert_eq!(
+            config.eal.to_string(),
+            "-l 1,2,3 -s 3 -a 0000:02:00.0 --vdev=event_sw0,credit_quanta=64 --no-telemetry \
+             --iova-mode=pa"
+        );
+
+        config
+            .apply_overrides([
+                ("DPDK_CORES", "0xf"),
+                ("DPDK_VDEVS", "event_sw1;net_ring0,nodeaction=r0:0:CREATE"),
+                ("DPDK_PORT1_TX_QUEUES", "4"),
+                ("HOME", "/root"),
+            ])
+            .unwrap();
+        assert!(matches!(config.eal.cores, CoreConfig::Mask(0xf)));
+        assert_eq!(config.eal.virtual_devices[0].id, 1);
+        assert_eq!(config.eal.virtual_devices[1].driver, "net_ring");
+        assert_eq!(config.ports[1].port, 1);
+        assert_eq!(config.ports[1].tx_queues, 4);
+
+        let json = serde_json::to_string(&config).unwrap();
+        let reloaded = ConfigFile::from_json(&json).unwrap();
+        assert_eq!(reloaded.eal.to_string(), config.eal.to_string());
+        assert_eq!(reloaded.ports, config.ports);
+
+        assert!(config
+            .apply_overrides([("DPDK_IOVA_MODE", "physical")])
+            .is_err());
+    }
+}
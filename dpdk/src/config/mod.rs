@@ -0,0 +1,799 @@
+use std::{
+    backtrace::Backtrace,
+    collections::{BTreeMap, HashMap},
+    ffi::CString,
+    fmt::Display,
+    path::PathBuf,
+    str::FromStr,
+};
+
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    eal::{self, LCoreId, RteErrnoValue},
+    error::DpdkError,
+    logging::LogLevel,
+    trace::TraceMode,
+};
+
+pub mod file;
+pub mod port;
+pub mod validate;
+pub mod vdev;
+
+pub use file::ConfigFile;
+pub use port::PortConfig;
+pub use vdev::VdevOptions;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("Invalid {what} {value:?}: {reason}")]
+    Invalid {
+        what: &'static str,
+        value: String,
+        reason: String,
+        backtrace: Backtrace,
+    },
+    #[error("Unable to read config file {path}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+        backtrace: Backtrace,
+    },
+    #[error("Invalid TOML in config file {path}")]
+    Toml {
+        path: PathBuf,
+        source: toml::de::Error,
+        backtrace: Backtrace,
+    },
+    #[error("Invalid JSON in config file {path}")]
+    Json {
+        path: PathBuf,
+        source: serde_json::Error,
+        backtrace: Backtrace,
+    },
+    #[error("Invalid value of environment variable {var}")]
+    Override {
+        var: String,
+        source: Box<ConfigError>,
+        backtrace: Backtrace,
+    },
+    #[error("Unknown format of config file {path}, expected a .toml or .json extension")]
+    UnknownFormat { path: PathBuf, backtrace: Backtrace },
+}
+
+impl ConfigError {
+    pub(crate) fn invalid(what: &'static str, value: &str, reason: impl ToString) -> Self {
+        Self::Invalid {
+            what,
+            value: value.to_string(),
+            reason: reason.to_string(),
+            backtrace: Backtrace::capture(),
+        }
+    }
+}
+
+/// Parses decimal or `0x` prefixed hexadecimal numbers.
+pub(crate) fn parse_int<T: num::Num>(what: &'static str, value: &str) -> Result<T, ConfigError> {
+    let trimmed = value.trim();
+    let parsed = match trimmed
+        .strip_prefix("0x")
+        .or_else(|| trimmed.strip_prefix("0X"))
+    {
+        Some(hex) => T::from_str_radix(hex, 16),
+        None => T::from_str_radix(trimmed, 10),
+    };
+    parsed.map_err(|_| ConfigError::invalid(what, value, "not a number"))
+}
+
+/// Cores are either a mask, as an integer or a `0x` prefixed string, a list like `"1,2,4-6"` or
+/// a mapping like `"1@2,3@(4,5)"`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "CoreConfigRepr", into = "String")]
+pub enum CoreConfig {
+    Mask(u64),
+    List(Vec<LCoreId>),
+    /// The CPUs each lcore thread may run on, an empty set means the CPU with the lcore id
+    Mapping(BTreeMap<LCoreId, Vec<u32>>),
+}
+
+impl CoreConfig {
+    /// The lcore ids, in ascending order.
+    pub fn lcores(&self) -> Vec<LCoreId> {
+        match self {
+            CoreConfig::Mask(mask) => (0..u64::BITS as LCoreId)
+                .filter(|bit| mask & (1 << bit) != 0)
+                .collect(),
+            CoreConfig::List(list) => list.iter().copied().sorted().dedup().collect(),
+            CoreConfig::Mapping(mapping) => mapping.keys().copied().collect(),
+        }
+    }
+
+    /// The CPUs the lcore threads run on, in ascending order.
+    pub fn cpus(&self) -> Vec<u32> {
+        match self {
+            CoreConfig::Mapping(mapping) => mapping
+                .iter()
+                .flat_map(|(lcore, cpus)| match cpus.as_slice() {
+                    [] => vec![*lcore as u32],
+                    cpus => cpus.to_vec(),
+                })
+                .sorted()
+                .dedup()
+                .collect(),
+            _ => self.lcores().into_iter().map(|lcore| lcore as u32).collect(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum CoreConfigRepr {
+    Mask(u64),
+    List(Vec<LCoreId>),
+    Text(String),
+}
+
+impl TryFrom<CoreConfigRepr> for CoreConfig {
+    type Error = ConfigError;
+
+    fn try_from(repr: CoreConfigRepr) -> Result<Self, Self::Error> {
+        match repr {
+            CoreConfigRepr::Mask(mask) => Ok(CoreConfig::Mask(mask)),
+            CoreConfigRepr::List(list) => Ok(CoreConfig::List(list)),
+            CoreConfigRepr::Text(text) => text.parse(),
+        }
+    }
+}
+
+impl FromStr for CoreConfig {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.starts_with("0x") || s.starts_with("0X") {
+            return parse_int("core mask", s).map(CoreConfig::Mask);
+        }
+        if !s.contains('@') {
+            return parse_id_list("core list", s).map(CoreConfig::List);
+        }
+
+        let mut mapping = BTreeMap::new();
+        for entry in split_outside_parens(s) {
+            let (lcores, cpus) = match entry.split_once('@') {
+                Some((lcores, cpus)) => (lcores, parse_id_group("lcore mapping", cpus)?),
+                None => (entry, vec![]),
+            };
+            for lcore in parse_id_group("lcore mapping", lcores)? {
+                mapping.insert(lcore, cpus.clone());
+            }
+        }
+        Ok(CoreConfig::Mapping(mapping))
+    }
+}
+
+/// Parses lists like `"1,2,4-6"`, the format of sysfs CPU lists too.
+pub(crate) fn parse_id_list<T>(what: &'static str, s: &str) -> Result<Vec<T>, ConfigError>
+where
+    T: num::Num + PartialOrd + Copy,
+    std::ops::RangeInclusive<T>: Iterator<Item = T>,
+{
+    let mut ids = vec![];
+    for part in s.split(',') {
+        let part = part.trim();
+        match part.split_once('-') {
+            Some((first, last)) => {
+                let first: T = parse_int(what, first)?;
+                let last: T = parse_int(what, last)?;
+                if first > last {
+                    return Err(ConfigError::invalid(
+                        what,
+                        s,
+                        format!("range {part} is reversed"),
+                    ));
+                }
+                ids.extend(first..=last);
+            }
+            None => ids.push(parse_int(what, part)?),
+        }
+    }
+    Ok(ids)
+}
+
+/// Parses a single id or range, or a list in parentheses like `"(1,2,4-6)"`.
+fn parse_id_group<T>(what: &'static str, s: &str) -> Result<Vec<T>, ConfigError>
+where
+    T: num::Num + PartialOrd + Copy,
+    std::ops::RangeInclusive<T>: Iterator<Item = T>,
+{
+    let s = s.trim();
+    match s.strip_prefix('(').and_then(|s| s.strip_suffix(')')) {
+        Some(list) => parse_id_list(what, list),
+        None if s.contains(',') => Err(ConfigError::invalid(
+            what,
+            s,
+            "lists must be in parentheses",
+        )),
+        None => parse_id_list(what, s),
+    }
+}
+
+fn split_outside_parens(s: &str) -> Vec<&str> {
+    let mut parts = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&s[start..]);
+    parts
+}
+
+/// The format of the matching EAL option, a `0x` prefixed mask, `"1,2,3"` or
+/// `"1@2,3@(4,5)"`.
+impl Display for CoreConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CoreConfig::Mask(mask) => write!(f, "{:#x}", mask),
+            CoreConfig::List(list) => write!(f, "{}", list.iter().join(",")),
+            CoreConfig::Mapping(mapping) => {
+                let entries = mapping.iter().map(|(lcore, cpus)| match cpus.as_slice() {
+                    [] => lcore.to_string(),
+                    [cpu] => format!("{lcore}@{cpu}"),
+                    cpus => format!("{lcore}@({})", cpus.iter().join(",")),
+                });
+                write!(f, "{}", entries.format(","))
+            }
+        }
+    }
+}
+
+impl From<CoreConfig> for String {
+    fn from(cores: CoreConfig) -> Self {
+        cores.to_string()
+    }
+}
+
+/// Written as `"0000:02:00.0"`, the domain may be left out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct PCIAddress {
+    pub domain: u16,
+    pub bus: u8,
+    pub device: u8,   // 5 bits
+    pub function: u8, // 3 bits
+}
+
+impl From<dpdk_sys::rte_pci_addr> for PCIAddress {
+    fn from(addr: dpdk_sys::rte_pci_addr) -> Self {
+        Self {
+            domain: addr.domain as u16,
+            bus: addr.bus,
+            device: addr.devid,
+            function: addr.function,
+        }
+    }
+}
+
+impl Display for PCIAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:04x}:{:02x}:{:02x}.{:1x}",
+            self.domain, self.bus, self.device, self.function
+        )
+    }
+}
+
+impl FromStr for PCIAddress {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            ConfigError::invalid("PCI address", s, "expected [domain:]bus:device.function")
+        };
+        let hex = |part: &str, max: u32| {
+            u32::from_str_radix(part, 16)
+                .ok()
+                .filter(|value| *value <= max)
+                .ok_or_else(invalid)
+        };
+
+        let (rest, function) = s.trim().rsplit_once('.').ok_or_else(invalid)?;
+        let parts = rest.split(':').collect_vec();
+        let (domain, bus, device) = match parts.as_slice() {
+            [bus, device] => (0, hex(bus, 0xff)?, hex(device, 0x1f)?),
+            [domain, bus, device] => (hex(domain, 0xffff)?, hex(bus, 0xff)?, hex(device, 0x1f)?),
+            _ => return Err(invalid()),
+        };
+        Ok(Self {
+            domain: domain as u16,
+            bus: bus as u8,
+            device: device as u8,
+            function: hex(function, 0x7)? as u8,
+        })
+    }
+}
+
+impl TryFrom<String> for PCIAddress {
+    type Error = ConfigError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<PCIAddress> for String {
+    fn from(addr: PCIAddress) -> Self {
+        addr.to_string()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VirtualDevice {
+    pub driver: String,
+    #[serde(default)]
+    pub id: u64,
+    #[serde(default)]
+    pub options: HashMap<String, String>,
+}
+
+/// Parses the `--vdev` syntax, for example `"event_sw0,credit_quanta=64"`.
+impl FromStr for VirtualDevice {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.trim().split(',');
+        let name = parts.next().unwrap_or_default();
+        let driver = name.trim_end_matches(|c: char| c.is_ascii_digit());
+        if driver.is_empty() {
+            return Err(ConfigError::invalid("virtual device", s, "missing driver name"));
+        }
+        let id = match &name[driver.len()..] {
+            "" => 0,
+            id => parse_int("virtual device", id)?,
+        };
+
+        let mut options = HashMap::new();
+        for option in parts {
+            let (key, value) = option.split_once('=').unwrap_or((option, ""));
+            options.insert(key.to_string(), value.to_string());
+        }
+
+        Ok(Self {
+            driver: driver.to_string(),
+            id,
+            options,
+        })
+    }
+}
+
+impl VirtualDevice {
+    pub fn with_driver(driver: impl ToString) -> VirtualDevice {
+        Self {
+            driver: driver.to_string(),
+            id: 0,
+            options: Default::default(),
+        }
+    }
+
+    /// A device of the driver of `options`, for example `VirtualDevice::from_options(0,
+    /// &PcapVdev { .. })`, after checking the options.
+    pub fn from_options<O: VdevOptions>(id: u64, options: &O) -> Result<Self, ConfigError> {
+        options.validate()?;
+        Ok(Self {
+            driver: O::DRIVER.to_string(),
+            id,
+            options: options
+                .options()
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect(),
+        })
+    }
+}
+
+/// The `--vdev` devargs, options are sorted by key and flags with an empty value are written
+/// without `=`.
+impl Display for VirtualDevice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.driver, self.id)?;
+        for (key, value) in self.options.iter().sorted() {
+            if value.is_empty() {
+                write!(f, ",{}", key)?;
+            } else {
+                write!(f, ",{}={}", key, value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "mode")]
+pub enum PCIOptions {
+    #[serde(rename = "no_pci")]
+    NoPCI,
+    #[serde(rename = "pci")]
+    PCI {
+        #[serde(default)]
+        blocked_devices: Vec<PCIAddress>,
+        #[serde(default)]
+        allowed_devices: Vec<PCIAddress>,
+    },
+}
+
+impl Default for PCIOptions {
+    fn default() -> Self {
+        Self::NoPCI
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MultiprocessingProcType {
+    Primary,
+    Secondary,
+    Auto,
+}
+
+impl Display for MultiprocessingProcType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MultiprocessingProcType::Primary => write!(f, "primary"),
+            MultiprocessingProcType::Secondary => write!(f, "secondary"),
+            MultiprocessingProcType::Auto => write!(f, "auto"),
+        }
+    }
+}
+
+impl FromStr for MultiprocessingProcType {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "primary" => Ok(Self::Primary),
+            "secondary" => Ok(Self::Secondary),
+            "auto" => Ok(Self::Auto),
+            _ => Err(ConfigError::invalid(
+                "process type",
+                s,
+                "expected primary, secondary or auto",
+            )),
+        }
+    }
+}
+
+impl Default for MultiprocessingProcType {
+    fn default() -> Self {
+        Self::Auto
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IOVAMode {
+    PA,
+    VA,
+}
+
+impl FromStr for IOVAMode {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "pa" => Ok(Self::PA),
+            "va" => Ok(Self::VA),
+            _ => Err(ConfigError::invalid("IOVA mode", s, "expected pa or va")),
+        }
+    }
+}
+
+/// A `--log-level` option, the level of the logtypes matching the `pattern` glob or the global
+/// level if there is no pattern. The EAL matches the glob with `fnmatch`, its other form with a
+/// regex, `--log-level=regex,level`, is not supported here.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogLevelConfig {
+    #[serde(default)]
+    pub pattern: Option<String>,
+    pub level: LogLevel,
+}
+
+/// Parses the `--log-level` syntax, `"pmd.net.*:debug"` or `"info"`.
+impl FromStr for LogLevelConfig {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.rsplit_once(':') {
+            Some((pattern, level)) => Ok(Self {
+                pattern: Some(pattern.to_string()),
+                level: level.parse()?,
+            }),
+            None => Ok(Self {
+                pattern: None,
+                level: s.parse()?,
+            }),
+        }
+    }
+}
+
+impl Display for LogLevelConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.pattern {
+            Some(pattern) => write!(f, "{}:{}", pattern, self.level),
+            None => write!(f, "{}", self.level),
+        }
+    }
+}
+
+/// Missing fields of a config file take their value from [`DPDKConfig::default`].
+#[derive(Debug, Builder, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DPDKConfig {
+    pub cores: CoreConfig,
+    pub main_lcore: Option<LCoreId>,
+    pub service_core_mask: Option<u64>,
+    pub pci_options: PCIOptions,
+    pub virtual_devices: Vec<VirtualDevice>,
+    pub num_memory_channels: Option<u64>,
+    pub enable_telemetry: bool,
+    /// Regex of the tracepoints to enable, tracing is off unless this is set
+    pub trace: Option<String>,
+    /// Where [`crate::trace::save`] writes to, `$HOME/dpdk-traces` by default
+    #[builder(default)]
+    pub trace_dir: Option<PathBuf>,
+    /// Size of the trace buffer of each thread in bytes
+    #[builder(default)]
+    pub trace_buffer_size: Option<u64>,
+    #[builder(default)]
+    pub trace_mode: Option<TraceMode>,
+    pub iova_mode: Option<IOVAMode>,
+    #[builder(default)]
+    pub proc_type: Option<MultiprocessingProcType>,
+    /// Use anonymous memory instead of hugepages, which also rules out secondary processes
+    #[builder(default)]
+    pub no_huge: bool,
+    /// Memory to preallocate in MB, split across sockets
+    #[builder(default)]
+    pub memory_mb: Option<u64>,
+    /// Memory to preallocate in MB on each socket, by socket id
+    #[builder(default)]
+    pub socket_mem: Option<Vec<u64>>,
+    /// Prefix of the hugepage files and runtime directory, processes sharing memory need the
+    /// same one
+    #[builder(default)]
+    pub file_prefix: Option<String>,
+    #[builder(default)]
+    pub huge_dir: Option<PathBuf>,
+    /// Create no shared files at all, like `no_huge` this rules out secondary processes
+    #[builder(default)]
+    pub in_memory: bool,
+    #[builder(default)]
+    pub log_levels: Vec<LogLevelConfig>,
+    /// Where the EAL tries to map its memory, secondary processes need the same mapping
+    #[builder(default)]
+    pub base_virtaddr: Option<u64>,
+}
+
+impl DPDKConfig {
+    /// Runs without hugepages under a file prefix unique to this process, so tests can run on
+    /// machines without hugepages and next to each other.
+    pub fn hugepage_free(mut self) -> Self {
+        self.no_huge = true;
+        self.file_prefix = Some(format!("rte-{}", std::process::id()));
+        self
+    }
+
+    /// The EAL arguments, starting with the program name.
+    pub fn args(&self) -> Vec<String> {
+        let program = std::env::args().next().unwrap_or_else(|| "dpdk".to_string());
+        let mut args = vec![program];
+
+        match &self.cores {
+            CoreConfig::Mask(mask) => args.extend(["-c".to_string(), format!("{:x}", mask)]),
+            CoreConfig::List(_) => args.extend(["-l".to_string(), self.cores.to_string()]),
+            CoreConfig::Mapping(_) => args.push(format!("--lcores={}", self.cores)),
+        }
+
+        if let Some(val) = self.main_lcore {
+            args.push(format!("--main-lcore={}", val));
+        }
+
+        if let Some(service_core_mask) = self.service_core_mask {
+            args.extend(["-s".to_string(), format!("{:x}", service_core_mask)]);
+        }
+
+        match &self.pci_options {
+            PCIOptions::NoPCI => args.push("--no-pci".to_string()),
+            PCIOptions::PCI {
+                blocked_devices,
+                allowed_devices,
+            } => {
+                for blocked_device in blocked_devices {
+                    args.extend(["-b".to_string(), blocked_device.to_string()]);
+                }
+
+                for allowed_device in allowed_devices {
+                    args.extend(["-a".to_string(), allowed_device.to_string()]);
+                }
+            }
+        }
+
+        for virtual_device in &self.virtual_devices {
+            args.push(format!("--vdev={}", virtual_device));
+        }
+
+        if let Some(memory_channels) = self.num_memory_channels {
+            args.extend(["-n".to_string(), memory_channels.to_string()]);
+        }
+
+        if self.no_huge {
+            args.push("--no-huge".to_string());
+        }
+
+        if let Some(memory_mb) = self.memory_mb {
+            args.extend(["-m".to_string(), memory_mb.to_string()]);
+        }
+
+        if let Some(socket_mem) = &self.socket_mem {
+            args.push(format!("--socket-mem={}", socket_mem.iter().join(",")));
+        }
+
+        if let Some(file_prefix) = &self.file_prefix {
+            args.push(format!("--file-prefix={}", file_prefix));
+        }
+
+        if let Some(huge_dir) = &self.huge_dir {
+            args.push(format!("--huge-dir={}", huge_dir.display()));
+        }
+
+        if self.in_memory {
+            args.push("--in-memory".to_string());
+        }
+
+        if let Some(base_virtaddr) = self.base_virtaddr {
+            args.push(format!("--base-virtaddr={:#x}", base_virtaddr));
+        }
+
+        for log_level in &self.log_levels {
+            args.push(format!("--log-level={}", log_level));
+        }
+
+        if self.enable_telemetry {
+            args.push("--telemetry".to_string());
+        } else {
+            args.push("--no-telemetry".to_string());
+        }
+
+        if let Some(trace) = &self.trace {
+            args.push(format!("--trace={}", trace));
+        }
+
+        if let Some(trace_dir) = &self.trace_dir {
+            args.push(format!("--trace-dir={}", trace_dir.display()));
+        }
+
+        if let Some(trace_buffer_size) = self.trace_buffer_size {
+            args.push(format!("--trace-bufsz={}", trace_buffer_size));
+        }
+
+        if let Some(trace_mode) = self.trace_mode {
+            args.push(format!("--trace-mode={}", trace_mode));
+        }
+
+        if let Some(iova_mode) = &self.iova_mode {
+            match iova_mode {
+                IOVAMode::PA => args.push("--iova-mode=pa".to_string()),
+                IOVAMode::VA => args.push("--iova-mode=va".to_string()),
+            }
+        }
+
+        if let Some(proc_type) = &self.proc_type {
+            args.push(format!("--proc-type={}", proc_type));
+        }
+
+        args
+    }
+
+    /// [`DPDKConfig::args`] as passed to `rte_eal_init`. Fails if an argument contains a NUL
+    /// byte.
+    pub fn argv(&self) -> Result<Vec<CString>, DpdkError> {
+        self.args()
+            .into_iter()
+            .map(|arg| {
+                CString::new(arg.as_str()).map_err(|_| {
+                    DpdkError::from_errno("rte_eal_init", RteErrnoValue::EINVAL as i32)
+                        .with_name(arg)
+                })
+            })
+            .collect()
+    }
+
+    /// Initializes the EAL with this config. The returned handle cleans up the EAL when dropped.
+    pub fn apply(self) -> Result<eal::Eal, DpdkError> {
+        let args = self.argv()?;
+        log::debug!("Initializing EAL with {}", self);
+        eal::Eal::init(&args)
+    }
+}
+
+impl Default for DPDKConfig {
+    fn default() -> Self {
+        Self {
+            cores: CoreConfig::Mask(1),
+            main_lcore: Default::default(),
+            service_core_mask: Default::default(),
+            pci_options: Default::default(),
+            virtual_devices: Default::default(),
+            num_memory_channels: Default::default(),
+            enable_telemetry: false,
+            trace: None,
+            trace_dir: None,
+            trace_buffer_size: None,
+            trace_mode: None,
+            iova_mode: None,
+            proc_type: None,
+            no_huge: false,
+            memory_mb: None,
+            socket_mem: None,
+            file_prefix: None,
+            huge_dir: None,
+            in_memory: false,
+            log_levels: vec![],
+            base_virtaddr: None,
+        }
+    }
+}
+
+/// The EAL arguments without the program name, arguments containing whitespace are quoted.
+impl Display for DPDKConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let args = self.args().into_iter().skip(1).map(|arg| {
+            if arg.contains(char::is_whitespace) {
+                format!("{:?}", arg)
+            } else {
+                arg
+            }
+        });
+        write!(f, "{}", args.format(" "))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn argv() {
+        let config = DPDKConfig {
+            cores: "0@1,1@(2-3)".parse().unwrap(),
+            virtual_devices: vec!["net_pcap0,iface=my nic".parse().unwrap()],
+            log_levels: vec!["pmd.net.*:debug".parse().unwrap()],
+            file_prefix: Some("ci".to_string()),
+            no_huge: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            &config.args()[1..],
+            [
+                "--lcores=0@1,1@(2,3)",
+                "--no-pci",
+                "--vdev=net_pcap0,iface=my nic",
+                "--no-huge",
+                "--file-prefix=ci",
+                "--log-level=pmd.net.*:debug",
+                "--no-telemetry",
+            ]
+        );
+        assert_eq!(config.argv().unwrap().len(), config.args().len());
+    }
+}
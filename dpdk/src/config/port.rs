@@ -0,0 +1,97 @@
+use std::ffi::CStr;
+
+use dpdk_sys::{rte_eth_dev_rx_offload_name, rte_eth_dev_tx_offload_name};
+use serde::{Deserialize, Serialize};
+
+use crate::device::eth::{
+    config::{DEFAULT_MBUFS_PER_RX_QUEUE, DEFAULT_MBUF_CACHE_SIZE},
+    dev::EthdevPortId,
+    info::DEFAULT_RING_SIZE,
+};
+
+use super::ConfigError;
+
+/// Queue, ring and offload settings of one ethernet port.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PortConfig {
+    pub port: EthdevPortId,
+    pub rx_queues: u16,
+    pub tx_queues: u16,
+    pub rx_ring_size: u16,
+    pub tx_ring_size: u16,
+    pub mtu: Option<u16>,
+    pub promiscuous: bool,
+    pub rss: bool,
+    /// Offload names as DPDK prints them, for example `"VLAN_STRIP"` or `"IPV4_CKSUM"`
+    pub rx_offloads: Vec<String>,
+    pub tx_offloads: Vec<String>,
+    /// Size of the mbuf pool backing the RX queues
+    pub num_mbufs: u32,
+    pub mbuf_cache_size: u32,
+}
+
+impl Default for PortConfig {
+    fn default() -> Self {
+        Self {
+            port: 0,
+            rx_queues: 1,
+            tx_queues: 1,
+            rx_ring_size: DEFAULT_RING_SIZE,
+            tx_ring_size: DEFAULT_RING_SIZE,
+            mtu: None,
+            promiscuous: false,
+            rss: false,
+            rx_offloads: vec![],
+            tx_offloads: vec![],
+            num_mbufs: DEFAULT_MBUFS_PER_RX_QUEUE,
+            mbuf_cache_size: DEFAULT_MBUF_CACHE_SIZE,
+        }
+    }
+}
+
+impl PortConfig {
+    pub fn with_port(port: EthdevPortId) -> Self {
+        Self {
+            port,
+            ..Default::default()
+        }
+    }
+
+    /// The `RTE_ETH_RX_OFFLOAD_*` flags of [`PortConfig::rx_offloads`].
+    pub fn rx_offload_flags(&self) -> Result<u64, ConfigError> {
+        offload_flags("RX offload", &self.rx_offloads, |flag| unsafe {
+            rte_eth_dev_rx_offload_name(flag)
+        })
+    }
+
+    /// The `RTE_ETH_TX_OFFLOAD_*` flags of [`PortConfig::tx_offloads`].
+    pub fn tx_offload_flags(&self) -> Result<u64, ConfigError> {
+        offload_flags("TX offload", &self.tx_offloads, |flag| unsafe {
+            rte_eth_dev_tx_offload_name(flag)
+        })
+    }
+}
+
+/// DPDK only maps flags to names, so this looks for the bit whose name matches.
+fn offload_flags(
+    what: &'static str,
+    names: &[String],
+    name_of: impl Fn(u64) -> *const libc::c_char,
+) -> Result<u64, ConfigError> {
+    let mut flags = 0;
+    for name in names {
+        let flag = (0..u64::BITS)
+            .map(|bit| 1u64 << bit)
+            .find(|&flag| {
+                let known = name_of(flag);
+                !known.is_null()
+                    && unsafe { CStr::from_ptr(known) }
+                        .to_string_lossy()
+                        .eq_ignore_ascii_case(name)
+            })
+            .ok_or_else(|| ConfigError::invalid(what, name, "unknown offload"))?;
+        flags |= flag;
+    }
+    Ok(flags)
+}
@@ -0,0 +1,287 @@
+use std::{backtrace::Backtrace, fmt::Display, path::PathBuf};
+
+use itertools::Itertools;
+
+use crate::{device::pci::DPDK_COMPATIBLE_DRIVERS, eal::LCoreId, sysfs::Host};
+
+use super::{DPDKConfig, PCIAddress, PCIOptions};
+
+/// A problem that would make `rte_eal_init` fail, or leave the application without the cores
+/// or devices it asked for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Diagnostic {
+    CpuMissing { cpu: u32 },
+    CpuOffline { cpu: u32 },
+    MainLcoreNotInSet { main_lcore: LCoreId },
+    /// The main lcore can not be a service core
+    ServiceCoreIsMain { lcore: LCoreId },
+    /// Service cores are taken from the configured cores, the EAL ignores the others
+    ServiceCoreNotInSet { lcore: LCoreId },
+    NoHugetlbfsMount,
+    HugeDirNotMounted { huge_dir: PathBuf },
+    NotEnoughHugepages { requested_mb: u64, free_mb: u64 },
+    PciDeviceMissing { address: PCIAddress },
+    PciDeviceUnbound { address: PCIAddress },
+    PciDriverIncompatible { address: PCIAddress, driver: String },
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Diagnostic::CpuMissing { cpu } => write!(f, "CPU {cpu} does not exist"),
+            Diagnostic::CpuOffline { cpu } => write!(f, "CPU {cpu} is offline"),
+            Diagnostic::MainLcoreNotInSet { main_lcore } => {
+                write!(f, "Main lcore {main_lcore} is not one of the configured cores")
+            }
+            Diagnostic::ServiceCoreIsMain { lcore } => {
+                write!(f, "Service core {lcore} is the main lcore")
+            }
+            Diagnostic::ServiceCoreNotInSet { lcore } => {
+                write!(f, "Service core {lcore} is not one of the configured cores")
+            }
+            Diagnostic::NoHugetlbfsMount => write!(f, "No hugetlbfs is mounted"),
+            Diagnostic::HugeDirNotMounted { huge_dir } => {
+                write!(f, "{} is not a hugetlbfs mount", huge_dir.display())
+            }
+            Diagnostic::NotEnoughHugepages {
+                requested_mb: 0, ..
+            } => write!(f, "No hugepages are free"),
+            Diagnostic::NotEnoughHugepages {
+                requested_mb,
+                free_mb,
+            } => write!(
+                f,
+                "{requested_mb} MB of hugepages requested but only {free_mb} MB are free"
+            ),
+            Diagnostic::PciDeviceMissing { address } => {
+                write!(f, "PCI device {address} does not exist")
+            }
+            Diagnostic::PciDeviceUnbound { address } => {
+                write!(f, "PCI device {address} is not bound to a driver")
+            }
+            Diagnostic::PciDriverIncompatible { address, driver } => write!(
+                f,
+                "PCI device {address} is bound to {driver}, expected one of {}",
+                DPDK_COMPATIBLE_DRIVERS.join(", ")
+            ),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("EAL config does not fit this host: {}", .diagnostics.iter().join("; "))]
+pub struct ValidationError {
+    pub diagnostics: Vec<Diagnostic>,
+    backtrace: Backtrace,
+}
+
+impl DPDKConfig {
+    /// Checks the config against this machine before `rte_eal_init`, which only reports an
+    /// errno when it fails.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        self.validate_on(&Host::default())
+    }
+
+    /// Like [`DPDKConfig::validate`], checks that can not read what they need from `host` are
+    /// skipped.
+    pub fn validate_on(&self, host: &Host) -> Result<(), ValidationError> {
+        let mut diagnostics = vec![];
+        self.check_cores(host, &mut diagnostics);
+        self.check_hugepages(host, &mut diagnostics);
+        self.check_pci_devices(host, &mut diagnostics);
+
+        if diagnostics.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationError {
+                diagnostics,
+                backtrace: Backtrace::capture(),
+            })
+        }
+    }
+
+    fn check_cores(&self, host: &Host, diagnostics: &mut Vec<Diagnostic>) {
+        let present = host.cpu_list("present");
+        let online = host.cpu_list("online");
+        let check_cpu = |cpu: u32| {
+            if present.as_ref().map_or(false, |present| !present.contains(&cpu)) {
+                Some(Diagnostic::CpuMissing { cpu })
+            } else if online.as_ref().map_or(false, |online| !online.contains(&cpu)) {
+                Some(Diagnostic::CpuOffline { cpu })
+            } else {
+                None
+            }
+        };
+        diagnostics.extend(self.cores.cpus().into_iter().filter_map(check_cpu));
+
+        let lcores = self.cores.lcores();
+        // Without --main-lcore the EAL picks the first configured core
+        let main_lcore = self.main_lcore.or_else(|| lcores.first().copied());
+        if let Some(main_lcore) = self.main_lcore {
+            if !lcores.contains(&main_lcore) {
+                diagnostics.push(Diagnostic::MainLcoreNotInSet { main_lcore });
+            }
+        }
+
+        let service_lcores = self
+            .service_core_mask
+            .map(|mask| super::CoreConfig::Mask(mask).lcores())
+            .unwrap_or_default();
+        for lcore in service_lcores {
+            if Some(lcore) == main_lcore {
+                diagnostics.push(Diagnostic::ServiceCoreIsMain { lcore });
+            } else if !lcores.contains(&lcore) {
+                diagnostics.push(Diagnostic::ServiceCoreNotInSet { lcore });
+                // Outside of the core set the lcore runs on the CPU with its id
+                diagnostics.extend(check_cpu(lcore as u32));
+            }
+        }
+    }
+
+    fn check_hugepages(&self, host: &Host, diagnostics: &mut Vec<Diagnostic>) {
+        if self.no_huge {
+            return;
+        }
+
+        if let Some(mounts) = host.hugetlbfs_mounts() {
+            match &self.huge_dir {
+                Some(huge_dir) if !mounts.contains(huge_dir) => {
+                    diagnostics.push(Diagnostic::HugeDirNotMounted {
+                        huge_dir: huge_dir.clone(),
+                    })
+                }
+                None if mounts.is_empty() && !self.in_memory => {
+                    diagnostics.push(Diagnostic::NoHugetlbfsMount)
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(free_mb) = host.free_hugepage_mb() {
+            let requested_mb = self
+                .socket_mem
+                .as_ref()
+                .map(|socket_mem| socket_mem.iter().sum())
+                .or(self.memory_mb)
+                .unwrap_or(0);
+            if free_mb == 0 || free_mb < requested_mb {
+                diagnostics.push(Diagnostic::NotEnoughHugepages {
+                    requested_mb,
+                    free_mb,
+                });
+            }
+        }
+    }
+
+    fn check_pci_devices(&self, host: &Host, diagnostics: &mut Vec<Diagnostic>) {
+        let allowed_devices = match &self.pci_options {
+            PCIOptions::PCI {
+                allowed_devices, ..
+            } => allowed_devices,
+            PCIOptions::NoPCI => return,
+        };
+
+        for &address in allowed_devices {
+            if !host.pci_device_path(address).exists() {
+                diagnostics.push(Diagnostic::PciDeviceMissing { address });
+                continue;
+            }
+            match host.pci_driver(address) {
+                None => diagnostics.push(Diagnostic::PciDeviceUnbound { address }),
+                Some(driver) if !DPDK_COMPATIBLE_DRIVERS.contains(&driver.as_str()) => {
+                    diagnostics.push(Diagnostic::PciDriverIncompatible { address, driver })
+                }
+                Some(_) => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{fs, os::unix::fs::symlink};
+
+    use super::*;
+    use crate::{config::CoreConfig, sysfs::TestDir};
+
+    #[test]
+    fn fake_sysfs() {
+        let dir = TestDir::new("validate");
+        let root = dir.path();
+        dir.write("sys/devices/system/cpu/present", "0-3\n");
+        dir.write("sys/devices/system/cpu/online", "0-2\n");
+        dir.write("sys/kernel/mm/hugepages/hugepages-2048kB/free_hugepages", "256\n");
+        dir.write("proc/mounts", "hugetlbfs /dev/hugepages hugetlbfs rw 0 0\n");
+        dir.write("sys/bus/pci/drivers/vfio-pci/.keep", "");
+        dir.write("sys/bus/pci/drivers/ixgbe/.keep", "");
+        for (address, driver) in [("0000:02:00.0", "vfio-pci"), ("0000:03:00.0", "ixgbe")] {
+            let device = root.join("sys/bus/pci/devices").join(address);
+            fs::create_dir_all(&device).unwrap();
+            let driver = root.join("sys/bus/pci/drivers").join(driver);
+            symlink(driver, device.join("driver")).unwrap();
+        }
+        let host = dir.host();
+
+        let mut config = DPDKConfig {
+            cores: CoreConfig::List(vec![1, 2]),
+            pci_options: PCIOptions::PCI {
+                blocked_devices: vec![],
+                allowed_devices: vec!["02:00.0".parse().unwrap()],
+            },
+            memory_mb: Some(256),
+            ..Default::default()
+        };
+        config.validate_on(&host).unwrap();
+
+        config.cores = CoreConfig::List(vec![1, 3, 4]);
+        config.main_lcore = Some(0);
+        config.service_core_mask = Some(0b100001);
+        config.memory_mb = Some(1024);
+        config.pci_options = PCIOptions::PCI {
+            blocked_devices: vec![],
+            allowed_devices: vec!["0000:03:00.0".parse().unwrap(), "04:00.0".parse().unwrap()],
+        };
+        let err = config.validate_on(&host).unwrap_err();
+        assert_eq!(
+            err.diagnostics,
+            [
+                Diagnostic::CpuOffline { cpu: 3 },
+                Diagnostic::CpuMissing { cpu: 4 },
+                Diagnostic::MainLcoreNotInSet { main_lcore: 0 },
+                Diagnostic::ServiceCoreIsMain { lcore: 0 },
+                Diagnostic::ServiceCoreNotInSet { lcore: 5 },
+                Diagnostic::CpuMissing { cpu: 5 },
+                Diagnostic::NotEnoughHugepages {
+                    requested_mb: 1024,
+                    free_mb: 512
+                },
+                Diagnostic::PciDriverIncompatible {
+                    address: "0000:03:00.0".parse().unwrap(),
+                    driver: "ixgbe".to_string()
+                },
+                Diagnostic::PciDeviceMissing {
+                    address: "0000:04:00.0".parse().unwrap()
+                },
+            ]
+        );
+
+        // The defaults of the thesis: lcore 0 is not configured and lcore 1 is the main one
+        config.cores = CoreConfig::List(vec![1, 2, 3]);
+        config.main_lcore = None;
+        config.service_core_mask = Some(0b11);
+        config.memory_mb = Some(256);
+        config.pci_options = PCIOptions::PCI {
+            blocked_devices: vec![],
+            allowed_devices: vec!["02:00.0".parse().unwrap()],
+        };
+        let err = config.validate_on(&host).unwrap_err();
+        assert_eq!(
+            err.diagnostics,
+            [
+                Diagnostic::CpuOffline { cpu: 3 },
+                Diagnostic::ServiceCoreNotInSet { lcore: 0 },
+                Diagnostic::ServiceCoreIsMain { lcore: 1 },
+            ]
+        );
+    }
+}
@@ -0,0 +1,550 @@
+//! Options of the virtual devices we use, turned into a [`VirtualDevice`] with
+//! [`VirtualDevice::from_options`]. Unset options are left out so the driver defaults apply.
+
+use std::path::PathBuf;
+
+use super::{ConfigError, VirtualDevice};
+
+/// Linux limit on interface names, including the NUL.
+const IFNAMSIZ: usize = 16;
+
+pub trait VdevOptions {
+    const DRIVER: &'static str;
+
+    /// Key and value of every option that is set, an empty value is a flag.
+    fn options(&self) -> Vec<(&'static str, String)>;
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        Ok(())
+    }
+
+    fn into_vdev(self, id: u64) -> Result<VirtualDevice, ConfigError>
+    where
+        Self: Sized,
+    {
+        VirtualDevice::from_options(id, &self)
+    }
+}
+
+fn push<T: ToString>(
+    options: &mut Vec<(&'static str, String)>,
+    key: &'static str,
+    value: &Option<T>,
+) {
+    if let Some(value) = value {
+        options.push((key, value.to_string()));
+    }
+}
+
+fn path(path: &Option<PathBuf>) -> Option<String> {
+    path.as_ref().map(|path| path.display().to_string())
+}
+
+fn check_iface(what: &'static str, iface: &Option<String>) -> Result<(), ConfigError> {
+    match iface {
+        Some(iface) if iface.is_empty() || iface.len() >= IFNAMSIZ => Err(ConfigError::invalid(
+            what,
+            iface,
+            format!("interface names are 1 to {} bytes long", IFNAMSIZ - 1),
+        )),
+        _ => Ok(()),
+    }
+}
+
+fn check_value(what: &'static str, value: &str) -> Result<(), ConfigError> {
+    if value.contains(|c: char| c == ',' || c == '=' || c.is_whitespace()) {
+        Err(ConfigError::invalid(
+            what,
+            value,
+            "may not contain commas, = or whitespace",
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+fn check_mac(what: &'static str, mac: &str) -> Result<(), ConfigError> {
+    let octets = mac.split(':').collect::<Vec<_>>();
+    let is_mac = octets.len() == 6
+        && octets
+            .iter()
+            .all(|octet| octet.len() == 2 && u8::from_str_radix(octet, 16).is_ok());
+    if is_mac {
+        Ok(())
+    } else {
+        Err(ConfigError::invalid(what, mac, "expected xx:xx:xx:xx:xx:xx"))
+    }
+}
+
+/// `net_pcap`, reads packets from pcap files or interfaces and writes them to others.
+#[derive(Debug, Clone, Default)]
+pub struct PcapVdev {
+    pub rx_pcap: Option<PathBuf>,
+    pub tx_pcap: Option<PathBuf>,
+    pub rx_iface: Option<String>,
+    /// Like `rx_iface` but only captures incoming packets
+    pub rx_iface_in: Option<String>,
+    pub tx_iface: Option<String>,
+    /// Receives from and sends to the same interface
+    pub iface: Option<String>,
+    /// Replay `rx_pcap` forever, the packets are cached in memory
+    pub infinite_rx: bool,
+    /// Report the MAC address of the interface instead of a random one
+    pub phy_mac: bool,
+}
+
+impl VdevOptions for PcapVdev {
+    const DRIVER: &'static str = "net_pcap";
+
+    fn options(&self) -> Vec<(&'static str, String)> {
+        let mut options = vec![];
+        push(&mut options, "rx_pcap", &path(&self.rx_pcap));
+        push(&mut options, "tx_pcap", &path(&self.tx_pcap));
+        push(&mut options, "rx_iface", &self.rx_iface);
+        push(&mut options, "rx_iface_in", &self.rx_iface_in);
+        push(&mut options, "tx_iface", &self.tx_iface);
+        push(&mut options, "iface", &self.iface);
+        if self.infinite_rx {
+            options.push(("infinite_rx", "1".to_string()));
+        }
+        if self.phy_mac {
+            options.push(("phy_mac", "1".to_string()));
+        }
+        options
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        for iface in [&self.rx_iface, &self.rx_iface_in, &self.tx_iface, &self.iface] {
+            check_iface("pcap interface", iface)?;
+        }
+        for file in [&self.rx_pcap, &self.tx_pcap] {
+            check_value("pcap file", &path(file).unwrap_or_default())?;
+        }
+
+        let rx_sources = [
+            self.rx_pcap.is_some(),
+            self.rx_iface.is_some(),
+            self.rx_iface_in.is_some(),
+            self.iface.is_some(),
+        ];
+        let rx_count = rx_sources.iter().filter(|set| **set).count();
+        let has_tx = self.tx_pcap.is_some() || self.tx_iface.is_some();
+        if self.iface.is_some() && (rx_count > 1 || has_tx) {
+            return Err(ConfigError::invalid(
+                "pcap options",
+                &self.iface.clone().unwrap_or_default(),
+                "iface can not be combined with other rx or tx options",
+            ));
+        }
+        if rx_count > 1 {
+            return Err(ConfigError::invalid(
+                "pcap options",
+                "",
+                "only one of rx_pcap, rx_iface and rx_iface_in may be set",
+            ));
+        }
+        if self.infinite_rx && self.rx_pcap.is_none() {
+            return Err(ConfigError::invalid(
+                "pcap options",
+                "infinite_rx",
+                "needs rx_pcap",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// `net_null`, drops every packet sent and receives empty packets as fast as it can.
+#[derive(Debug, Clone, Default)]
+pub struct NullVdev {
+    /// Size of the received packets, 64 bytes by default
+    pub size: Option<u32>,
+    /// Copy packet data on rx and tx, to include the memory traffic in benchmarks
+    pub copy: bool,
+    /// Receive nothing
+    pub no_rx: bool,
+}
+
+impl VdevOptions for NullVdev {
+    const DRIVER: &'static str = "net_null";
+
+    fn options(&self) -> Vec<(&'static str, String)> {
+        let mut options = vec![];
+        push(&mut options, "size", &self.size);
+        if self.copy {
+            options.push(("copy", "1".to_string()));
+        }
+        if self.no_rx {
+            options.push(("no-rx", "1".to_string()));
+        }
+        options
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        if self.copy && self.no_rx {
+            return Err(ConfigError::invalid(
+                "null options",
+                "copy,no-rx",
+                "copy and no_rx are mutually exclusive",
+            ));
+        }
+        if self.size == Some(0) {
+            return Err(ConfigError::invalid("null packet size", "0", "must not be 0"));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RingAction {
+    /// Create new rings
+    Create,
+    /// Use the rings of a port created by another `net_ring` device with the same name
+    Attach,
+}
+
+/// `net_ring`, a port whose queues are rte_rings, what it sends another port can receive.
+#[derive(Debug, Clone, Default)]
+pub struct RingVdev {
+    /// `(name, numa node, action)`, without it the device gets a private ring per queue
+    pub nodeaction: Option<(String, u32, RingAction)>,
+}
+
+impl VdevOptions for RingVdev {
+    const DRIVER: &'static str = "net_ring";
+
+    fn options(&self) -> Vec<(&'static str, String)> {
+        let mut options = vec![];
+        if let Some((name, node, action)) = &self.nodeaction {
+            let action = match action {
+                RingAction::Create => "CREATE",
+                RingAction::Attach => "ATTACH",
+            };
+            options.push(("nodeaction", format!("{name}:{node}:{action}")));
+        }
+        options
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        if let Some((name, _, _)) = &self.nodeaction {
+            check_value("ring name", name)?;
+            if name.is_empty() || name.contains(':') {
+                return Err(ConfigError::invalid(
+                    "ring name",
+                    name,
+                    "must be non-empty and may not contain ':'",
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `net_tap`, a kernel tap interface, so packets can be injected and captured with the usual
+/// tools.
+#[derive(Debug, Clone, Default)]
+pub struct TapVdev {
+    /// Name of the tap interface, `dtap<n>` by default
+    pub iface: Option<String>,
+    /// A MAC address like `"02:00:00:00:00:01"` or `"fixed"`
+    pub mac: Option<String>,
+    /// Mirror the traffic of this interface to the tap
+    pub remote: Option<String>,
+    /// Keep the interface after the port is closed
+    pub persist: bool,
+}
+
+impl VdevOptions for TapVdev {
+    const DRIVER: &'static str = "net_tap";
+
+    fn options(&self) -> Vec<(&'static str, String)> {
+        let mut options = vec![];
+        push(&mut options, "iface", &self.iface);
+        push(&mut options, "mac", &self.mac);
+        push(&mut options, "remote", &self.remote);
+        if self.persist {
+            options.push(("persist", String::new()));
+        }
+        options
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        check_iface("tap interface", &self.iface)?;
+        check_iface("tap remote interface", &self.remote)?;
+        match &self.mac {
+            // The driver derives a fixed address from the interface index
+            Some(mac) if mac != "fixed" => check_mac("tap MAC address", mac),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// `net_af_packet`, uses a kernel interface through an AF_PACKET socket.
+#[derive(Debug, Clone, Default)]
+pub struct AfPacketVdev {
+    pub iface: String,
+    /// Number of rx and tx queue pairs, 1 by default
+    pub qpairs: Option<u16>,
+    pub blocksz: Option<u32>,
+    pub framesz: Option<u32>,
+    pub framecnt: Option<u32>,
+    /// Bypass the kernel qdisc layer on tx
+    pub qdisc_bypass: bool,
+}
+
+impl VdevOptions for AfPacketVdev {
+    const DRIVER: &'static str = "net_af_packet";
+
+    fn options(&self) -> Vec<(&'static str, String)> {
+        let mut options = vec![("iface", self.iface.clone())];
+        push(&mut options, "qpairs", &self.qpairs);
+        push(&mut options, "blocksz", &self.blocksz);
+        push(&mut options, "framesz", &self.framesz);
+        push(&mut options, "framecnt", &self.framecnt);
+        if self.qdisc_bypass {
+            options.push(("qdisc_bypass", "1".to_string()));
+        }
+        options
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        check_iface("af_packet interface", &Some(self.iface.clone()))?;
+        if self.qpairs == Some(0) {
+            return Err(ConfigError::invalid("af_packet qpairs", "0", "must not be 0"));
+        }
+        // The driver defaults are a 4096 byte block and 2048 byte frames
+        let blocksz = self.blocksz.unwrap_or(4096);
+        let framesz = self.framesz.unwrap_or(2048);
+        if framesz == 0 || framesz > blocksz || blocksz % framesz != 0 {
+            return Err(ConfigError::invalid(
+                "af_packet frame size",
+                &framesz.to_string(),
+                format!("must divide the block size {blocksz}"),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemifRole {
+    Server,
+    Client,
+}
+
+/// `net_memif`, a shared memory interface to another process, for example a second DPDK
+/// application or VPP.
+#[derive(Debug, Clone, Default)]
+pub struct MemifVdev {
+    /// Interface id, both ends of a connection use the same one
+    pub id: Option<u32>,
+    /// Client by default
+    pub role: Option<MemifRole>,
+    /// Buffer size, 2048 bytes by default
+    pub bsize: Option<u16>,
+    /// log2 of the ring size, 10 by default
+    pub rsize: Option<u8>,
+    /// `/run/memif.sock` by default
+    pub socket: Option<PathBuf>,
+    pub mac: Option<String>,
+    pub secret: Option<String>,
+    /// Clients only, map the buffers of the server instead of copying
+    pub zero_copy: bool,
+}
+
+impl VdevOptions for MemifVdev {
+    const DRIVER: &'static str = "net_memif";
+
+    fn options(&self) -> Vec<(&'static str, String)> {
+        let mut options = vec![];
+        push(&mut options, "id", &self.id);
+        if let Some(role) = self.role {
+            let role = match role {
+                MemifRole::Server => "server",
+                MemifRole::Client => "client",
+            };
+            options.push(("role", role.to_string()));
+        }
+        push(&mut options, "bsize", &self.bsize);
+        push(&mut options, "rsize", &self.rsize);
+        push(&mut options, "socket", &path(&self.socket));
+        push(&mut options, "mac", &self.mac);
+        push(&mut options, "secret", &self.secret);
+        if self.zero_copy {
+            options.push(("zero-copy", "yes".to_string()));
+        }
+        options
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        if let Some(rsize) = self.rsize {
+            if rsize > 14 {
+                return Err(ConfigError::invalid(
+                    "memif ring size",
+                    &rsize.to_string(),
+                    "log2 of the ring size may be at most 14",
+                ));
+            }
+        }
+        if let Some(secret) = &self.secret {
+            check_value("memif secret", secret)?;
+            if secret.len() > 24 {
+                return Err(ConfigError::invalid(
+                    "memif secret",
+                    secret,
+                    "may be at most 24 bytes",
+                ));
+            }
+        }
+        check_value("memif socket", &path(&self.socket).unwrap_or_default())?;
+        if let Some(mac) = &self.mac {
+            check_mac("memif MAC address", mac)?;
+        }
+        if self.zero_copy && self.role == Some(MemifRole::Server) {
+            return Err(ConfigError::invalid(
+                "memif options",
+                "zero-copy",
+                "only clients can use zero copy",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// `event_sw`, the software event device, which needs a service core to schedule.
+#[derive(Debug, Clone, Default)]
+pub struct EventSwVdev {
+    pub numa_node: Option<u32>,
+    /// Events scheduled per service call
+    pub sched_quanta: Option<u32>,
+    /// Credits a port takes from the device at once
+    pub credit_quanta: Option<u32>,
+}
+
+impl VdevOptions for EventSwVdev {
+    const DRIVER: &'static str = "event_sw";
+
+    fn options(&self) -> Vec<(&'static str, String)> {
+        let mut options = vec![];
+        push(&mut options, "numa_node", &self.numa_node);
+        push(&mut options, "sched_quanta", &self.sched_quanta);
+        push(&mut options, "credit_quanta", &self.credit_quanta);
+        options
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        if self.sched_quanta == Some(0) || self.credit_quanta == Some(0) {
+            return Err(ConfigError::invalid(
+                "event_sw quanta",
+                "0",
+                "must not be 0",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// `crypto_null`, a crypto device that does nothing, for testing crypto pipelines.
+#[derive(Debug, Clone, Default)]
+pub struct CryptoNullVdev {
+    pub max_nb_queue_pairs: Option<u16>,
+    pub socket_id: Option<u32>,
+}
+
+impl VdevOptions for CryptoNullVdev {
+    const DRIVER: &'static str = "crypto_null";
+
+    fn options(&self) -> Vec<(&'static str, String)> {
+        let mut options = vec![];
+        push(&mut options, "max_nb_queue_pairs", &self.max_nb_queue_pairs);
+        push(&mut options, "socket_id", &self.socket_id);
+        options
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        if self.max_nb_queue_pairs == Some(0) {
+            return Err(ConfigError::invalid(
+                "crypto_null queue pairs",
+                "0",
+                "must not be 0",
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn devargs() {
+        let pcap = PcapVdev {
+            rx_pcap: Some("./inputs/a.pcap".into()),
+            tx_pcap: Some("./outputs/a.pcap".into()),
+            infinite_rx: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            pcap.into_vdev(0).unwrap().to_string(),
+            "net_pcap0,infinite_rx=1,rx_pcap=./inputs/a.pcap,tx_pcap=./outputs/a.pcap"
+        );
+
+        let tap = TapVdev {
+            iface: Some("dtap0".to_string()),
+            persist: true,
+            ..Default::default()
+        };
+        assert_eq!(tap.into_vdev(1).unwrap().to_string(), "net_tap1,iface=dtap0,persist");
+
+        let null = NullVdev {
+            copy: true,
+            no_rx: true,
+            ..Default::default()
+        };
+        assert!(null.into_vdev(0).is_err());
+        let af_packet = AfPacketVdev {
+            iface: "a-very-long-interface".to_string(),
+            ..Default::default()
+        };
+        assert!(af_packet.into_vdev(0).is_err());
+
+        let ring = RingVdev {
+            nodeaction: Some(("r0".to_string(), 0, RingAction::Attach)),
+        };
+        assert_eq!(ring.into_vdev(0).unwrap().to_string(), "net_ring0,nodeaction=r0:0:ATTACH");
+
+        let mut memif = MemifVdev {
+            id: Some(1),
+            role: Some(MemifRole::Server),
+            socket: Some("/run/a.sock".into()),
+            mac: Some("02:00:00:00:00:01".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            memif.clone().into_vdev(0).unwrap().to_string(),
+            "net_memif0,id=1,mac=02:00:00:00:00:01,role=server,socket=/run/a.sock"
+        );
+        memif.mac = Some("fixed".to_string());
+        assert!(memif.into_vdev(0).is_err());
+
+        let event_sw = EventSwVdev {
+            numa_node: Some(0),
+            sched_quanta: Some(64),
+            ..Default::default()
+        };
+        assert_eq!(
+            event_sw.into_vdev(0).unwrap().to_string(),
+            "event_sw0,numa_node=0,sched_quanta=64"
+        );
+
+        let crypto_null = CryptoNullVdev {
+            max_nb_queue_pairs: Some(2),
+            socket_id: Some(0),
+        };
+        assert_eq!(
+            crypto_null.into_vdev(0).unwrap().to_string(),
+            "crypto_null0,max_nb_queue_pairs=2,socket_id=0"
+        );
+    }
+}
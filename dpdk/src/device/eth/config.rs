@@ -0,0 +1,844 @@
+use std::{backtrace::Backtrace, ffi::CStr, fmt::Display, mem::MaybeUninit};
+
+use bitflags::bitflags;
+use dpdk_sys::{
+    rte_eth_conf, rte_eth_dev_configure, rte_eth_dev_rx_offload_name,
+    rte_eth_dev_tx_offload_name, rte_eth_rx_mq_mode, rte_eth_rxconf, rte_eth_tx_mq_mode,
+    rte_eth_txconf, RTE_ETH_LINK_SPEED_100G, RTE_ETH_LINK_SPEED_100M, RTE_ETH_LINK_SPEED_100M_HD, RTE_ETH_LINK_SPEED_10G,
+    RTE_ETH_LINK_SPEED_10M, RTE_ETH_LINK_SPEED_10M_HD, RTE_ETH_LINK_SPEED_1G,
+    RTE_ETH_LINK_SPEED_200G, RTE_ETH_LINK_SPEED_20G, RTE_ETH_LINK_SPEED_25G,
+    RTE_ETH_LINK_SPEED_2_5G, RTE_ETH_LINK_SPEED_40G, RTE_ETH_LINK_SPEED_50G,
+    RTE_ETH_LINK_SPEED_56G, RTE_ETH_LINK_SPEED_5G, RTE_ETH_LINK_SPEED_FIXED,
+    RTE_ETH_RSS_ETH, RTE_ETH_RSS_FRAG_IPV4, RTE_ETH_RSS_FRAG_IPV6, RTE_ETH_RSS_GENEVE,
+    RTE_ETH_RSS_GTPU, RTE_ETH_RSS_IPV4, RTE_ETH_RSS_IPV6, RTE_ETH_RSS_IPV6_EX,
+    RTE_ETH_RSS_IPV6_TCP_EX, RTE_ETH_RSS_IPV6_UDP_EX, RTE_ETH_RSS_L2_PAYLOAD,
+    RTE_ETH_RSS_NONFRAG_IPV4_OTHER, RTE_ETH_RSS_NONFRAG_IPV4_SCTP, RTE_ETH_RSS_NONFRAG_IPV4_TCP,
+    RTE_ETH_RSS_NONFRAG_IPV4_UDP, RTE_ETH_RSS_NONFRAG_IPV6_OTHER, RTE_ETH_RSS_NONFRAG_IPV6_SCTP,
+    RTE_ETH_RSS_NONFRAG_IPV6_TCP, RTE_ETH_RSS_NONFRAG_IPV6_UDP, RTE_ETH_RSS_NVGRE,
+    RTE_ETH_RSS_PORT, RTE_ETH_RSS_VXLAN, RTE_ETH_RX_OFFLOAD_BUFFER_SPLIT,
+    RTE_ETH_RX_OFFLOAD_IPV4_CKSUM, RTE_ETH_RX_OFFLOAD_KEEP_CRC, RTE_ETH_RX_OFFLOAD_MACSEC_STRIP,
+    RTE_ETH_RX_OFFLOAD_OUTER_IPV4_CKSUM, RTE_ETH_RX_OFFLOAD_OUTER_UDP_CKSUM,
+    RTE_ETH_RX_OFFLOAD_QINQ_STRIP, RTE_ETH_RX_OFFLOAD_RSS_HASH, RTE_ETH_RX_OFFLOAD_SCATTER,
+    RTE_ETH_RX_OFFLOAD_SCTP_CKSUM, RTE_ETH_RX_OFFLOAD_SECURITY, RTE_ETH_RX_OFFLOAD_TCP_CKSUM,
+    RTE_ETH_RX_OFFLOAD_TCP_LRO, RTE_ETH_RX_OFFLOAD_TIMESTAMP, RTE_ETH_RX_OFFLOAD_UDP_CKSUM,
+    RTE_ETH_RX_OFFLOAD_VLAN_EXTEND, RTE_ETH_RX_OFFLOAD_VLAN_FILTER, RTE_ETH_RX_OFFLOAD_VLAN_STRIP,
+    RTE_ETH_TX_OFFLOAD_GENEVE_TNL_TSO, RTE_ETH_TX_OFFLOAD_GRE_TNL_TSO,
+    RTE_ETH_TX_OFFLOAD_IPIP_TNL_TSO, RTE_ETH_TX_OFFLOAD_IPV4_CKSUM, RTE_ETH_TX_OFFLOAD_IP_TNL_TSO,
+    RTE_ETH_TX_OFFLOAD_MACSEC_INSERT, RTE_ETH_TX_OFFLOAD_MBUF_FAST_FREE,
+    RTE_ETH_TX_OFFLOAD_MT_LOCKFREE, RTE_ETH_TX_OFFLOAD_MULTI_SEGS,
+    RTE_ETH_TX_OFFLOAD_OUTER_IPV4_CKSUM, RTE_ETH_TX_OFFLOAD_OUTER_UDP_CKSUM,
+    RTE_ETH_TX_OFFLOAD_QINQ_INSERT, RTE_ETH_TX_OFFLOAD_SCTP_CKSUM, RTE_ETH_TX_OFFLOAD_SECURITY,
+    RTE_ETH_TX_OFFLOAD_SEND_ON_TIMESTAMP, RTE_ETH_TX_OFFLOAD_TCP_CKSUM, RTE_ETH_TX_OFFLOAD_TCP_TSO,
+    RTE_ETH_TX_OFFLOAD_UDP_CKSUM, RTE_ETH_TX_OFFLOAD_UDP_TNL_TSO, RTE_ETH_TX_OFFLOAD_UDP_TSO,
+    RTE_ETH_TX_OFFLOAD_VLAN_INSERT, RTE_ETH_TX_OFFLOAD_VXLAN_TNL_TSO,
+};
+
+use crate::{
+    config::{ConfigError, PortConfig},
+    error::DpdkError,
+};
+
+use super::{
+    dev::EthdevPortId,
+    info::{DescriptorLimits, EthDeviceInfo, DEFAULT_RING_SIZE},
+};
+
+bitflags! {
+    /// Speeds the link may negotiate, none means every speed the device supports.
+    pub struct LinkSpeeds: u32 {
+        /// Only use the single speed given, without autonegotiation
+        const FIXED = RTE_ETH_LINK_SPEED_FIXED;
+        const SPEED_10M_HD = RTE_ETH_LINK_SPEED_10M_HD;
+        const SPEED_10M = RTE_ETH_LINK_SPEED_10M;
+        const SPEED_100M_HD = RTE_ETH_LINK_SPEED_100M_HD;
+        const SPEED_100M = RTE_ETH_LINK_SPEED_100M;
+        const SPEED_1G = RTE_ETH_LINK_SPEED_1G;
+        const SPEED_2_5G = RTE_ETH_LINK_SPEED_2_5G;
+        const SPEED_5G = RTE_ETH_LINK_SPEED_5G;
+        const SPEED_10G = RTE_ETH_LINK_SPEED_10G;
+        const SPEED_20G = RTE_ETH_LINK_SPEED_20G;
+        const SPEED_25G = RTE_ETH_LINK_SPEED_25G;
+        const SPEED_40G = RTE_ETH_LINK_SPEED_40G;
+        const SPEED_50G = RTE_ETH_LINK_SPEED_50G;
+        const SPEED_56G = RTE_ETH_LINK_SPEED_56G;
+        const SPEED_100G = RTE_ETH_LINK_SPEED_100G;
+        const SPEED_200G = RTE_ETH_LINK_SPEED_200G;
+    }
+}
+
+bitflags! {
+    pub struct RxOffload: u64 {
+        const VLAN_STRIP = RTE_ETH_RX_OFFLOAD_VLAN_STRIP;
+        const IPV4_CKSUM = RTE_ETH_RX_OFFLOAD_IPV4_CKSUM;
+        const UDP_CKSUM = RTE_ETH_RX_OFFLOAD_UDP_CKSUM;
+        const TCP_CKSUM = RTE_ETH_RX_OFFLOAD_TCP_CKSUM;
+        const TCP_LRO = RTE_ETH_RX_OFFLOAD_TCP_LRO;
+        const QINQ_STRIP = RTE_ETH_RX_OFFLOAD_QINQ_STRIP;
+        const OUTER_IPV4_CKSUM = RTE_ETH_RX_OFFLOAD_OUTER_IPV4_CKSUM;
+        const MACSEC_STRIP = RTE_ETH_RX_OFFLOAD_MACSEC_STRIP;
+        const VLAN_FILTER = RTE_ETH_RX_OFFLOAD_VLAN_FILTER;
+        const VLAN_EXTEND = RTE_ETH_RX_OFFLOAD_VLAN_EXTEND;
+        /// Receive packets larger than one mbuf as chains of mbufs
+        const SCATTER = RTE_ETH_RX_OFFLOAD_SCATTER;
+        const TIMESTAMP = RTE_ETH_RX_OFFLOAD_TIMESTAMP;
+        const SECURITY = RTE_ETH_RX_OFFLOAD_SECURITY;
+        const KEEP_CRC = RTE_ETH_RX_OFFLOAD_KEEP_CRC;
+        const SCTP_CKSUM = RTE_ETH_RX_OFFLOAD_SCTP_CKSUM;
+        const OUTER_UDP_CKSUM = RTE_ETH_RX_OFFLOAD_OUTER_UDP_CKSUM;
+        /// Store the RSS hash in the mbuf
+        const RSS_HASH = RTE_ETH_RX_OFFLOAD_RSS_HASH;
+        const BUFFER_SPLIT = RTE_ETH_RX_OFFLOAD_BUFFER_SPLIT;
+    }
+}
+
+bitflags! {
+    pub struct TxOffload: u64 {
+        const VLAN_INSERT = RTE_ETH_TX_OFFLOAD_VLAN_INSERT;
+        const IPV4_CKSUM = RTE_ETH_TX_OFFLOAD_IPV4_CKSUM;
+        const UDP_CKSUM = RTE_ETH_TX_OFFLOAD_UDP_CKSUM;
+        const TCP_CKSUM = RTE_ETH_TX_OFFLOAD_TCP_CKSUM;
+        const SCTP_CKSUM = RTE_ETH_TX_OFFLOAD_SCTP_CKSUM;
+        const TCP_TSO = RTE_ETH_TX_OFFLOAD_TCP_TSO;
+        const UDP_TSO = RTE_ETH_TX_OFFLOAD_UDP_TSO;
+        const OUTER_IPV4_CKSUM = RTE_ETH_TX_OFFLOAD_OUTER_IPV4_CKSUM;
+        const QINQ_INSERT = RTE_ETH_TX_OFFLOAD_QINQ_INSERT;
+        const VXLAN_TNL_TSO = RTE_ETH_TX_OFFLOAD_VXLAN_TNL_TSO;
+        const GRE_TNL_TSO = RTE_ETH_TX_OFFLOAD_GRE_TNL_TSO;
+        const IPIP_TNL_TSO = RTE_ETH_TX_OFFLOAD_IPIP_TNL_TSO;
+        const GENEVE_TNL_TSO = RTE_ETH_TX_OFFLOAD_GENEVE_TNL_TSO;
+        const MACSEC_INSERT = RTE_ETH_TX_OFFLOAD_MACSEC_INSERT;
+        /// Several threads may send on the same queue without locking
+        const MT_LOCKFREE = RTE_ETH_TX_OFFLOAD_MT_LOCKFREE;
+        /// Send packets that are chains of mbufs
+        const MULTI_SEGS = RTE_ETH_TX_OFFLOAD_MULTI_SEGS;
+        /// All mbufs sent on a queue come from one mempool and have a refcount of 1
+        const MBUF_FAST_FREE = RTE_ETH_TX_OFFLOAD_MBUF_FAST_FREE;
+        const SECURITY = RTE_ETH_TX_OFFLOAD_SECURITY;
+        const UDP_TNL_TSO = RTE_ETH_TX_OFFLOAD_UDP_TNL_TSO;
+        const IP_TNL_TSO = RTE_ETH_TX_OFFLOAD_IP_TNL_TSO;
+        const OUTER_UDP_CKSUM = RTE_ETH_TX_OFFLOAD_OUTER_UDP_CKSUM;
+        const SEND_ON_TIMESTAMP = RTE_ETH_TX_OFFLOAD_SEND_ON_TIMESTAMP;
+    }
+}
+
+bitflags! {
+    /// Packet types whose headers are hashed to pick the RSS queue.
+    pub struct RssHashFunctions: u64 {
+        const IPV4 = RTE_ETH_RSS_IPV4;
+        const FRAG_IPV4 = RTE_ETH_RSS_FRAG_IPV4;
+        const NONFRAG_IPV4_TCP = RTE_ETH_RSS_NONFRAG_IPV4_TCP;
+        const NONFRAG_IPV4_UDP = RTE_ETH_RSS_NONFRAG_IPV4_UDP;
+        const NONFRAG_IPV4_SCTP = RTE_ETH_RSS_NONFRAG_IPV4_SCTP;
+        const NONFRAG_IPV4_OTHER = RTE_ETH_RSS_NONFRAG_IPV4_OTHER;
+        const IPV6 = RTE_ETH_RSS_IPV6;
+        const FRAG_IPV6 = RTE_ETH_RSS_FRAG_IPV6;
+        const NONFRAG_IPV6_TCP = RTE_ETH_RSS_NONFRAG_IPV6_TCP;
+        const NONFRAG_IPV6_UDP = RTE_ETH_RSS_NONFRAG_IPV6_UDP;
+        const NONFRAG_IPV6_SCTP = RTE_ETH_RSS_NONFRAG_IPV6_SCTP;
+        const NONFRAG_IPV6_OTHER = RTE_ETH_RSS_NONFRAG_IPV6_OTHER;
+        const L2_PAYLOAD = RTE_ETH_RSS_L2_PAYLOAD;
+        const IPV6_EX = RTE_ETH_RSS_IPV6_EX;
+        const IPV6_TCP_EX = RTE_ETH_RSS_IPV6_TCP_EX;
+        const IPV6_UDP_EX = RTE_ETH_RSS_IPV6_UDP_EX;
+        const PORT = RTE_ETH_RSS_PORT;
+        const VXLAN = RTE_ETH_RSS_VXLAN;
+        const GENEVE = RTE_ETH_RSS_GENEVE;
+        const NVGRE = RTE_ETH_RSS_NVGRE;
+        const GTPU = RTE_ETH_RSS_GTPU;
+        const ETH = RTE_ETH_RSS_ETH;
+
+        const IP = Self::IPV4.bits | Self::FRAG_IPV4.bits | Self::NONFRAG_IPV4_OTHER.bits
+            | Self::IPV6.bits | Self::FRAG_IPV6.bits | Self::NONFRAG_IPV6_OTHER.bits
+            | Self::IPV6_EX.bits;
+        const TCP = Self::NONFRAG_IPV4_TCP.bits | Self::NONFRAG_IPV6_TCP.bits
+            | Self::IPV6_TCP_EX.bits;
+        const UDP = Self::NONFRAG_IPV4_UDP.bits | Self::NONFRAG_IPV6_UDP.bits
+            | Self::IPV6_UDP_EX.bits;
+    }
+}
+
+/// Names of the set bits as DPDK prints them.
+fn offload_names(bits: u64, name_of: unsafe extern "C" fn(u64) -> *const libc::c_char) -> String {
+    let names = (0..u64::BITS)
+        .map(|bit| 1u64 << bit)
+        .filter(|flag| bits & flag != 0)
+        .map(|flag| {
+            unsafe { CStr::from_ptr(name_of(flag)) }
+                .to_string_lossy()
+                .into_owned()
+        })
+        .collect::<Vec<_>>();
+    names.join("|")
+}
+
+impl Display for RxOffload {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", offload_names(self.bits, rte_eth_dev_rx_offload_name))
+    }
+}
+
+impl Display for TxOffload {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", offload_names(self.bits, rte_eth_dev_tx_offload_name))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RxMqMode {
+    None,
+    Rss,
+    Dcb,
+    DcbRss,
+    VmdqOnly,
+    VmdqRss,
+    VmdqDcb,
+    VmdqDcbRss,
+}
+
+impl From<RxMqMode> for rte_eth_rx_mq_mode {
+    fn from(mode: RxMqMode) -> Self {
+        match mode {
+            RxMqMode::None => rte_eth_rx_mq_mode::RTE_ETH_MQ_RX_NONE,
+            RxMqMode::Rss => rte_eth_rx_mq_mode::RTE_ETH_MQ_RX_RSS,
+            RxMqMode::Dcb => rte_eth_rx_mq_mode::RTE_ETH_MQ_RX_DCB,
+            RxMqMode::DcbRss => rte_eth_rx_mq_mode::RTE_ETH_MQ_RX_DCB_RSS,
+            RxMqMode::VmdqOnly => rte_eth_rx_mq_mode::RTE_ETH_MQ_RX_VMDQ_ONLY,
+            RxMqMode::VmdqRss => rte_eth_rx_mq_mode::RTE_ETH_MQ_RX_VMDQ_RSS,
+            RxMqMode::VmdqDcb => rte_eth_rx_mq_mode::RTE_ETH_MQ_RX_VMDQ_DCB,
+            RxMqMode::VmdqDcbRss => rte_eth_rx_mq_mode::RTE_ETH_MQ_RX_VMDQ_DCB_RSS,
+        }
+    }
+}
+
+impl RxMqMode {
+    /// Whether packets are spread with RSS, which needs an [`RssConfig`]
+    pub fn uses_rss(self) -> bool {
+        matches!(
+            self,
+            RxMqMode::Rss | RxMqMode::DcbRss | RxMqMode::VmdqRss | RxMqMode::VmdqDcbRss
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TxMqMode {
+    None,
+    Dcb,
+    VmdqDcb,
+    VmdqOnly,
+}
+
+impl From<TxMqMode> for rte_eth_tx_mq_mode {
+    fn from(mode: TxMqMode) -> Self {
+        match mode {
+            TxMqMode::None => rte_eth_tx_mq_mode::RTE_ETH_MQ_TX_NONE,
+            TxMqMode::Dcb => rte_eth_tx_mq_mode::RTE_ETH_MQ_TX_DCB,
+            TxMqMode::VmdqDcb => rte_eth_tx_mq_mode::RTE_ETH_MQ_TX_VMDQ_DCB,
+            TxMqMode::VmdqOnly => rte_eth_tx_mq_mode::RTE_ETH_MQ_TX_VMDQ_ONLY,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RssConfig {
+    pub hash_functions: RssHashFunctions,
+    /// The driver default key is used if None
+    pub key: Option<Vec<u8>>,
+}
+
+impl Default for RssConfig {
+    fn default() -> Self {
+        Self {
+            hash_functions: RssHashFunctions::IP | RssHashFunctions::TCP | RssHashFunctions::UDP,
+            key: None,
+        }
+    }
+}
+
+impl RssConfig {
+    /// The default hash functions, limited to those the device supports.
+    pub fn for_device(info: &EthDeviceInfo) -> Self {
+        Self {
+            hash_functions: Self::default().hash_functions & info.flow_type_rss_offloads,
+            key: None,
+        }
+    }
+}
+
+/// Which events raise interrupts instead of having to be polled for.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InterruptConfig {
+    /// Link status changes
+    pub lsc: bool,
+    /// Packets arriving on rx queues
+    pub rxq: bool,
+    /// Device removal
+    pub rmv: bool,
+}
+
+/// Settings of one rx queue. Thresholds left at None use the device defaults.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RxQueueConfig {
+    pub ring_size: u16,
+    pub free_thresh: Option<u16>,
+    /// Drop packets when no descriptors are free instead of holding up the other queues
+    pub drop_en: bool,
+    /// The queue is started with `rte_eth_dev_rx_queue_start` instead of with the port
+    pub deferred_start: bool,
+    /// Offloads on top of the ones of the port, needs per-queue support by the device
+    pub offloads: RxOffload,
+}
+
+impl Default for RxQueueConfig {
+    fn default() -> Self {
+        Self {
+            ring_size: DEFAULT_RING_SIZE,
+            free_thresh: None,
+            drop_en: false,
+            deferred_start: false,
+            offloads: RxOffload::empty(),
+        }
+    }
+}
+
+impl RxQueueConfig {
+    /// `default` is the `default_rxconf` reported by the device.
+    pub fn to_raw(&self, default: &rte_eth_rxconf, port_offloads: RxOffload) -> rte_eth_rxconf {
+        let mut conf = *default;
+        if let Some(free_thresh) = self.free_thresh {
+            conf.rx_free_thresh = free_thresh;
+        }
+        conf.rx_drop_en = self.drop_en as u8;
+        conf.rx_deferred_start = self.deferred_start as u8;
+        conf.offloads = (self.offloads | port_offloads).bits;
+        conf
+    }
+}
+
+/// Settings of one tx queue. Thresholds left at None use the device defaults.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxQueueConfig {
+    pub ring_size: u16,
+    /// Descriptors used before the device reports them as done
+    pub rs_thresh: Option<u16>,
+    pub free_thresh: Option<u16>,
+    pub deferred_start: bool,
+    /// Offloads on top of the ones of the port, needs per-queue support by the device
+    pub offloads: TxOffload,
+}
+
+impl Default for TxQueueConfig {
+    fn default() -> Self {
+        Self {
+            ring_size: DEFAULT_RING_SIZE,
+            rs_thresh: None,
+            free_thresh: None,
+            deferred_start: false,
+            offloads: TxOffload::empty(),
+        }
+    }
+}
+
+impl TxQueueConfig {
+    /// `default` is the `default_txconf` reported by the device.
+    pub fn to_raw(&self, default: &rte_eth_txconf, port_offloads: TxOffload) -> rte_eth_txconf {
+        let mut conf = *default;
+        if let Some(rs_thresh) = self.rs_thresh {
+            conf.tx_rs_thresh = rs_thresh;
+        }
+        if let Some(free_thresh) = self.free_thresh {
+            conf.tx_free_thresh = free_thresh;
+        }
+        conf.tx_deferred_start = self.deferred_start as u8;
+        conf.offloads = (self.offloads | port_offloads).bits;
+        conf
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum EthConfigError {
+    #[error("Port {port} does not support the rx offloads {offloads}{}", queue_suffix(.queue))]
+    UnsupportedRxOffloads {
+        port: EthdevPortId,
+        queue: Option<u16>,
+        offloads: RxOffload,
+        backtrace: Backtrace,
+    },
+    #[error("Port {port} does not support the tx offloads {offloads}{}", queue_suffix(.queue))]
+    UnsupportedTxOffloads {
+        port: EthdevPortId,
+        queue: Option<u16>,
+        offloads: TxOffload,
+        backtrace: Backtrace,
+    },
+    #[error("Port {port} can not hash {unsupported:?} for RSS")]
+    UnsupportedRssHash {
+        port: EthdevPortId,
+        unsupported: RssHashFunctions,
+        backtrace: Backtrace,
+    },
+    #[error(
+        "Port {port} uses the rx mq mode {rx_mq_mode:?} {} an RSS config",
+        if *.rss { "with" } else { "without" }
+    )]
+    RssModeMismatch {
+        port: EthdevPortId,
+        rx_mq_mode: RxMqMode,
+        rss: bool,
+        backtrace: Backtrace,
+    },
+    #[error("Port {port} needs an RSS key of {expected} bytes, got {len}")]
+    InvalidRssKey {
+        port: EthdevPortId,
+        len: usize,
+        expected: u8,
+        backtrace: Backtrace,
+    },
+    #[error("Port {port} does not support the link speeds {unsupported:?}")]
+    UnsupportedLinkSpeeds {
+        port: EthdevPortId,
+        unsupported: LinkSpeeds,
+        backtrace: Backtrace,
+    },
+    #[error("Port {port} supports at most {max} {direction} queues, {requested} requested")]
+    TooManyQueues {
+        port: EthdevPortId,
+        direction: &'static str,
+        requested: usize,
+        max: u16,
+        backtrace: Backtrace,
+    },
+    #[error("Port {port} needs at least one rx or tx queue")]
+    NoQueues {
+        port: EthdevPortId,
+        backtrace: Backtrace,
+    },
+    #[error("MTU {mtu} of port {port} is outside of {min}..={max}")]
+    MtuOutOfRange {
+        port: EthdevPortId,
+        mtu: u16,
+        min: u16,
+        max: u16,
+        backtrace: Backtrace,
+    },
+    #[error("{direction} queue {queue} of port {port} has {size} descriptors, expected {min}..={max} in multiples of {align}")]
+    InvalidRingSize {
+        port: EthdevPortId,
+        direction: &'static str,
+        queue: u16,
+        size: u16,
+        min: u16,
+        max: u16,
+        align: u16,
+        backtrace: Backtrace,
+    },
+    #[error(transparent)]
+    Dpdk(#[from] DpdkError),
+}
+
+fn queue_suffix(queue: &Option<u16>) -> String {
+    match queue {
+        Some(queue) => format!(" on queue {queue}"),
+        None => String::new(),
+    }
+}
+
+/// Mbufs in the pool of each rx queue if not configured otherwise.
+pub const DEFAULT_MBUFS_PER_RX_QUEUE: u32 = 8191;
+pub const DEFAULT_MBUF_CACHE_SIZE: u32 = 250;
+
+/// Everything `rte_eth_dev_configure` and the queue setup need for a port. The number of queues
+/// is the length of `rx_queues` and `tx_queues`.
+#[derive(Debug, Clone, Builder)]
+#[builder(default)]
+pub struct EthPortConfig {
+    pub link_speeds: LinkSpeeds,
+    pub rx_mq_mode: RxMqMode,
+    pub tx_mq_mode: TxMqMode,
+    /// The driver default MTU is used if None
+    pub mtu: Option<u16>,
+    /// Only used with one of the RSS multi-queue modes
+    pub rss: Option<RssConfig>,
+    pub rx_offloads: RxOffload,
+    pub tx_offloads: TxOffload,
+    pub rx_queues: Vec<RxQueueConfig>,
+    pub tx_queues: Vec<TxQueueConfig>,
+    pub interrupts: InterruptConfig,
+    /// Mbufs in the pool of each rx queue, best one less than a power of two
+    pub mbufs_per_rx_queue: u32,
+    pub mbuf_cache_size: u32,
+}
+
+impl Default for EthPortConfig {
+    fn default() -> Self {
+        Self {
+            link_speeds: LinkSpeeds::empty(),
+            rx_mq_mode: RxMqMode::None,
+            tx_mq_mode: TxMqMode::None,
+            mtu: None,
+            rss: None,
+            rx_offloads: RxOffload::empty(),
+            tx_offloads: TxOffload::empty(),
+            rx_queues: vec![RxQueueConfig::default()],
+            tx_queues: vec![TxQueueConfig::default()],
+            interrupts: InterruptConfig::default(),
+            mbufs_per_rx_queue: DEFAULT_MBUFS_PER_RX_QUEUE,
+            mbuf_cache_size: DEFAULT_MBUF_CACHE_SIZE,
+        }
+    }
+}
+
+impl EthPortConfig {
+    /// A config with as many queues as requested and the device supports, with the ring sizes
+    /// the driver prefers. RSS is enabled if there is more than one rx queue.
+    pub fn for_device(info: &EthDeviceInfo, rx_queues: u16, tx_queues: u16) -> Self {
+        let rx_queue = RxQueueConfig {
+            ring_size: info.rx_ring_size(),
+            ..Default::default()
+        };
+        let tx_queue = TxQueueConfig {
+            ring_size: info.tx_ring_size(),
+            ..Default::default()
+        };
+        let rx_queues = rx_queues.min(info.max_rx_queues);
+        let tx_queues = tx_queues.min(info.max_tx_queues);
+        let rss = (rx_queues > 1).then(|| RssConfig::for_device(info));
+        Self {
+            rx_mq_mode: if rss.is_some() {
+                RxMqMode::Rss
+            } else {
+                RxMqMode::None
+            },
+            rss,
+            rx_queues: vec![rx_queue; rx_queues as usize],
+            tx_queues: vec![tx_queue; tx_queues as usize],
+            ..Default::default()
+        }
+    }
+
+    /// The raw config, which points into the RSS key of `self`.
+    pub fn to_raw(&self) -> rte_eth_conf {
+        let mut conf = unsafe { MaybeUninit::<rte_eth_conf>::zeroed().assume_init() };
+        conf.link_speeds = self.link_speeds.bits;
+        conf.rxmode.mq_mode = self.rx_mq_mode.into();
+        conf.rxmode.mtu = self.mtu.unwrap_or(0) as u32;
+        conf.rxmode.offloads = self.rx_offloads.bits;
+        conf.txmode.mq_mode = self.tx_mq_mode.into();
+        conf.txmode.offloads = self.tx_offloads.bits;
+
+        if let Some(rss) = &self.rss {
+            let rss_conf = &mut conf.rx_adv_conf.rss_conf;
+            rss_conf.rss_hf = rss.hash_functions.bits;
+            if let Some(key) = &rss.key {
+                // DPDK copies the key and never writes to it
+                rss_conf.rss_key = key.as_ptr() as *mut u8;
+                rss_conf.rss_key_len = key.len() as u8;
+            }
+        }
+
+        conf.intr_conf.set_lsc(self.interrupts.lsc as u32);
+        conf.intr_conf.set_rxq(self.interrupts.rxq as u32);
+        conf.intr_conf.set_rmv(self.interrupts.rmv as u32);
+        conf
+    }
+
+    /// Checks the config against what `info` reports the device supports.
+    pub fn check(&self, info: &EthDeviceInfo) -> Result<(), EthConfigError> {
+        let port = info.port;
+        if self.rx_queues.is_empty() && self.tx_queues.is_empty() {
+            return Err(EthConfigError::NoQueues {
+                port,
+                backtrace: Backtrace::capture(),
+            });
+        }
+        for (direction, requested, max) in [
+            ("rx", self.rx_queues.len(), info.max_rx_queues),
+            ("tx", self.tx_queues.len(), info.max_tx_queues),
+        ] {
+            if requested > max as usize {
+                return Err(EthConfigError::TooManyQueues {
+                    port,
+                    direction,
+                    requested,
+                    max,
+                    backtrace: Backtrace::capture(),
+                });
+            }
+        }
+
+        let unsupported = self.rx_offloads - info.rx_offload_capa;
+        if !unsupported.is_empty() {
+            return Err(EthConfigError::UnsupportedRxOffloads {
+                port,
+                queue: None,
+                offloads: unsupported,
+                backtrace: Backtrace::capture(),
+            });
+        }
+        let unsupported = self.tx_offloads - info.tx_offload_capa;
+        if !unsupported.is_empty() {
+            return Err(EthConfigError::UnsupportedTxOffloads {
+                port,
+                queue: None,
+                offloads: unsupported,
+                backtrace: Backtrace::capture(),
+            });
+        }
+
+        // Speeds are only meaningful for physical devices, which report what they support
+        let speeds = self.link_speeds - LinkSpeeds::FIXED;
+        if !info.speed_capa.is_empty() && !speeds.is_empty() {
+            let unsupported = speeds - info.speed_capa;
+            if !unsupported.is_empty() {
+                return Err(EthConfigError::UnsupportedLinkSpeeds {
+                    port,
+                    unsupported,
+                    backtrace: Backtrace::capture(),
+                });
+            }
+        }
+
+        if let Some(mtu) = self.mtu {
+            if mtu < info.min_mtu || mtu > info.max_mtu {
+                return Err(EthConfigError::MtuOutOfRange {
+                    port,
+                    mtu,
+                    min: info.min_mtu,
+                    max: info.max_mtu,
+                    backtrace: Backtrace::capture(),
+                });
+            }
+        }
+
+        if self.rx_mq_mode.uses_rss() != self.rss.is_some() {
+            return Err(EthConfigError::RssModeMismatch {
+                port,
+                rx_mq_mode: self.rx_mq_mode,
+                rss: self.rss.is_some(),
+                backtrace: Backtrace::capture(),
+            });
+        }
+        if let Some(rss) = &self.rss {
+            let unsupported = rss.hash_functions - info.flow_type_rss_offloads;
+            if !unsupported.is_empty() {
+                return Err(EthConfigError::UnsupportedRssHash {
+                    port,
+                    unsupported,
+                    backtrace: Backtrace::capture(),
+                });
+            }
+            if let Some(key) = &rss.key {
+                if key.len() != info.hash_key_size as usize {
+                    return Err(EthConfigError::InvalidRssKey {
+                        port,
+                        len: key.len(),
+                        expected: info.hash_key_size,
+                        backtrace: Backtrace::capture(),
+                    });
+                }
+            }
+        }
+
+        for (queue, rx_queue) in self.rx_queues.iter().enumerate() {
+            let queue = queue as u16;
+            check_ring_size(port, "rx", queue, rx_queue.ring_size, &info.rx_desc_lim)?;
+            let unsupported = rx_queue.offloads - info.rx_queue_offload_capa;
+            if !unsupported.is_empty() {
+                return Err(EthConfigError::UnsupportedRxOffloads {
+                    port,
+                    queue: Some(queue),
+                    offloads: unsupported,
+                    backtrace: Backtrace::capture(),
+                });
+            }
+        }
+        for (queue, tx_queue) in self.tx_queues.iter().enumerate() {
+            let queue = queue as u16;
+            check_ring_size(port, "tx", queue, tx_queue.ring_size, &info.tx_desc_lim)?;
+            let unsupported = tx_queue.offloads - info.tx_queue_offload_capa;
+            if !unsupported.is_empty() {
+                return Err(EthConfigError::UnsupportedTxOffloads {
+                    port,
+                    queue: Some(queue),
+                    offloads: unsupported,
+                    backtrace: Backtrace::capture(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks the config against the capabilities of the device, then configures the port. The
+    /// queues still have to be set up.
+    pub fn configure(&self, port: EthdevPortId) -> Result<(), EthConfigError> {
+        let info = EthDeviceInfo::get(port)?;
+        self.check(&info)?;
+
+        let conf = self.to_raw();
+        let ret = unsafe {
+            rte_eth_dev_configure(
+                port,
+                self.rx_queues.len() as u16,
+                self.tx_queues.len() as u16,
+                &conf,
+            )
+        };
+        DpdkError::check("rte_eth_dev_configure", ret).map_err(|err| err.with_port(port))?;
+        Ok(())
+    }
+}
+
+fn check_ring_size(
+    port: EthdevPortId,
+    direction: &'static str,
+    queue: u16,
+    size: u16,
+    lim: &DescriptorLimits,
+) -> Result<(), EthConfigError> {
+    if !lim.allows(size) {
+        return Err(EthConfigError::InvalidRingSize {
+            port,
+            direction,
+            queue,
+            size,
+            min: lim.min,
+            max: lim.max,
+            align: lim.align,
+            backtrace: Backtrace::capture(),
+        });
+    }
+    Ok(())
+}
+
+impl EthPortConfig {
+    /// The config of a port as read from the config file. With `rss` set, the default hash
+    /// functions the device supports are used, like [`for_device`](Self::for_device) does.
+    pub fn from_port_config(
+        config: &PortConfig,
+        info: &EthDeviceInfo,
+    ) -> Result<Self, ConfigError> {
+        let rx_queue = RxQueueConfig {
+            ring_size: config.rx_ring_size,
+            ..Default::default()
+        };
+        let tx_queue = TxQueueConfig {
+            ring_size: config.tx_ring_size,
+            ..Default::default()
+        };
+        Ok(Self {
+            rx_mq_mode: if config.rss {
+                RxMqMode::Rss
+            } else {
+                RxMqMode::None
+            },
+            mtu: config.mtu,
+            rss: config.rss.then(|| RssConfig::for_device(info)),
+            rx_offloads: known_offloads("RX offloads", config.rx_offload_flags()?, |flags| {
+                RxOffload::from_bits(flags)
+            })?,
+            tx_offloads: known_offloads("TX offloads", config.tx_offload_flags()?, |flags| {
+                TxOffload::from_bits(flags)
+            })?,
+            rx_queues: vec![rx_queue; config.rx_queues as usize],
+            tx_queues: vec![tx_queue; config.tx_queues as usize],
+            mbufs_per_rx_queue: config.num_mbufs,
+            mbuf_cache_size: config.mbuf_cache_size,
+            ..Default::default()
+        })
+    }
+}
+
+/// DPDK may know offloads that the flags here are missing, those can not be configured.
+fn known_offloads<T>(
+    what: &'static str,
+    flags: u64,
+    from_bits: impl Fn(u64) -> Option<T>,
+) -> Result<T, ConfigError> {
+    from_bits(flags).ok_or_else(|| {
+        let unknown = (0..u64::BITS)
+            .map(|bit| 1u64 << bit)
+            .filter(|&flag| flags & flag != 0 && from_bits(flag).is_none())
+            .fold(0, |unknown, flag| unknown | flag);
+        ConfigError::invalid(
+            what,
+            &format!("{flags:#x}"),
+            format!("unsupported bits {unknown:#x}"),
+        )
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use dpdk_sys::rte_eth_dev_info;
+
+    use super::*;
+
+    #[test]
+    fn check_capabilities() {
+        let mut info = unsafe { MaybeUninit::<rte_eth_dev_info>::zeroed().assume_init() };
+        info.max_rx_queues = 4;
+        info.max_tx_queues = 4;
+        info.min_mtu = 68;
+        info.max_mtu = 9000;
+        info.rx_offload_capa = RxOffload::IPV4_CKSUM.bits;
+        info.rx_desc_lim.nb_max = 4096;
+        info.rx_desc_lim.nb_align = 8;
+        info.flow_type_rss_offloads = RssHashFunctions::IPV4.bits;
+        let info = EthDeviceInfo::from_raw(0, &info);
+
+        let mut config = EthPortConfigBuilder::default()
+            .mtu(Some(1500))
+            .rx_offloads(RxOffload::IPV4_CKSUM)
+            .rx_queues(vec![RxQueueConfig::default(); 2])
+            .build()
+            .unwrap();
+        config.check(&info).unwrap();
+
+        config.rx_queues[1].ring_size = 1001;
+        assert!(matches!(
+            config.check(&info),
+            Err(EthConfigError::InvalidRingSize { queue: 1, .. })
+        ));
+
+        config.rx_queues.truncate(1);
+        config.rx_offloads |= RxOffload::TCP_LRO;
+        assert!(matches!(
+            config.check(&info),
+            Err(EthConfigError::UnsupportedRxOffloads { offloads, queue: None, .. })
+                if offloads == RxOffload::TCP_LRO
+        ));
+
+        config.rx_offloads = RxOffload::empty();
+        config.mtu = Some(9600);
+        assert!(matches!(
+            config.check(&info),
+            Err(EthConfigError::MtuOutOfRange { max: 9000, .. })
+        ));
+
+        config.mtu = None;
+        config.rx_mq_mode = RxMqMode::Rss;
+        assert!(matches!(
+            config.check(&info),
+            Err(EthConfigError::RssModeMismatch { rss: false, .. })
+        ));
+
+        let rss = RssConfig::for_device(&info);
+        assert_eq!(rss.hash_functions, RssHashFunctions::IPV4);
+        config.rss = Some(rss);
+        config.check(&info).unwrap();
+
+        config.rx_mq_mode = RxMqMode::None;
+        assert!(matches!(
+            config.check(&info),
+            Err(EthConfigError::RssModeMismatch { rss: true, .. })
+        ));
+    }
+
+    #[test]
+    fn unknown_offloads() {
+        let flags = RxOffload::IPV4_CKSUM.bits | 1 << 63;
+        let err = known_offloads("RX offloads", flags, RxOffload::from_bits).unwrap_err();
+        assert!(err.to_string().ends_with("unsupported bits 0x8000000000000000"));
+    }
+}
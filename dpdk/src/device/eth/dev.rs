@@ -1,45 +1,17 @@
-use std::backtrace::Backtrace;
+use std::ffi::CStr;
 
 use dpdk_sys::{
-    rte_eth_conf, rte_eth_dev_configure, rte_eth_dev_count_avail, rte_eth_dev_socket_id,
-    rte_eth_rx_queue_setup, rte_eth_tx_queue_setup, rte_socket_id, rte_eth_dev_start, rte_mempool_create_empty, RTE_MEMPOOL_CACHE_MAX_SIZE,
+    rte_eth_dev_count_avail, rte_eth_dev_socket_id, rte_socket_id, rte_eth_dev_start,
+    rte_eth_dev_get_name_by_port, rte_eth_dev_get_port_by_name, RTE_ETH_NAME_MAX_LEN,
 };
 
+use crate::{error::DpdkError, util::str_to_c_string};
 
-pub type EthdevPortId = u16;
-pub type EventQueueId = u16;
+use super::config::{EthConfigError, EthPortConfig};
 
-#[derive(Debug, thiserror::Error)]
-pub enum EthDriverError {
-    #[error("Error starting port {port}, rx queue {queue}, received driver error {driver_error}")]
-    PortRxQueueStartError {
-        port: EthdevPortId,
-        queue: EventQueueId,
-        driver_error: i32,
-        backtrace: Backtrace,
-    },
-    #[error("Error starting port {port}, tx queue {queue}, received driver error {driver_error}")]
-    PortTxQueueStartError {
-        port: EthdevPortId,
-        queue: EventQueueId,
-        driver_error: i32,
-        backtrace: Backtrace,
-    },
-    #[error("Error configuring port {port}, received driver error {driver_error}")]
-    PortConfigureError {
-        port: EthdevPortId,
-        driver_error: i32,
-        backtrace: Backtrace,
-    },
-    #[error("Error starting port {port}, recieved driver error {driver_error}")]
-    PortStartError {
-        port: EthdevPortId,
-        driver_error: i32,
-        backtrace: Backtrace,
-    }
-}
 
-use crate::memory::pktmbuf_pool::PktMbufPool;
+pub type EthdevPortId = u16;
+pub type EventQueueId = u16;
 
 pub fn num_ports_available() -> u16 {
     unsafe { rte_eth_dev_count_avail() }
@@ -49,6 +21,29 @@ pub fn iter_ports() -> std::ops::Range<u16> {
     0..num_ports_available()
 }
 
+/// Port id of a device by its name, for example a port probed by the primary process.
+pub fn port_id_by_name(name: &str) -> Option<EthdevPortId> {
+    let c_name = str_to_c_string(name);
+    let mut port_id: EthdevPortId = 0;
+    let ret = unsafe { rte_eth_dev_get_port_by_name(c_name.as_ptr(), &mut port_id) };
+    if ret == 0 {
+        Some(port_id)
+    } else {
+        None
+    }
+}
+
+pub fn port_name(port_id: EthdevPortId) -> Option<String> {
+    let mut name = [0 as libc::c_char; RTE_ETH_NAME_MAX_LEN as usize];
+    let ret = unsafe { rte_eth_dev_get_name_by_port(port_id, name.as_mut_ptr()) };
+    if ret == 0 {
+        let name = unsafe { CStr::from_ptr(name.as_ptr()) };
+        Some(name.to_string_lossy().into_owned())
+    } else {
+        None
+    }
+}
+
 pub fn socket_id() -> u32 {
     unsafe { rte_socket_id() }
 }
@@ -62,89 +57,12 @@ pub fn socket_id_for_port(port_id: EthdevPortId) -> Option<u32> {
     }
 }
 
-pub fn configure_port(
-    port: EthdevPortId,
-    num_rx_queues: u16,
-    num_tx_queues: u16,
-    port_conf: &rte_eth_conf,
-) -> Result<(), EthDriverError> {
-    let ret = unsafe { rte_eth_dev_configure(port, num_rx_queues, num_tx_queues, port_conf) };
-    if ret < 0 {
-        Err(EthDriverError::PortConfigureError {
-            port,
-            driver_error: ret,
-            backtrace: Backtrace::capture()
-        })
-    } else {
-        Ok(())
-    }
-}
-
-pub fn setup_port_queues(
-    port: EthdevPortId,
-    num_rx_queues: u16,
-    num_tx_queues: u16,
-    port_conf: &rte_eth_conf,
-    rx_ring_size: u16,
-    tx_ring_size: u16,
-) -> Result<(), EthDriverError> {
-    let n = 2 << 20;
-    let cache_size = n.max(RTE_MEMPOOL_CACHE_MAX_SIZE);
-    let mut pool = PktMbufPool::new("rx_pkt_pool", n, cache_size)
-        .expect("Unable to create mbuf pool");
-    configure_port(port, num_rx_queues, num_tx_queues, port_conf)?;
-
-    let port_socket = socket_id_for_port(port).expect("Port had no socket id");
-
-    for queue_id in 0..num_rx_queues {
-        let driver_error = unsafe {
-            rte_eth_rx_queue_setup(
-                port,
-                queue_id,
-                rx_ring_size,
-                port_socket,
-                std::ptr::null(),
-                pool.as_mut(),
-            )
-        };
-        if driver_error < 0 {
-            return Err(EthDriverError::PortRxQueueStartError {
-                port,
-                queue: queue_id,
-                driver_error,
-                backtrace: Backtrace::capture()
-            });
-        }
-    }
-
-    for queue_id in 0..num_tx_queues {
-        let driver_error = unsafe {
-            rte_eth_tx_queue_setup(
-                port,
-                queue_id,
-                tx_ring_size,
-                port_socket,
-                std::ptr::null(),
-            )
-        };
-        if driver_error < 0 {
-            return Err(EthDriverError::PortTxQueueStartError {
-                port,
-                queue: queue_id,
-                driver_error,
-                backtrace: Backtrace::capture()
-            });
-        }
-    }
-
-    Ok(())
+/// Checks `config` against the capabilities of the device and configures the port.
+pub fn configure_port(port: EthdevPortId, config: &EthPortConfig) -> Result<(), EthConfigError> {
+    config.configure(port)
 }
 
-pub fn start_port(port: EthdevPortId) -> Result<(), EthDriverError> {
+pub fn start_port(port: EthdevPortId) -> Result<(), DpdkError> {
     let ret = unsafe { rte_eth_dev_start(port) };
-    if ret < 0 {
-        Err(EthDriverError::PortStartError { port, driver_error: ret, backtrace: Backtrace::capture() })
-    } else {
-        Ok(())
-    }
+    DpdkError::check("rte_eth_dev_start", ret).map_err(|err| err.with_port(port))
 }
\ No newline at end of file
@@ -0,0 +1,212 @@
+use std::{ffi::CStr, mem::MaybeUninit};
+
+use dpdk_sys::{
+    rte_eth_desc_lim, rte_eth_dev_info, rte_eth_dev_info_get, rte_eth_dev_portconf,
+    rte_eth_rxconf, rte_eth_txconf,
+};
+
+use crate::error::DpdkError;
+
+use super::{
+    config::{LinkSpeeds, RssHashFunctions, RxOffload, TxOffload},
+    dev::EthdevPortId,
+};
+
+/// Ring size used when the driver has no preference, what the DPDK examples use.
+pub const DEFAULT_RING_SIZE: u16 = 1024;
+
+/// How many descriptors a queue ring may have.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DescriptorLimits {
+    pub min: u16,
+    pub max: u16,
+    /// The ring size has to be a multiple of this
+    pub align: u16,
+    /// Segments of one packet, 0 if unlimited
+    pub max_segments_per_packet: u16,
+    /// Segments of one packet with MTU size, 0 if unlimited
+    pub max_segments_per_mtu: u16,
+}
+
+impl From<&rte_eth_desc_lim> for DescriptorLimits {
+    fn from(lim: &rte_eth_desc_lim) -> Self {
+        Self {
+            min: lim.nb_min,
+            // Some virtual devices report no limits at all
+            max: if lim.nb_max == 0 { u16::MAX } else { lim.nb_max },
+            align: lim.nb_align.max(1),
+            max_segments_per_packet: lim.nb_seg_max,
+            max_segments_per_mtu: lim.nb_mtu_seg_max,
+        }
+    }
+}
+
+impl DescriptorLimits {
+    pub fn allows(&self, size: u16) -> bool {
+        size >= self.min && size <= self.max && size % self.align == 0
+    }
+
+    /// The closest ring size to `size` the device accepts, like
+    /// `rte_eth_dev_adjust_nb_rx_tx_desc` does.
+    pub fn adjust(&self, size: u16) -> u16 {
+        let size = size.clamp(self.min, self.max);
+        let aligned = (size as u32 + self.align as u32 - 1) / self.align as u32 * self.align as u32;
+        if aligned > self.max as u32 {
+            self.max / self.align * self.align
+        } else {
+            aligned as u16
+        }
+    }
+}
+
+/// Settings the driver works best with, 0 means it has no preference.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PreferredPortConfig {
+    pub burst_size: u16,
+    pub ring_size: u16,
+    pub num_queues: u16,
+}
+
+impl From<&rte_eth_dev_portconf> for PreferredPortConfig {
+    fn from(conf: &rte_eth_dev_portconf) -> Self {
+        Self {
+            burst_size: conf.burst_size,
+            ring_size: conf.ring_size,
+            num_queues: conf.nb_queues,
+        }
+    }
+}
+
+/// What `rte_eth_dev_info_get` reports about a port.
+#[derive(Debug, Clone)]
+pub struct EthDeviceInfo {
+    pub port: EthdevPortId,
+    pub driver_name: String,
+    /// Index of the kernel interface, None if there is none
+    pub if_index: Option<u32>,
+    pub min_mtu: u16,
+    pub max_mtu: u16,
+    pub min_rx_bufsize: u32,
+    pub max_rx_pktlen: u32,
+    pub max_lro_pkt_size: u32,
+    pub max_rx_queues: u16,
+    pub max_tx_queues: u16,
+    pub max_mac_addrs: u32,
+    pub rx_desc_lim: DescriptorLimits,
+    pub tx_desc_lim: DescriptorLimits,
+    /// Offloads that can be enabled for the whole port
+    pub rx_offload_capa: RxOffload,
+    pub tx_offload_capa: TxOffload,
+    /// Offloads that can also be enabled per queue
+    pub rx_queue_offload_capa: RxOffload,
+    pub tx_queue_offload_capa: TxOffload,
+    pub flow_type_rss_offloads: RssHashFunctions,
+    /// Entries of the RSS redirection table
+    pub reta_size: u16,
+    pub hash_key_size: u8,
+    /// Empty for devices without a physical link
+    pub speed_capa: LinkSpeeds,
+    /// Queues the port is currently configured with
+    pub nb_rx_queues: u16,
+    pub nb_tx_queues: u16,
+    pub default_rxconf: rte_eth_rxconf,
+    pub default_txconf: rte_eth_txconf,
+    pub preferred_rx: PreferredPortConfig,
+    pub preferred_tx: PreferredPortConfig,
+}
+
+impl EthDeviceInfo {
+    pub fn get(port: EthdevPortId) -> Result<Self, DpdkError> {
+        let mut info = unsafe { MaybeUninit::<rte_eth_dev_info>::zeroed().assume_init() };
+        let ret = unsafe { rte_eth_dev_info_get(port, &mut info) };
+        DpdkError::check("rte_eth_dev_info_get", ret).map_err(|err| err.with_port(port))?;
+        Ok(Self::from_raw(port, &info))
+    }
+
+    pub fn from_raw(port: EthdevPortId, info: &rte_eth_dev_info) -> Self {
+        let driver_name = if info.driver_name.is_null() {
+            String::new()
+        } else {
+            unsafe { CStr::from_ptr(info.driver_name) }
+                .to_string_lossy()
+                .into_owned()
+        };
+        Self {
+            port,
+            driver_name,
+            if_index: (info.if_index != 0).then_some(info.if_index),
+            min_mtu: info.min_mtu,
+            max_mtu: info.max_mtu,
+            min_rx_bufsize: info.min_rx_bufsize,
+            max_rx_pktlen: info.max_rx_pktlen,
+            max_lro_pkt_size: info.max_lro_pkt_size,
+            max_rx_queues: info.max_rx_queues,
+            max_tx_queues: info.max_tx_queues,
+            max_mac_addrs: info.max_mac_addrs,
+            rx_desc_lim: (&info.rx_desc_lim).into(),
+            tx_desc_lim: (&info.tx_desc_lim).into(),
+            rx_offload_capa: RxOffload::from_bits_truncate(info.rx_offload_capa),
+            tx_offload_capa: TxOffload::from_bits_truncate(info.tx_offload_capa),
+            rx_queue_offload_capa: RxOffload::from_bits_truncate(info.rx_queue_offload_capa),
+            tx_queue_offload_capa: TxOffload::from_bits_truncate(info.tx_queue_offload_capa),
+            flow_type_rss_offloads: RssHashFunctions::from_bits_truncate(
+                info.flow_type_rss_offloads,
+            ),
+            reta_size: info.reta_size,
+            hash_key_size: info.hash_key_size,
+            speed_capa: LinkSpeeds::from_bits_truncate(info.speed_capa),
+            nb_rx_queues: info.nb_rx_queues,
+            nb_tx_queues: info.nb_tx_queues,
+            default_rxconf: info.default_rxconf,
+            default_txconf: info.default_txconf,
+            preferred_rx: (&info.default_rxportconf).into(),
+            preferred_tx: (&info.default_txportconf).into(),
+        }
+    }
+
+    /// The preferred rx ring size of the driver, or [`DEFAULT_RING_SIZE`], within the limits.
+    pub fn rx_ring_size(&self) -> u16 {
+        let preferred = match self.preferred_rx.ring_size {
+            0 => DEFAULT_RING_SIZE,
+            size => size,
+        };
+        self.rx_desc_lim.adjust(preferred)
+    }
+
+    /// The preferred tx ring size of the driver, or [`DEFAULT_RING_SIZE`], within the limits.
+    pub fn tx_ring_size(&self) -> u16 {
+        let preferred = match self.preferred_tx.ring_size {
+            0 => DEFAULT_RING_SIZE,
+            size => size,
+        };
+        self.tx_desc_lim.adjust(preferred)
+    }
+
+    /// Whether the hash functions can be used for RSS on this port.
+    pub fn supports_rss(&self, hash_functions: RssHashFunctions) -> bool {
+        !hash_functions.is_empty() && self.flow_type_rss_offloads.contains(hash_functions)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ring_sizes() {
+        let mut raw = unsafe { MaybeUninit::<rte_eth_dev_info>::zeroed().assume_init() };
+        raw.rx_desc_lim.nb_min = 64;
+        raw.rx_desc_lim.nb_max = 4096;
+        raw.rx_desc_lim.nb_align = 32;
+        raw.default_rxportconf.ring_size = 1000;
+        raw.tx_desc_lim.nb_max = 512;
+
+        let info = EthDeviceInfo::from_raw(0, &raw);
+        assert_eq!(info.driver_name, "");
+        assert_eq!(info.rx_ring_size(), 1024);
+        assert_eq!(info.tx_ring_size(), 512);
+        assert_eq!(info.rx_desc_lim.adjust(10), 64);
+        assert_eq!(info.rx_desc_lim.adjust(u16::MAX), 4096);
+        assert!(!info.rx_desc_lim.allows(1000));
+    }
+}
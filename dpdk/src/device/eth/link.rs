@@ -0,0 +1,252 @@
+use std::{
+    backtrace::Backtrace,
+    fmt::Display,
+    mem::MaybeUninit,
+    time::{Duration, Instant},
+};
+
+use dpdk_sys::{
+    rte_eth_dev_callback_register, rte_eth_dev_callback_unregister, rte_eth_event_type,
+    rte_eth_link, rte_eth_link_get, rte_eth_link_get_nowait, RTE_ETH_ALL,
+    RTE_ETH_LINK_AUTONEG, RTE_ETH_LINK_FULL_DUPLEX, RTE_ETH_LINK_UP, RTE_ETH_SPEED_NUM_NONE,
+    RTE_ETH_SPEED_NUM_UNKNOWN,
+};
+use libc::c_void;
+
+use crate::{callback::RegisteredCallback, error::DpdkError};
+
+use super::dev::EthdevPortId;
+
+/// How often [`wait_for_link_up`] looks at the link.
+const LINK_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, thiserror::Error)]
+pub enum LinkError {
+    #[error("Link of port {port} is still down after {timeout:?}")]
+    Timeout {
+        port: EthdevPortId,
+        timeout: Duration,
+        backtrace: Backtrace,
+    },
+    #[error(transparent)]
+    Dpdk(#[from] DpdkError),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Duplex {
+    Half,
+    Full,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LinkStatus {
+    pub up: bool,
+    /// None if the driver does not know the speed or there is no link
+    pub speed_mbps: Option<u32>,
+    pub duplex: Duplex,
+    pub autoneg: bool,
+}
+
+impl From<&rte_eth_link> for LinkStatus {
+    fn from(link: &rte_eth_link) -> Self {
+        Self {
+            up: link.link_status() as u32 == RTE_ETH_LINK_UP,
+            speed_mbps: match link.link_speed {
+                RTE_ETH_SPEED_NUM_NONE | RTE_ETH_SPEED_NUM_UNKNOWN => None,
+                speed => Some(speed),
+            },
+            duplex: if link.link_duplex() as u32 == RTE_ETH_LINK_FULL_DUPLEX {
+                Duplex::Full
+            } else {
+                Duplex::Half
+            },
+            autoneg: link.link_autoneg() as u32 == RTE_ETH_LINK_AUTONEG,
+        }
+    }
+}
+
+impl Display for LinkStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if !self.up {
+            return write!(f, "down");
+        }
+        write!(f, "up")?;
+        match self.speed_mbps {
+            Some(speed) if speed >= 1000 => write!(f, ", {} Gbps", speed as f64 / 1000.0)?,
+            Some(speed) => write!(f, ", {speed} Mbps")?,
+            None => write!(f, ", unknown speed")?,
+        }
+        match self.duplex {
+            Duplex::Full => write!(f, ", full duplex")?,
+            Duplex::Half => write!(f, ", half duplex")?,
+        }
+        if self.autoneg {
+            write!(f, ", autoneg")?;
+        }
+        Ok(())
+    }
+}
+
+/// The link status of the port, waiting for the driver to report it. This can take seconds on
+/// some NICs, see [`link_status_nowait`].
+pub fn link_status(port: EthdevPortId) -> Result<LinkStatus, DpdkError> {
+    let mut link = unsafe { MaybeUninit::<rte_eth_link>::zeroed().assume_init() };
+    let ret = unsafe { rte_eth_link_get(port, &mut link) };
+    DpdkError::check("rte_eth_link_get", ret).map_err(|err| err.with_port(port))?;
+    Ok((&link).into())
+}
+
+/// The last link status the driver knows of, without waiting.
+pub fn link_status_nowait(port: EthdevPortId) -> Result<LinkStatus, DpdkError> {
+    let mut link = unsafe { MaybeUninit::<rte_eth_link>::zeroed().assume_init() };
+    let ret = unsafe { rte_eth_link_get_nowait(port, &mut link) };
+    DpdkError::check("rte_eth_link_get_nowait", ret).map_err(|err| err.with_port(port))?;
+    Ok((&link).into())
+}
+
+/// Polls the link of a started port until it is up, so traffic is not sent into a link that
+/// is still negotiating.
+pub fn wait_for_link_up(port: EthdevPortId, timeout: Duration) -> Result<LinkStatus, LinkError> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        let status = link_status_nowait(port)?;
+        if status.up {
+            return Ok(status);
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return Err(LinkError::Timeout {
+                port,
+                timeout,
+                backtrace: Backtrace::capture(),
+            });
+        }
+        std::thread::sleep(LINK_POLL_INTERVAL.min(deadline - now));
+    }
+}
+
+/// Port events that can have callbacks. LSC and RMV are only raised for ports configured with
+/// the matching [`InterruptConfig`](super::config::InterruptConfig) flags.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PortEvent {
+    /// The link went up or down, see [`link_status_nowait`] for the new status
+    LinkStatusChange,
+    /// The device was unplugged
+    Removed,
+    /// The device has to be reset, for example because its PF was
+    ResetNeeded,
+}
+
+impl From<PortEvent> for rte_eth_event_type {
+    fn from(event: PortEvent) -> Self {
+        match event {
+            PortEvent::LinkStatusChange => rte_eth_event_type::RTE_ETH_EVENT_INTR_LSC,
+            PortEvent::Removed => rte_eth_event_type::RTE_ETH_EVENT_INTR_RMV,
+            PortEvent::ResetNeeded => rte_eth_event_type::RTE_ETH_EVENT_INTR_RESET,
+        }
+    }
+}
+
+type PortEventCallback = dyn FnMut(EthdevPortId, PortEvent) + Send;
+
+unsafe extern "C" fn port_event_trampoline(
+    port: u16,
+    event: rte_eth_event_type,
+    arg: *mut c_void,
+    _ret_param: *mut c_void,
+) -> libc::c_int {
+    let event = match event {
+        rte_eth_event_type::RTE_ETH_EVENT_INTR_LSC => PortEvent::LinkStatusChange,
+        rte_eth_event_type::RTE_ETH_EVENT_INTR_RMV => PortEvent::Removed,
+        rte_eth_event_type::RTE_ETH_EVENT_INTR_RESET => PortEvent::ResetNeeded,
+        _ => return 0,
+    };
+    unsafe {
+        RegisteredCallback::<PortEventCallback>::call(
+            arg,
+            format_args!("Port event callback for port {port}"),
+            |f| f(port, event),
+        )
+    };
+    0
+}
+
+/// A registered port event callback, unregistered when dropped.
+pub struct PortEventHandle {
+    port: u16,
+    event: PortEvent,
+    callback: RegisteredCallback<PortEventCallback>,
+}
+
+impl Drop for PortEventHandle {
+    fn drop(&mut self) {
+        self.callback.unregister(|arg| unsafe {
+            rte_eth_dev_callback_unregister(
+                self.port,
+                self.event.into(),
+                Some(port_event_trampoline),
+                arg,
+            )
+        });
+    }
+}
+
+/// Calls `f` whenever `event` happens on `port`, or on any port if it is None. Callbacks run on
+/// the EAL interrupt thread.
+pub fn on_port_event<F>(
+    port: Option<EthdevPortId>,
+    event: PortEvent,
+    f: F,
+) -> Result<PortEventHandle, DpdkError>
+where
+    F: FnMut(EthdevPortId, PortEvent) + Send + 'static,
+{
+    let callback = RegisteredCallback::<PortEventCallback>::new(Box::new(f));
+    let port_id = port.unwrap_or(RTE_ETH_ALL as u16);
+
+    let ret = unsafe {
+        rte_eth_dev_callback_register(
+            port_id,
+            event.into(),
+            Some(port_event_trampoline),
+            callback.arg(),
+        )
+    };
+    if ret != 0 {
+        let err = DpdkError::from_return("rte_eth_dev_callback_register", ret);
+        return Err(match port {
+            Some(port) => err.with_port(port),
+            None => err,
+        });
+    }
+
+    Ok(PortEventHandle {
+        port: port_id,
+        event,
+        callback,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn display() {
+        let mut status = LinkStatus {
+            up: true,
+            speed_mbps: Some(2500),
+            duplex: Duplex::Full,
+            autoneg: true,
+        };
+        assert_eq!(status.to_string(), "up, 2.5 Gbps, full duplex, autoneg");
+        status.speed_mbps = Some(100);
+        status.duplex = Duplex::Half;
+        status.autoneg = false;
+        assert_eq!(status.to_string(), "up, 100 Mbps, half duplex");
+        status.speed_mbps = Some(10000);
+        assert_eq!(status.to_string(), "up, 10 Gbps, half duplex");
+        status.up = false;
+        assert_eq!(status.to_string(), "down");
+    }
+}
@@ -1,3 +1,8 @@
+pub mod config;
 pub mod dev;
+pub mod info;
+pub mod link;
+pub mod port;
+pub mod queue;
 pub mod rx;
 pub mod tx;
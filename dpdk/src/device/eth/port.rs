@@ -0,0 +1,249 @@
+use std::{backtrace::Backtrace, marker::PhantomData, time::Duration};
+
+use dpdk_sys::{
+    rte_eth_dev_close, rte_eth_dev_reset, rte_eth_dev_start, rte_eth_dev_stop,
+    rte_eth_rx_queue_setup, rte_eth_tx_queue_setup, RTE_MEMPOOL_CACHE_MAX_SIZE, SOCKET_ID_ANY,
+};
+
+use crate::{eal::Eal, error::DpdkError, memory::pktmbuf_pool::PktMbufPool};
+
+use super::{
+    config::{EthConfigError, EthPortConfig},
+    dev::{socket_id_for_port, EthdevPortId},
+    info::EthDeviceInfo,
+    link::{self, LinkError, LinkStatus},
+    queue::QueueError,
+    rx::RxQueue,
+    tx::TxQueue,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PortState {
+    /// Queues are set up, the port has never been started
+    Configured,
+    Started,
+    Stopped,
+    /// The port is released and can not be used anymore
+    Closed,
+}
+
+/// A configured ethernet port together with the mempools its rx queues allocate from.
+///
+/// The port is stopped and closed when this is dropped, before the mempools are freed, so the
+/// driver never refers to buffers that are gone. It borrows the [`Eal`] so it can not outlive
+/// `rte_eal_cleanup`.
+pub struct EthPort<'eal> {
+    id: EthdevPortId,
+    config: EthPortConfig,
+    info: EthDeviceInfo,
+    state: PortState,
+    /// One per rx queue, dropped after the port is closed
+    rx_pools: Vec<PktMbufPool>,
+    _eal: PhantomData<&'eal Eal>,
+}
+
+impl<'eal> EthPort<'eal> {
+    /// Configures the port and sets up its queues, each rx queue gets its own mempool on the
+    /// NUMA node of the port. On failure the port is left open so it can be configured again,
+    /// and the mempools created so far are leaked, as queues set up before the failure still
+    /// refer to them.
+    pub fn new(
+        _eal: &'eal Eal,
+        id: EthdevPortId,
+        config: EthPortConfig,
+    ) -> Result<Self, EthConfigError> {
+        let info = EthDeviceInfo::get(id)?;
+        let mut rx_pools = vec![];
+        if let Err(err) = Self::setup(id, &config, &info, &mut rx_pools) {
+            leak_pools(&mut rx_pools);
+            return Err(err);
+        }
+        Ok(Self {
+            id,
+            config,
+            info,
+            state: PortState::Configured,
+            rx_pools,
+            _eal: PhantomData,
+        })
+    }
+
+    /// Configures the port and its queues, creating the pools `rx_pools` is missing.
+    fn setup(
+        id: EthdevPortId,
+        config: &EthPortConfig,
+        info: &EthDeviceInfo,
+        rx_pools: &mut Vec<PktMbufPool>,
+    ) -> Result<(), EthConfigError> {
+        config.configure(id)?;
+
+        // Virtual devices have no NUMA affinity
+        let socket = socket_id_for_port(id).unwrap_or(SOCKET_ID_ANY as u32);
+        let cache_size = config.mbuf_cache_size.min(RTE_MEMPOOL_CACHE_MAX_SIZE);
+
+        for (queue_id, queue) in config.rx_queues.iter().enumerate() {
+            let queue_id = queue_id as u16;
+            // Pools survive a reset, the new queues take over the old ones
+            if rx_pools.len() <= queue_id as usize {
+                let pool = PktMbufPool::new_on_socket(
+                    &format!("eth{id}_rxq{queue_id}"),
+                    config.mbufs_per_rx_queue,
+                    cache_size,
+                    socket as i32,
+                )?;
+                rx_pools.push(pool);
+            }
+
+            let rx_conf = queue.to_raw(&info.default_rxconf, config.rx_offloads);
+            let ret = unsafe {
+                rte_eth_rx_queue_setup(
+                    id,
+                    queue_id,
+                    queue.ring_size,
+                    socket,
+                    &rx_conf,
+                    rx_pools[queue_id as usize].as_mut(),
+                )
+            };
+            DpdkError::check("rte_eth_rx_queue_setup", ret)
+                .map_err(|err| err.with_port(id).with_queue(queue_id))?;
+        }
+
+        for (queue_id, queue) in config.tx_queues.iter().enumerate() {
+            let queue_id = queue_id as u16;
+            let tx_conf = queue.to_raw(&info.default_txconf, config.tx_offloads);
+            let ret = unsafe {
+                rte_eth_tx_queue_setup(id, queue_id, queue.ring_size, socket, &tx_conf)
+            };
+            DpdkError::check("rte_eth_tx_queue_setup", ret)
+                .map_err(|err| err.with_port(id).with_queue(queue_id))?;
+        }
+
+        Ok(())
+    }
+
+    pub fn id(&self) -> EthdevPortId {
+        self.id
+    }
+
+    pub fn config(&self) -> &EthPortConfig {
+        &self.config
+    }
+
+    pub fn info(&self) -> &EthDeviceInfo {
+        &self.info
+    }
+
+    pub fn state(&self) -> PortState {
+        self.state
+    }
+
+    /// The pool rx queue `queue` allocates from, for allocating packets to send from the same
+    /// memory.
+    pub fn rx_pool(&mut self, queue: u16) -> Option<&mut PktMbufPool> {
+        self.rx_pools.get_mut(queue as usize)
+    }
+
+    pub fn link_status(&self) -> Result<LinkStatus, DpdkError> {
+        link::link_status_nowait(self.id)
+    }
+
+    /// Waits until the link of the started port is up.
+    pub fn wait_for_link_up(&self, timeout: Duration) -> Result<LinkStatus, LinkError> {
+        link::wait_for_link_up(self.id, timeout)
+    }
+
+    /// The handle for rx queue `queue`, the port has to be started.
+    pub fn rx_queue(&self, queue: u16) -> Result<RxQueue<'_>, QueueError> {
+        self.ensure_started()?;
+        RxQueue::new(self.id, queue)
+    }
+
+    /// The handle for tx queue `queue`, the port has to be started.
+    pub fn tx_queue(&self, queue: u16) -> Result<TxQueue<'_>, QueueError> {
+        self.ensure_started()?;
+        TxQueue::new(self.id, queue)
+    }
+
+    fn ensure_started(&self) -> Result<(), QueueError> {
+        if self.state == PortState::Started {
+            Ok(())
+        } else {
+            Err(QueueError::NotStarted {
+                port: self.id,
+                backtrace: Backtrace::capture(),
+            })
+        }
+    }
+
+    fn ensure_open(&self, operation: &'static str) -> Result<(), DpdkError> {
+        if self.state == PortState::Closed {
+            Err(DpdkError::from_errno(operation, libc::ENODEV).with_port(self.id))
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn start(&mut self) -> Result<(), DpdkError> {
+        self.ensure_open("rte_eth_dev_start")?;
+        if self.state == PortState::Started {
+            return Ok(());
+        }
+        let ret = unsafe { rte_eth_dev_start(self.id) };
+        DpdkError::check("rte_eth_dev_start", ret).map_err(|err| err.with_port(self.id))?;
+        self.state = PortState::Started;
+        Ok(())
+    }
+
+    /// Queue handles borrow the port, so none of them can poll it once it is stopped.
+    pub fn stop(&mut self) -> Result<(), DpdkError> {
+        self.ensure_open("rte_eth_dev_stop")?;
+        if self.state != PortState::Started {
+            return Ok(());
+        }
+        let ret = unsafe { rte_eth_dev_stop(self.id) };
+        DpdkError::check("rte_eth_dev_stop", ret).map_err(|err| err.with_port(self.id))?;
+        self.state = PortState::Stopped;
+        Ok(())
+    }
+
+    /// Resets the device, for example after the PF of a VF was reset, and sets it up again with
+    /// the same config. The port is left stopped. If setting it up again fails, the port keeps
+    /// its mempools until it is closed, so queues that were set up keep valid buffers.
+    pub fn reset(&mut self) -> Result<(), EthConfigError> {
+        self.ensure_open("rte_eth_dev_reset")?;
+        let ret = unsafe { rte_eth_dev_reset(self.id) };
+        DpdkError::check("rte_eth_dev_reset", ret).map_err(|err| err.with_port(self.id))?;
+        self.state = PortState::Stopped;
+        self.info = EthDeviceInfo::get(self.id)?;
+        Self::setup(self.id, &self.config, &self.info, &mut self.rx_pools)
+    }
+
+    /// Stops the port and releases it. The mempools are kept until this is dropped.
+    pub fn close(&mut self) -> Result<(), DpdkError> {
+        if self.state == PortState::Closed {
+            return Ok(());
+        }
+        self.stop()?;
+        let ret = unsafe { rte_eth_dev_close(self.id) };
+        DpdkError::check("rte_eth_dev_close", ret).map_err(|err| err.with_port(self.id))?;
+        self.state = PortState::Closed;
+        Ok(())
+    }
+}
+
+impl Drop for EthPort<'_> {
+    fn drop(&mut self) {
+        if let Err(err) = self.close() {
+            log::error!("Unable to close port {}, leaking its mempools: {err}", self.id);
+            leak_pools(&mut self.rx_pools);
+        }
+    }
+}
+
+/// For pools the driver may still write into, leaking them is the lesser evil.
+fn leak_pools(pools: &mut Vec<PktMbufPool>) {
+    for pool in pools.drain(..) {
+        std::mem::forget(pool);
+    }
+}
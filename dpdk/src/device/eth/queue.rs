@@ -0,0 +1,144 @@
+//! Bookkeeping that keeps [`RxQueue`](super::rx::RxQueue) and [`TxQueue`](super::tx::TxQueue)
+//! handles unique. DPDK queues are not thread safe, so each one may only ever have one handle.
+
+use std::{backtrace::Backtrace, fmt::Display};
+
+use dpdk_sys::{rte_eth_dev_is_valid_port, rte_eth_fp_ops};
+use parking_lot::Mutex;
+
+use crate::error::DpdkError;
+
+use super::{dev::EthdevPortId, info::EthDeviceInfo};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Rx,
+    Tx,
+}
+
+impl Display for Direction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Direction::Rx => write!(f, "rx"),
+            Direction::Tx => write!(f, "tx"),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum QueueError {
+    #[error("Port {port} does not exist")]
+    InvalidPort {
+        port: EthdevPortId,
+        backtrace: Backtrace,
+    },
+    #[error("Port {port} has no {direction} queue {queue}, it has {count}")]
+    NoSuchQueue {
+        port: EthdevPortId,
+        direction: Direction,
+        queue: u16,
+        count: u16,
+        backtrace: Backtrace,
+    },
+    #[error("Port {port} is not started")]
+    NotStarted {
+        port: EthdevPortId,
+        backtrace: Backtrace,
+    },
+    #[error("There already is a handle for {direction} queue {queue} of port {port}")]
+    AlreadyClaimed {
+        port: EthdevPortId,
+        direction: Direction,
+        queue: u16,
+        backtrace: Backtrace,
+    },
+    #[error(transparent)]
+    Dpdk(#[from] DpdkError),
+}
+
+static CLAIMED: Mutex<Vec<(EthdevPortId, Direction, u16)>> = Mutex::new(Vec::new());
+
+/// Marks a queue as taken until it is dropped.
+#[derive(Debug)]
+pub(crate) struct QueueClaim {
+    port: EthdevPortId,
+    direction: Direction,
+    queue: u16,
+}
+
+impl QueueClaim {
+    /// Checks in release builds too that the queue exists on a started port and has no handle
+    /// yet.
+    pub(crate) fn new(
+        port: EthdevPortId,
+        direction: Direction,
+        queue: u16,
+    ) -> Result<Self, QueueError> {
+        if unsafe { rte_eth_dev_is_valid_port(port) } != 1 {
+            return Err(QueueError::InvalidPort {
+                port,
+                backtrace: Backtrace::capture(),
+            });
+        }
+
+        let info = EthDeviceInfo::get(port)?;
+        let count = match direction {
+            Direction::Rx => info.nb_rx_queues,
+            Direction::Tx => info.nb_tx_queues,
+        };
+        if queue >= count {
+            return Err(QueueError::NoSuchQueue {
+                port,
+                direction,
+                queue,
+                count,
+                backtrace: Backtrace::capture(),
+            });
+        }
+
+        // The fast path data of a queue is only filled in by `rte_eth_dev_start`
+        let fp_ops = unsafe { &rte_eth_fp_ops[port as usize] };
+        let data = match direction {
+            Direction::Rx => fp_ops.rxq.data,
+            Direction::Tx => fp_ops.txq.data,
+        };
+        // `queue` is below the configured count, which is at most RTE_MAX_QUEUES_PER_PORT
+        if data.is_null() || unsafe { *data.add(queue as usize) }.is_null() {
+            return Err(QueueError::NotStarted {
+                port,
+                backtrace: Backtrace::capture(),
+            });
+        }
+
+        let mut claimed = CLAIMED.lock();
+        if claimed.contains(&(port, direction, queue)) {
+            return Err(QueueError::AlreadyClaimed {
+                port,
+                direction,
+                queue,
+                backtrace: Backtrace::capture(),
+            });
+        }
+        claimed.push((port, direction, queue));
+        Ok(Self {
+            port,
+            direction,
+            queue,
+        })
+    }
+
+    pub(crate) fn port(&self) -> EthdevPortId {
+        self.port
+    }
+
+    pub(crate) fn queue(&self) -> u16 {
+        self.queue
+    }
+}
+
+impl Drop for QueueClaim {
+    fn drop(&mut self) {
+        let entry = (self.port, self.direction, self.queue);
+        CLAIMED.lock().retain(|claimed| *claimed != entry);
+    }
+}
@@ -1,13 +1,68 @@
-use std::{intrinsics::transmute, ptr::slice_from_raw_parts_mut, sync::atomic::AtomicPtr};
+use std::{
+    cell::Cell, intrinsics::transmute, marker::PhantomData, ptr::slice_from_raw_parts_mut,
+    sync::atomic::AtomicPtr,
+};
 
 use dpdk_sys::{
     rte_eth_call_rx_callbacks, rte_eth_dev_is_valid_port, rte_eth_fp_ops, rte_mbuf,
     RTE_MAX_ETHPORTS, RTE_MAX_QUEUES_PER_PORT,
 };
 
-use super::dev::{EthdevPortId, EventQueueId};
+use super::{
+    dev::{EthdevPortId, EventQueueId},
+    queue::{Direction, QueueClaim, QueueError},
+};
+
+/// The only handle to an rx queue of a started [`EthPort`](super::port::EthPort), which it borrows
+/// so the port can not be stopped or dropped while the queue is in use.
+///
+/// A handle can be moved to the lcore that polls the queue but not shared between lcores:
+///
+/// ```compile_fail
+/// fn shared<T: Sync>() {}
+/// shared::<dpdk::device::eth::rx::RxQueue<'static>>();
+/// ```
+#[derive(Debug)]
+pub struct RxQueue<'port> {
+    claim: QueueClaim,
+    _port: PhantomData<&'port ()>,
+    _not_sync: PhantomData<Cell<()>>,
+}
+
+impl<'port> RxQueue<'port> {
+    /// Fails if the port is not started, has no such queue or there already is a handle for it.
+    pub(super) fn new(port_id: EthdevPortId, queue_id: u16) -> Result<Self, QueueError> {
+        Ok(Self {
+            claim: QueueClaim::new(port_id, Direction::Rx, queue_id)?,
+            _port: PhantomData,
+            _not_sync: PhantomData,
+        })
+    }
+
+    pub fn port_id(&self) -> EthdevPortId {
+        self.claim.port()
+    }
+
+    pub fn queue_id(&self) -> u16 {
+        self.claim.queue()
+    }
 
-pub fn receive_burst<const NUM_PACKETS: usize>(
+    /// Fills the front of `rx_buffer` with received packets and returns how many there are.
+    #[inline]
+    pub fn receive_burst<const NUM_PACKETS: usize>(
+        &mut self,
+        rx_buffer: &mut [&mut rte_mbuf; NUM_PACKETS],
+    ) -> u16 {
+        // The handle was checked on creation and is the only one for the queue
+        unsafe { burst(self.claim.port(), self.claim.queue(), rx_buffer) }
+    }
+}
+
+/// # Safety
+///
+/// The port has to be started and have the queue, and no other thread may use the queue at the
+/// same time. Only checked in debug builds, an [`RxQueue`] makes sure of all of this.
+pub unsafe fn receive_burst<const NUM_PACKETS: usize>(
     port_id: EthdevPortId,
     queue_id: EventQueueId,
     rx_buffer: &mut [&mut rte_mbuf; NUM_PACKETS],
@@ -24,7 +79,15 @@ pub fn receive_burst<const NUM_PACKETS: usize>(
         unsafe { rte_eth_dev_is_valid_port(port_id) } == 1,
         "Invalid port id {port_id}"
     );
+    burst(port_id, queue_id, rx_buffer)
+}
 
+#[inline(always)]
+unsafe fn burst<const NUM_PACKETS: usize>(
+    port_id: EthdevPortId,
+    queue_id: EventQueueId,
+    rx_buffer: &mut [&mut rte_mbuf; NUM_PACKETS],
+) -> u16 {
     let queue_data_pointer: &rte_eth_fp_ops = unsafe { &rte_eth_fp_ops[port_id as usize] };
     let queue_data = unsafe {
         let queue_data_pointer_rxq_data = queue_data_pointer.rxq.data;
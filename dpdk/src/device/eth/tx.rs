@@ -1,16 +1,79 @@
-use std::{intrinsics::transmute, ptr::slice_from_raw_parts_mut, sync::atomic::AtomicPtr};
+use std::{
+    cell::Cell, intrinsics::transmute, marker::PhantomData, ptr::slice_from_raw_parts_mut,
+    sync::atomic::AtomicPtr,
+};
 
 use dpdk_sys::{
     rte_eth_dev_is_valid_port, rte_eth_fp_ops, rte_mbuf,
     RTE_MAX_ETHPORTS, RTE_MAX_QUEUES_PER_PORT, rte_eth_call_tx_callbacks,
 };
 
-use super::dev::{EthdevPortId, EventQueueId};
+use super::{
+    dev::{EthdevPortId, EventQueueId},
+    queue::{Direction, QueueClaim, QueueError},
+};
+
+/// The only handle to a tx queue of a started [`EthPort`](super::port::EthPort), which it borrows
+/// so the port can not be stopped or dropped while the queue is in use.
+///
+/// A handle can be moved to the lcore that sends on the queue but not shared between lcores:
+///
+/// ```compile_fail
+/// fn shared<T: Sync>() {}
+/// shared::<dpdk::device::eth::tx::TxQueue<'static>>();
+/// ```
+#[derive(Debug)]
+pub struct TxQueue<'port> {
+    claim: QueueClaim,
+    _port: PhantomData<&'port ()>,
+    _not_sync: PhantomData<Cell<()>>,
+}
+
+impl<'port> TxQueue<'port> {
+    /// Fails if the port is not started, has no such queue or there already is a handle for it.
+    pub(super) fn new(port_id: EthdevPortId, queue_id: u16) -> Result<Self, QueueError> {
+        Ok(Self {
+            claim: QueueClaim::new(port_id, Direction::Tx, queue_id)?,
+            _port: PhantomData,
+            _not_sync: PhantomData,
+        })
+    }
+
+    pub fn port_id(&self) -> EthdevPortId {
+        self.claim.port()
+    }
+
+    pub fn queue_id(&self) -> u16 {
+        self.claim.queue()
+    }
 
-pub fn send_burst<const NUM_PACKETS: usize>(port_id: EthdevPortId, queue_id: EventQueueId, tx_buffer: &mut[&mut rte_mbuf; NUM_PACKETS], count: u16) -> u16 {
+    /// Sends the first `count` packets of `tx_buffer` and returns how many were taken, the rest
+    /// still belong to the caller.
+    #[inline]
+    pub fn send_burst<const NUM_PACKETS: usize>(
+        &mut self,
+        tx_buffer: &mut [&mut rte_mbuf; NUM_PACKETS],
+        count: u16,
+    ) -> u16 {
+        debug_assert!(count as usize <= NUM_PACKETS);
+        // The handle was checked on creation and is the only one for the queue
+        unsafe { burst(self.claim.port(), self.claim.queue(), tx_buffer, count) }
+    }
+}
+
+/// # Safety
+///
+/// The port has to be started and have the queue, and no other thread may use the queue at the
+/// same time. Only checked in debug builds, a [`TxQueue`] makes sure of all of this.
+pub unsafe fn send_burst<const NUM_PACKETS: usize>(port_id: EthdevPortId, queue_id: EventQueueId, tx_buffer: &mut[&mut rte_mbuf; NUM_PACKETS], count: u16) -> u16 {
     debug_assert!(u32::from(port_id) < RTE_MAX_ETHPORTS, "Invalid port id {port_id}, maximum is {RTE_MAX_ETHPORTS}.");
     debug_assert!(u32::from(queue_id) < RTE_MAX_QUEUES_PER_PORT, "Invalid queue id {queue_id}, maximum is {RTE_MAX_QUEUES_PER_PORT}");
     debug_assert!(unsafe { rte_eth_dev_is_valid_port(port_id) } == 1, "Invalid port id {port_id}");
+    burst(port_id, queue_id, tx_buffer, count)
+}
+
+#[inline(always)]
+unsafe fn burst<const NUM_PACKETS: usize>(port_id: EthdevPortId, queue_id: EventQueueId, tx_buffer: &mut[&mut rte_mbuf; NUM_PACKETS], count: u16) -> u16 {
 
     let queue_data_pointer: &rte_eth_fp_ops = unsafe { &rte_eth_fp_ops[port_id as usize] };
     let queue_data = unsafe {
@@ -2,10 +2,10 @@ use std::{backtrace::Backtrace, mem::MaybeUninit};
 
 use dpdk_sys::{
     rte_event_dev_config, rte_event_dev_configure, rte_event_dev_info, rte_event_dev_info_get,
-    rte_event_dev_start, ENOLINK, ESTALE,
+    rte_event_dev_start,
 };
 
-use crate::device::eth::dev::iter_ports;
+use crate::{device::eth::dev::iter_ports, error::DpdkError};
 
 use super::{
     eth::rx::{get_rx_capabilities_by_id, EventDevRxCapabilities},
@@ -16,41 +16,26 @@ use derive_builder::Builder;
 
 #[derive(Debug, thiserror::Error)]
 pub enum EventDevConfigDriverError {
-    #[error("Failed to configure device {eventdev_id}, recieved driver error {driver_error}")]
-    DriverConfigurationFailureError {
-        eventdev_id: EventDeviceId,
-        driver_error: i32,
-        backtrace: Backtrace,
-    },
+    #[error(transparent)]
+    Dpdk(#[from] DpdkError),
     #[error("Invalid configuration")]
     InvalidConfiguration(#[from] EventDevConfigBuilderError, Backtrace)
 }
 
-pub fn get_eventdev_info(
-    device_id: EventDeviceId,
-) -> Result<rte_event_dev_info, EventDevConfigDriverError> {
+pub fn get_eventdev_info(device_id: EventDeviceId) -> Result<rte_event_dev_info, DpdkError> {
     let mut eventdev_info: rte_event_dev_info = unsafe { MaybeUninit::zeroed().assume_init() };
 
     let err = unsafe { rte_event_dev_info_get(device_id, &mut eventdev_info) };
 
-    if err != 0 {
-        Err(EventDevConfigDriverError::DriverConfigurationFailureError {
-            eventdev_id: device_id,
-            driver_error: err,
-            backtrace: Backtrace::capture(),
-        })
-    } else {
-        Ok(eventdev_info)
-    }
+    DpdkError::check("rte_event_dev_info_get", err).map_err(|err| err.with_eventdev(device_id))?;
+    Ok(eventdev_info)
 }
 
-pub fn do_capability_setup(
-    device_id: EventDeviceId,
-) -> Result<rte_event_dev_info, EventDevConfigDriverError> {
+pub fn do_capability_setup(device_id: EventDeviceId) -> Result<rte_event_dev_info, DpdkError> {
     let mut capabilities = EventDevRxCapabilities::empty();
 
     for ethdev_id in iter_ports() {
-        capabilities = capabilities & (get_rx_capabilities_by_id(device_id, ethdev_id))
+        capabilities = capabilities & (get_rx_capabilities_by_id(device_id, ethdev_id)?)
     }
     let _pipeline_rx_capabilities = capabilities | EventDevRxCapabilities::HW_PACKET_TRANSFER;
 
@@ -100,13 +85,13 @@ pub fn setup_eventdev_simple(
         .num_ports(num_ports)
         .num_event_queues(num_event_queues)
         .build()?;
-    configure_eventdev(device_id, &config)
+    Ok(configure_eventdev(device_id, &config)?)
 }
 
 pub fn configure_eventdev(
     device_id: EventDeviceId,
     config: &EventDevConfig,
-) -> Result<(), EventDevConfigDriverError> {
+) -> Result<(), DpdkError> {
     let config = rte_event_dev_config {
         dequeue_timeout_ns: config.dequeue_timeout,
         nb_events_limit: config.event_limit_amount,
@@ -118,41 +103,23 @@ pub fn configure_eventdev(
         event_dev_cfg: config.event_dev_cfg,
         nb_single_link_event_port_queues: config.num_single_link_port_event_queues,
     };
-    println!("Eventdev config: {config:#?}");
+    log::debug!("Eventdev config: {config:#?}");
     let ret = unsafe { rte_event_dev_configure(device_id, &config) };
 
-    if ret == 0 {
-        Ok(())
-    } else {
-        Err(EventDevConfigDriverError::DriverConfigurationFailureError {
-            eventdev_id: device_id,
-            driver_error: ret,
-            backtrace: Backtrace::capture(),
-        })
-    }
+    DpdkError::check("rte_event_dev_configure", ret).map_err(|err| err.with_eventdev(device_id))
 }
 
 pub mod event_queue_config {
-    use std::backtrace::Backtrace;
-
     use dpdk_sys::{
         rte_event_queue_conf, rte_event_queue_setup, RTE_EVENT_DEV_PRIORITY_NORMAL,
         RTE_EVENT_QUEUE_CFG_SINGLE_LINK, RTE_SCHED_TYPE_ATOMIC, RTE_SCHED_TYPE_ORDERED,
         RTE_SCHED_TYPE_PARALLEL,
     };
 
-    use crate::device::{eth::dev::EventQueueId, event::EventDeviceId};
-
-    #[derive(Debug, thiserror::Error)]
-    pub enum EventDevQueueConfigError {
-        #[error("Invalid config for device {device_id}, queue {queue_id}, rte_event_queue_setup returned {code}")]
-        ConfigurationFailure {
-            device_id: EventDeviceId,
-            queue_id: EventQueueId,
-            code: i32,
-            backtrace: Backtrace,
-        },
-    }
+    use crate::{
+        device::{eth::dev::EventQueueId, event::EventDeviceId},
+        error::DpdkError,
+    };
 
     #[derive(Debug, Clone, Copy)]
     pub enum EventQueueConfigType {
@@ -186,19 +153,11 @@ pub mod event_queue_config {
             &self,
             device_id: EventDeviceId,
             queue_id: EventQueueId,
-        ) -> Result<(), EventDevQueueConfigError> {
+        ) -> Result<(), DpdkError> {
             let queue_conf: rte_event_queue_conf = self.into();
             let ret = unsafe { rte_event_queue_setup(device_id, queue_id as u8, &queue_conf) };
-            if ret == 0 {
-                Ok(())
-            } else {
-                Err(EventDevQueueConfigError::ConfigurationFailure {
-                    device_id,
-                    queue_id,
-                    code: ret,
-                    backtrace: Backtrace::capture(),
-                })
-            }
+            DpdkError::check("rte_event_queue_setup", ret)
+                .map_err(|err| err.with_eventdev(device_id).with_queue(queue_id))
         }
     }
 
@@ -239,46 +198,19 @@ pub mod event_queue_config {
     }
 }
 pub mod event_port_config {
-    use std::{backtrace::Backtrace, mem::MaybeUninit};
+    use std::mem::MaybeUninit;
 
     use dpdk_sys::{
         rte_event_port_conf, rte_event_port_default_conf_get, rte_event_port_link,
-        rte_event_port_setup, EDQUOT,
+        rte_event_port_setup,
     };
 
     use crate::{
         device::event::{EventDeviceId, EventPortId},
         eal::RteErrnoValue,
+        error::DpdkError,
     };
 
-    #[derive(Debug, thiserror::Error)]
-    pub enum EventPortConfigError {
-        #[error("Application tried to link port {port_id} on device {device_id} configured with RTE_EVENT_QUEUE_CFG_SINGLE_LINK to more than one event ports")]
-        TooManyLinksToQueue {
-            device_id: EventDeviceId,
-            port_id: EventPortId,
-            backtrace: Backtrace,
-        },
-        #[error(
-            "Error configuring port {port_id} on device {device_id}. Got error code {error_code}."
-        )]
-        GenericPortConfigError {
-            device_id: EventDeviceId,
-            port_id: EventPortId,
-            error_code: i32,
-            backtrace: Backtrace,
-        },
-        #[error(
-            "Invalid parameter for linking port {port_id} on device {device_id}, actually linked {num_linked}"
-        )]
-        InvalidLinkParameters {
-            device_id: EventDeviceId,
-            port_id: EventPortId,
-            num_linked: u32,
-            backtrace: Backtrace,
-        },
-    }
-
     pub struct EventPortConfig {
         pub new_event_threshold: i32,
         pub dequeue_depth: u16,
@@ -300,26 +232,19 @@ pub mod event_port_config {
     }
 
     impl EventPortConfig {
+        /// Fails with `EDQUOT` if the port is linked to a single link queue that already has a
+        /// port.
         pub fn setup_port(
             &self,
             device_id: EventDeviceId,
             port_id: EventPortId,
-        ) -> Result<(), EventPortConfigError> {
+        ) -> Result<(), DpdkError> {
             let config: rte_event_port_conf = self.into();
             let ret = unsafe { rte_event_port_setup(device_id as u8, port_id as u8, &config) };
-            if ret == -1 * EDQUOT as i32 {
-                Err(EventPortConfigError::TooManyLinksToQueue {
-                    device_id,
-                    port_id,
-                    backtrace: Backtrace::capture(),
-                })
-            } else if ret < 0 {
-                Err(EventPortConfigError::GenericPortConfigError {
-                    device_id,
-                    port_id,
-                    error_code: ret,
-                    backtrace: Backtrace::capture(),
-                })
+            if ret < 0 {
+                Err(DpdkError::from_return("rte_event_port_setup", ret)
+                    .with_eventdev(device_id)
+                    .with_event_port(port_id))
             } else {
                 Ok(())
             }
@@ -348,11 +273,13 @@ pub mod event_port_config {
         }
     }
 
+    /// Fails with `EDQUOT` if one of the queues is a single link queue that already has a port.
+    /// Links that were made before the error are kept.
     pub fn link_port_to_queue(
         device_id: EventDeviceId,
         port_id: EventPortId,
         queue_ids: &[u8],
-    ) -> Result<(), EventPortConfigError> {
+    ) -> Result<(), DpdkError> {
         RteErrnoValue::clear();
         let ret = unsafe {
             rte_event_port_link(
@@ -364,49 +291,25 @@ pub mod event_port_config {
             )
         };
         if ret != queue_ids.len() as i32 {
-            match RteErrnoValue::most_recent() {
-                RteErrnoValue::EDQUOT => Err(EventPortConfigError::TooManyLinksToQueue {
-                    device_id,
-                    port_id,
-                    backtrace: Backtrace::capture(),
-                }),
-                RteErrnoValue::EINVAL => Err(EventPortConfigError::InvalidLinkParameters {
-                    device_id,
-                    port_id,
-                    num_linked: ret as u32,
-                    backtrace: Backtrace::capture(),
-                }),
-                other => {
-                    panic!("Unhandled rte errono value {other:?}")
-                }
+            log::warn!(
+                "Linked {ret} of {} queues to event port {port_id} on eventdev {device_id}",
+                queue_ids.len()
+            );
+            let mut err = DpdkError::from_rte_errno("rte_event_port_link");
+            if err.errno() == 0 {
+                // Some drivers link fewer queues without setting rte_errno
+                err = DpdkError::from_errno("rte_event_port_link", RteErrnoValue::EINVAL as i32);
             }
+            Err(err.with_eventdev(device_id).with_event_port(port_id))
         } else {
             Ok(())
         }
     }
 }
 
-#[derive(Debug, thiserror::Error)]
-pub enum EventDevStartErrors {
-    #[error("Not all ports were started")]
-    NotAllPortsStartedError(Backtrace),
-    #[error("Not all queues are linked")]
-    NotAllQueuesLinkedError(Backtrace),
-}
-
-pub fn start_event_dev(device_id: EventDeviceId) -> Result<(), EventDevStartErrors> {
+/// Fails with `ESTALE` if not all ports were set up and with `ENOLINK` if not all queues are
+/// linked to a port.
+pub fn start_event_dev(device_id: EventDeviceId) -> Result<(), DpdkError> {
     let ret = unsafe { rte_event_dev_start(device_id) };
-    if ret == 0 {
-        Ok(())
-    } else if ret == -1 * ESTALE as i32 {
-        Err(EventDevStartErrors::NotAllPortsStartedError(
-            Backtrace::capture(),
-        ))
-    } else if ret == -1 * ENOLINK as i32 {
-        Err(EventDevStartErrors::NotAllQueuesLinkedError(
-            Backtrace::capture(),
-        ))
-    } else {
-        panic!("Unhandled error, {ret}");
-    }
+    DpdkError::check("rte_event_dev_start", ret).map_err(|err| err.with_eventdev(device_id))
 }
@@ -1,4 +1,7 @@
-use crate::device::{event::EventDeviceId, eth::dev::EthdevPortId};
+use crate::{
+    device::{eth::dev::EthdevPortId, event::EventDeviceId},
+    error::DpdkError,
+};
 use bitflags::bitflags;
 use dpdk_sys::rte_event_eth_rx_adapter_caps_get;
 
@@ -25,49 +28,35 @@ bitflags!{
 pub fn get_rx_capabilities_by_id(
     eventdev_id: EventDeviceId,
     ethdev_id: EthdevPortId,
-) -> EventDevRxCapabilities {
+) -> Result<EventDevRxCapabilities, DpdkError> {
     let mut caps: u32 = 0;
     let ret =
         unsafe { rte_event_eth_rx_adapter_caps_get(eventdev_id, ethdev_id, &mut caps as *mut u32) };
-    assert!(ret == 0, "Invalid capabilities for rx port {ethdev_id}");
+    DpdkError::check("rte_event_eth_rx_adapter_caps_get", ret)
+        .map_err(|err| err.with_eventdev(eventdev_id).with_port(ethdev_id))?;
 
-    return EventDevRxCapabilities::from_bits_truncate(caps);
+    Ok(EventDevRxCapabilities::from_bits_truncate(caps))
 }
 
 pub mod rx_adapter {
-    use std::backtrace::Backtrace;
-
     use dpdk_sys::{
         rte_event_eth_rx_adapter_create,
         rte_event_eth_rx_adapter_queue_add, rte_event_eth_rx_adapter_queue_conf,
         rte_event_eth_rx_adapter_start, rte_event_port_conf, rte_mempool,
-        EINVAL, EIO, ENOMEM, RTE_EVENT_PORT_CFG_HINT_PRODUCER, rte_event_eth_rx_adapter_stop,
+        RTE_EVENT_PORT_CFG_HINT_PRODUCER, rte_event_eth_rx_adapter_stop,
     };
     
 
-    use crate::device::{
-        eth::dev::EthdevPortId,
-        event::{
-            dev::{
-                get_eventdev_info, EventDevConfigDriverError,
-            },
-            event::Event,
-            EventDeviceId,
+    use crate::{
+        device::{
+            eth::dev::EthdevPortId,
+            event::{dev::get_eventdev_info, event::Event, EventDeviceId},
         },
+        error::DpdkError,
     };
 
     pub type RxAdapterId = u8;
 
-    #[derive(Debug, thiserror::Error)]
-    pub enum NewRxAdapterErrors {
-        #[error("Invalid ethernet device id {ethdev_id}")]
-        InvalidEtherDevice { ethdev_id: u8, backtrace: Backtrace },
-        #[error("OOM: Ran out of memory allocating space for port configuration.")]
-        OOM { backtrace: Backtrace },
-        #[error("Unable to get eventdev info for event device")]
-        EventDevInfoError(#[from] EventDevConfigDriverError),
-    }
-
     pub enum RxAdapterQueueConfigExtensions {
         None,
         Vector {
@@ -113,17 +102,6 @@ pub mod rx_adapter {
         }
     }
 
-    #[derive(Debug, thiserror::Error)]
-    pub enum AddQueueToRxAdapterErrors {
-        #[error("While rx adapter {adapter_id} was trying to add a queue to event device {event_device_id} to handle input from port {ether_device_id}, restart was rejected, causing a configuration error.")]
-        ConfigurationAndRestartError {
-            adapter_id: RxAdapterId,
-            event_device_id: EventDeviceId,
-            ether_device_id: EthdevPortId,
-            backtrace: Backtrace,
-        },
-    }
-
     pub struct RxAdapter {
         adapter_id: RxAdapterId,
         event_device_id: EventDeviceId,
@@ -135,7 +113,7 @@ pub mod rx_adapter {
             adapter_id: RxAdapterId,
             eventdev_id: EventDeviceId,
             ethdev_id: EthdevPortId,
-        ) -> Result<RxAdapter, NewRxAdapterErrors> {
+        ) -> Result<RxAdapter, DpdkError> {
             let dev_info = get_eventdev_info(eventdev_id)?;
 
             let mut event_port_config = rte_event_port_conf {
@@ -146,31 +124,35 @@ pub mod rx_adapter {
             };
 
             let err = unsafe {
-                rte_event_eth_rx_adapter_create(adapter_id, ethdev_id as u8, &mut event_port_config)
+                rte_event_eth_rx_adapter_create(adapter_id, eventdev_id, &mut event_port_config)
             };
+            DpdkError::check("rte_event_eth_rx_adapter_create", err).map_err(|err| {
+                err.with_adapter(adapter_id)
+                    .with_eventdev(eventdev_id)
+                    .with_port(ethdev_id)
+            })?;
+
+            Ok(RxAdapter {
+                adapter_id,
+                event_device_id: eventdev_id,
+                ether_device_id: ethdev_id,
+            })
+        }
 
-            match (err * -1) as u32 {
-                EINVAL => Err(NewRxAdapterErrors::InvalidEtherDevice {
-                    ethdev_id: ethdev_id as u8,
-                    backtrace: Backtrace::capture(),
-                }),
-                ENOMEM => Err(NewRxAdapterErrors::OOM {
-                    backtrace: Backtrace::capture(),
-                }),
-                0 => Ok(RxAdapter {
-                    adapter_id,
-                    event_device_id: eventdev_id,
-                    ether_device_id: ethdev_id,
-                }),
-                x => unimplemented!("Unknown error code {x}"),
-            }
+        fn error(&self, operation: &'static str, ret: i32) -> DpdkError {
+            DpdkError::from_return(operation, ret)
+                .with_adapter(self.adapter_id)
+                .with_eventdev(self.event_device_id)
+                .with_port(self.ether_device_id)
         }
 
+        /// `rx_queue_id` -1 adds every rx queue of the port. Fails with `EIO` if the adapter
+        /// had to be stopped for this and could not be restarted.
         pub fn add_rx_queue(
             &mut self,
             queue_config: RxAdapterQueueConfig,
             rx_queue_id: i32,
-        ) -> Result<(), AddQueueToRxAdapterErrors> {
+        ) -> Result<(), DpdkError> {
             let config = &queue_config.into();
             let err = unsafe {
                 
@@ -181,30 +163,23 @@ pub mod rx_adapter {
                     config,
                 )
             };
-            match (err * -1) as u32 {
-                0 => Ok(()),
-                EIO => Err(AddQueueToRxAdapterErrors::ConfigurationAndRestartError {
-                    adapter_id: self.adapter_id,
-                    event_device_id: self.event_device_id,
-                    ether_device_id: self.ether_device_id,
-                    backtrace: Backtrace::capture(),
-                }),
-                EINVAL => {
-                    panic!("Check logs")
-                }
-                ENOMEM => {
-                    panic!("OOM")
-                }
-                x => unimplemented!("Unknown error code {x}"),
+            if err == 0 {
+                Ok(())
+            } else if rx_queue_id < 0 {
+                Err(self.error("rte_event_eth_rx_adapter_queue_add", err))
+            } else {
+                Err(self
+                    .error("rte_event_eth_rx_adapter_queue_add", err)
+                    .with_queue(rx_queue_id as u16))
             }
         }
 
-        pub fn start_forwarding_packets(self) {
+        pub fn start_forwarding_packets(self) -> Result<(), DpdkError> {
             let err = unsafe { rte_event_eth_rx_adapter_start(self.adapter_id) };
-            match (err * -1) as u32 {
-                0 => {}
-                EINVAL => unreachable!("Adapter ID should always be a real adapter at this point"),
-                x => unimplemented!("Unknown error code {x}"),
+            if err == 0 {
+                Ok(())
+            } else {
+                Err(self.error("rte_event_eth_rx_adapter_start", err))
             }
         }
     }
@@ -1,6 +1,9 @@
 use dpdk_sys::rte_event_eth_tx_adapter_caps_get;
 
-use crate::device::{eth::dev::EthdevPortId, event::EventDeviceId};
+use crate::{
+    device::{eth::dev::EthdevPortId, event::EventDeviceId},
+    error::DpdkError,
+};
 use bitflags::bitflags;
 
 
@@ -70,59 +73,41 @@ bitflags! {
 pub fn get_tx_capabilities_by_id(
     eventdev_id: EventDeviceId,
     ethdev_id: EthdevPortId,
-) -> EventDevTxCapabilities {
+) -> Result<EventDevTxCapabilities, DpdkError> {
     let mut caps: u32 = 0;
     let ret =
         unsafe { rte_event_eth_tx_adapter_caps_get(eventdev_id, ethdev_id, &mut caps as *mut u32) };
-    assert!(ret == 0, "Invalid capabilities for rx port {ethdev_id}");
+    DpdkError::check("rte_event_eth_tx_adapter_caps_get", ret)
+        .map_err(|err| err.with_eventdev(eventdev_id).with_port(ethdev_id))?;
 
-    return EventDevTxCapabilities::from_bits_truncate(caps);
+    Ok(EventDevTxCapabilities::from_bits_truncate(caps))
 }
 
 pub mod tx_adapter {
-    use std::backtrace::Backtrace;
-
     use dpdk_sys::{
-        rte_event_eth_tx_adapter_create, rte_event_port_conf, RTE_EVENT_PORT_CFG_HINT_CONSUMER, rte_event_eth_tx_adapter_queue_add, rte_event_eth_tx_adapter_start, ENOMEM, EINVAL, rte_event_eth_tx_adapter_stop,
+        rte_event_eth_tx_adapter_create, rte_event_port_conf, RTE_EVENT_PORT_CFG_HINT_CONSUMER, rte_event_eth_tx_adapter_queue_add, rte_event_eth_tx_adapter_start, rte_event_eth_tx_adapter_stop,
     };
 
-    use crate::device::{event::{
-        dev::{get_eventdev_info, EventDevConfigDriverError},
-        EventDeviceId,
-    }, eth::dev::EthdevPortId};
+    use crate::{
+        device::{
+            eth::dev::EthdevPortId,
+            event::{dev::get_eventdev_info, EventDeviceId},
+        },
+        error::DpdkError,
+    };
 
     pub type TxAdapterId = u8;
 
-    #[derive(Debug, thiserror::Error)]
-    pub enum NewTxAdapterErrors {
-        #[error("Invalid ethernet device id {ethdev_id}")]
-        InvalidEtherDevice { ethdev_id: u8, backtrace: Backtrace },
-        #[error("OOM: Ran out of memory allocating space for port configuration.")]
-        OOM { backtrace: Backtrace },
-        #[error("Unable to get eventdev info for event device")]
-        EventDevInfoError(#[from] EventDevConfigDriverError),
-    }
-
-    #[derive(Debug, thiserror::Error)]
-    pub enum AddQueueToTxAdapterErrors {
-        #[error("While rx adapter {adapter_id} was trying to add a queue to event device {event_device_id} to handle input from port {ether_device_id}, restart was rejected, causing a configuration error.")]
-        ConfigurationAndRestartError {
-            adapter_id: TxAdapterId,
-            event_device_id: EventDeviceId,
-            ether_device_id: EthdevPortId,
-            backtrace: Backtrace,
-        },
-    }
-
     pub struct TxAdapter {
-        id: TxAdapterId
+        id: TxAdapterId,
+        event_device_id: EventDeviceId,
     }
 
     impl TxAdapter {
         pub fn new(
             adapter_id: TxAdapterId,
             eventdev_id: EventDeviceId,
-        ) -> Result<TxAdapter, NewTxAdapterErrors> {
+        ) -> Result<TxAdapter, DpdkError> {
             let dev_info = get_eventdev_info(eventdev_id)?;
 
             let mut event_port_config = rte_event_port_conf {
@@ -136,22 +121,27 @@ pub mod tx_adapter {
                 rte_event_eth_tx_adapter_create(adapter_id, eventdev_id, &mut event_port_config)
             };
 
-            match (err * -1) as u32 {
-                ENOMEM => Err(NewTxAdapterErrors::OOM {
-                    backtrace: Backtrace::capture(),
-                }),
-                0 => Ok(TxAdapter {
-                    id: adapter_id,
-                }),
-                x => unimplemented!("Unknown error code {x}"),
-            }
+            DpdkError::check("rte_event_eth_tx_adapter_create", err)
+                .map_err(|err| err.with_adapter(adapter_id).with_eventdev(eventdev_id))?;
+
+            Ok(TxAdapter {
+                id: adapter_id,
+                event_device_id: eventdev_id,
+            })
+        }
+
+        fn error(&self, operation: &'static str, ret: i32) -> DpdkError {
+            DpdkError::from_return(operation, ret)
+                .with_adapter(self.id)
+                .with_eventdev(self.event_device_id)
         }
 
+        /// `ethdev_queue_id` -1 adds every tx queue of the port.
         pub fn add_tx_queue(
             &mut self,
             eth_dev_id: EthdevPortId,
             ethdev_queue_id: i32,
-        ) {
+        ) -> Result<(), DpdkError> {
             let err = unsafe {
                 rte_event_eth_tx_adapter_queue_add(
                     self.id,
@@ -159,26 +149,26 @@ pub mod tx_adapter {
                     ethdev_queue_id,
                 )
             };
-            match (err * -1) as u32 {
-                0 => {},
-                EINVAL => {
-                    panic!("Check logs");
-                }
-                ENOMEM => {
-                    panic!("OOM");
-                }
-                x => {
-                    unimplemented!("Unknown error code {x}");
-                }
+            if err == 0 {
+                Ok(())
+            } else if ethdev_queue_id < 0 {
+                Err(self
+                    .error("rte_event_eth_tx_adapter_queue_add", err)
+                    .with_port(eth_dev_id))
+            } else {
+                Err(self
+                    .error("rte_event_eth_tx_adapter_queue_add", err)
+                    .with_port(eth_dev_id)
+                    .with_queue(ethdev_queue_id as u16))
             }
         }
 
-        pub fn start_sending_packets(self) {
+        pub fn start_sending_packets(self) -> Result<(), DpdkError> {
             let err = unsafe { rte_event_eth_tx_adapter_start(self.id) };
-            match (err * -1) as u32 {
-                0 => {}
-                EINVAL => unreachable!("Adapter ID should always be a real adapter at this point"),
-                x => unimplemented!("Unknown error code {x}"),
+            if err == 0 {
+                Ok(())
+            } else {
+                Err(self.error("rte_event_eth_tx_adapter_start", err))
             }
         }
     }
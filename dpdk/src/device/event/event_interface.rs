@@ -73,6 +73,7 @@ pub fn enqueue_tx_adapter_events(
     }
 }
 
+/// `timeout` is in device ticks, see [`crate::time::dequeue_timeout_ticks`].
 #[inline(always)]
 pub fn dequeue_events(
     eventdev_id: EventDeviceId,
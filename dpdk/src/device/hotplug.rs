@@ -0,0 +1,292 @@
+use std::{
+    backtrace::Backtrace,
+    ffi::{CStr, CString},
+    fmt::Display,
+    mem::MaybeUninit,
+};
+
+use dpdk_sys::{
+    rte_dev_event_callback_register, rte_dev_event_callback_unregister,
+    rte_dev_event_monitor_start, rte_dev_event_monitor_stop, rte_dev_event_type,
+    rte_dev_hotplug_handle_disable, rte_dev_hotplug_handle_enable, rte_dev_iterator,
+    rte_dev_probe, rte_devargs, rte_devargs_parse, rte_devargs_reset, rte_eal_hotplug_remove,
+    rte_eth_dev_close, rte_eth_dev_stop, rte_eth_iterator_init, rte_eth_iterator_next,
+    ENODEV, RTE_MAX_ETHPORTS,
+};
+use libc::c_void;
+
+use crate::{
+    callback::RegisteredCallback, device::eth::dev::EthdevPortId, eal::RteErrnoValue,
+    error::DpdkError, util::str_to_c_string,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum HotplugError {
+    #[error("Device {devargs} was probed but has no ethernet ports")]
+    NoPorts { devargs: String, backtrace: Backtrace },
+    #[error(transparent)]
+    Dpdk(#[from] DpdkError),
+}
+
+/// A parsed device string, `bus:device,key=value,...` where the bus is optional.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Devargs {
+    pub bus: String,
+    pub name: String,
+    pub args: String,
+}
+
+impl Display for Devargs {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if !self.bus.is_empty() {
+            write!(f, "{}:", self.bus)?;
+        }
+        write!(f, "{}", self.name)?;
+        if !self.args.is_empty() {
+            write!(f, ",{}", self.args)?;
+        }
+        Ok(())
+    }
+}
+
+impl Devargs {
+    /// Asks the EAL which bus handles the device, so this needs an initialized EAL.
+    pub fn parse(devargs: &str) -> Result<Self, DpdkError> {
+        let c_devargs = CString::new(devargs).map_err(|_| {
+            DpdkError::from_errno("rte_devargs_parse", RteErrnoValue::EINVAL as i32)
+                .with_name(devargs)
+        })?;
+
+        let mut raw = unsafe { MaybeUninit::<rte_devargs>::zeroed().assume_init() };
+        let ret = unsafe { rte_devargs_parse(&mut raw, c_devargs.as_ptr()) };
+        DpdkError::check("rte_devargs_parse", ret).map_err(|err| err.with_name(devargs))?;
+
+        let parsed = Devargs::from_raw(&raw);
+        unsafe { rte_devargs_reset(&mut raw) };
+        Ok(parsed)
+    }
+
+    pub(crate) fn from_raw(raw: &rte_devargs) -> Self {
+        let to_string = |ptr: *const libc::c_char| {
+            if ptr.is_null() {
+                String::new()
+            } else {
+                unsafe { CStr::from_ptr(ptr) }.to_string_lossy().into_owned()
+            }
+        };
+        Devargs {
+            bus: if raw.bus.is_null() {
+                String::new()
+            } else {
+                to_string(unsafe { (*raw.bus).name })
+            },
+            name: to_string(raw.name.as_ptr()),
+            args: to_string(unsafe { raw.__bindgen_anon_1.args }),
+        }
+    }
+}
+
+/// A device attached with [`attach`]. Dropping it leaves the device attached, use
+/// [`AttachedDevice::detach`] to remove it.
+#[derive(Debug)]
+pub struct AttachedDevice {
+    devargs: Devargs,
+    ports: Vec<EthdevPortId>,
+}
+
+impl AttachedDevice {
+    pub fn devargs(&self) -> &Devargs {
+        &self.devargs
+    }
+
+    /// The first port of the device, most devices only have one.
+    pub fn port(&self) -> EthdevPortId {
+        self.ports[0]
+    }
+
+    pub fn ports(&self) -> &[EthdevPortId] {
+        &self.ports
+    }
+
+    /// Stops and closes every port of the device, then removes it from its bus. In multi-process
+    /// setups the other processes detach it too.
+    pub fn detach(self) -> Result<(), DpdkError> {
+        for &port in &self.ports {
+            // ENODEV means the port is already gone, for example after the device was unplugged
+            let ret = unsafe { rte_eth_dev_stop(port) };
+            if ret != -(ENODEV as i32) {
+                DpdkError::check("rte_eth_dev_stop", ret).map_err(|err| err.with_port(port))?;
+            }
+            let ret = unsafe { rte_eth_dev_close(port) };
+            if ret != -(ENODEV as i32) {
+                DpdkError::check("rte_eth_dev_close", ret).map_err(|err| err.with_port(port))?;
+            }
+        }
+
+        let bus = str_to_c_string(&self.devargs.bus);
+        let name = str_to_c_string(&self.devargs.name);
+        let ret = unsafe { rte_eal_hotplug_remove(bus.as_ptr(), name.as_ptr()) };
+        DpdkError::check("rte_eal_hotplug_remove", ret)
+            .map_err(|err| err.with_name(format!("{}:{}", self.devargs.bus, self.devargs.name)))?;
+        Ok(())
+    }
+}
+
+/// Ethernet ports created for the devices matching `devargs`.
+fn ports_matching(devargs: &CStr) -> Vec<EthdevPortId> {
+    let mut iter = unsafe { MaybeUninit::<rte_dev_iterator>::zeroed().assume_init() };
+    if unsafe { rte_eth_iterator_init(&mut iter, devargs.as_ptr()) } != 0 {
+        return vec![];
+    }
+
+    // rte_eth_iterator_next cleans up the iterator once it runs out of ports
+    let mut ports = vec![];
+    loop {
+        let port = unsafe { rte_eth_iterator_next(&mut iter) };
+        if port as u32 >= RTE_MAX_ETHPORTS {
+            break;
+        }
+        ports.push(port);
+    }
+    ports
+}
+
+/// Probes a device after EAL init, for example `"net_ring0"`, `"net_pcap0,iface=eth0"` or
+/// `"0000:3b:00.0"`, and returns the ethernet ports it created. In multi-process setups the
+/// other processes attach it too.
+pub fn attach(devargs: &str) -> Result<AttachedDevice, HotplugError> {
+    let parsed = Devargs::parse(devargs)?;
+    let c_devargs = str_to_c_string(devargs);
+
+    let ret = unsafe { rte_dev_probe(c_devargs.as_ptr()) };
+    DpdkError::check("rte_dev_probe", ret).map_err(|err| err.with_name(devargs))?;
+
+    let ports = ports_matching(&c_devargs);
+    if ports.is_empty() {
+        return Err(HotplugError::NoPorts {
+            devargs: devargs.to_string(),
+            backtrace: Backtrace::capture(),
+        });
+    }
+
+    Ok(AttachedDevice {
+        devargs: parsed,
+        ports,
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeviceEvent {
+    Added,
+    Removed,
+}
+
+type DeviceEventCallback = dyn FnMut(&str, DeviceEvent) + Send;
+
+unsafe extern "C" fn device_event_trampoline(
+    device_name: *const libc::c_char,
+    event: rte_dev_event_type,
+    arg: *mut c_void,
+) {
+    let name = unsafe { CStr::from_ptr(device_name) }.to_string_lossy();
+    let event = match event {
+        rte_dev_event_type::RTE_DEV_EVENT_ADD => DeviceEvent::Added,
+        rte_dev_event_type::RTE_DEV_EVENT_REMOVE => DeviceEvent::Removed,
+        _ => return,
+    };
+    unsafe {
+        RegisteredCallback::<DeviceEventCallback>::call(
+            arg,
+            format_args!("Device event callback for {name}"),
+            |f| f(&name, event),
+        )
+    };
+}
+
+/// A registered device event callback, unregistered when dropped.
+///
+/// Events are only delivered while the monitor is running, see [`start_event_monitor`].
+pub struct DeviceEventHandle {
+    device_name: Option<CString>,
+    callback: RegisteredCallback<DeviceEventCallback>,
+}
+
+impl DeviceEventHandle {
+    fn device_name_ptr(&self) -> *const libc::c_char {
+        self.device_name
+            .as_ref()
+            .map(|name| name.as_ptr())
+            .unwrap_or(std::ptr::null())
+    }
+}
+
+impl Drop for DeviceEventHandle {
+    fn drop(&mut self) {
+        self.callback.unregister(|arg| unsafe {
+            rte_dev_event_callback_unregister(
+                self.device_name_ptr(),
+                Some(device_event_trampoline),
+                arg,
+            )
+        });
+    }
+}
+
+/// Calls `f` with the device name whenever a device is added or removed, or only for
+/// `device_name` if it is given. Callbacks run on the EAL interrupt thread.
+pub fn on_device_event<F>(
+    device_name: Option<&str>,
+    f: F,
+) -> Result<DeviceEventHandle, DpdkError>
+where
+    F: FnMut(&str, DeviceEvent) + Send + 'static,
+{
+    let callback = RegisteredCallback::<DeviceEventCallback>::new(Box::new(f));
+    let device_name = device_name.map(str_to_c_string);
+
+    let ret = unsafe {
+        rte_dev_event_callback_register(
+            device_name
+                .as_ref()
+                .map(|name| name.as_ptr())
+                .unwrap_or(std::ptr::null()),
+            Some(device_event_trampoline),
+            callback.arg(),
+        )
+    };
+    if ret != 0 {
+        return Err(DpdkError::from_return("rte_dev_event_callback_register", ret));
+    }
+
+    Ok(DeviceEventHandle {
+        device_name,
+        callback,
+    })
+}
+
+/// Starts listening for kernel uevents, needed for [`on_device_event`] callbacks to fire.
+pub fn start_event_monitor() -> Result<(), DpdkError> {
+    DpdkError::check("rte_dev_event_monitor_start", unsafe {
+        rte_dev_event_monitor_start()
+    })
+}
+
+pub fn stop_event_monitor() -> Result<(), DpdkError> {
+    DpdkError::check("rte_dev_event_monitor_stop", unsafe {
+        rte_dev_event_monitor_stop()
+    })
+}
+
+/// Makes the EAL catch the SIGBUS raised by accessing the memory of a device that was
+/// unplugged or reset, instead of crashing the process.
+pub fn enable_hotplug_handling() -> Result<(), DpdkError> {
+    DpdkError::check("rte_dev_hotplug_handle_enable", unsafe {
+        rte_dev_hotplug_handle_enable()
+    })
+}
+
+pub fn disable_hotplug_handling() -> Result<(), DpdkError> {
+    DpdkError::check("rte_dev_hotplug_handle_disable", unsafe {
+        rte_dev_hotplug_handle_disable()
+    })
+}
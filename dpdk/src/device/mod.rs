@@ -1,2 +1,4 @@
 pub mod event;
 pub mod eth;
+pub mod hotplug;
+pub mod pci;
@@ -0,0 +1,265 @@
+//! Finding PCI network devices and moving them between kernel drivers through sysfs, what
+//! `dpdk-devbind.py` does. Binding needs root and has to happen before EAL init.
+
+use std::{
+    backtrace::Backtrace,
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use crate::{config::PCIAddress, sysfs::Host};
+
+/// Kernel drivers a PCI device can be bound to for a DPDK poll mode driver to use it. The
+/// Mellanox drivers are bifurcated, DPDK shares the device with the kernel driver.
+pub const DPDK_COMPATIBLE_DRIVERS: &[&str] = &[
+    "vfio-pci",
+    "uio_pci_generic",
+    "igb_uio",
+    "mlx4_core",
+    "mlx5_core",
+];
+
+/// PCI class of network controllers, the first byte of the class code.
+pub const NETWORK_CLASS: u32 = 0x02;
+
+#[derive(Debug, thiserror::Error)]
+pub enum PciError {
+    #[error("Unable to access {path}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+        backtrace: Backtrace,
+    },
+    #[error("No PCI device {address}")]
+    NotFound {
+        address: PCIAddress,
+        backtrace: Backtrace,
+    },
+    #[error("Kernel driver {driver} is not loaded")]
+    DriverNotLoaded { driver: String, backtrace: Backtrace },
+    #[error("PCI device {address} is in use by interface {interface}, which is up")]
+    DeviceActive {
+        address: PCIAddress,
+        interface: String,
+        backtrace: Backtrace,
+    },
+}
+
+fn io_error(path: &Path) -> impl FnOnce(std::io::Error) -> PciError + '_ {
+    move |source| PciError::Io {
+        path: path.to_path_buf(),
+        source,
+        backtrace: Backtrace::capture(),
+    }
+}
+
+/// A PCI device as the kernel sees it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PciNic {
+    pub address: PCIAddress,
+    pub vendor_id: u16,
+    pub device_id: u16,
+    /// Class, subclass and programming interface
+    pub class: u32,
+    /// None if the device is not bound to any driver
+    pub driver: Option<String>,
+    /// Network interfaces of the kernel driver, none for devices bound to a DPDK driver
+    pub interfaces: Vec<String>,
+    pub numa_node: Option<u32>,
+}
+
+impl PciNic {
+    /// Whether DPDK can use the device with its current driver.
+    pub fn is_dpdk_compatible(&self) -> bool {
+        self.driver
+            .as_deref()
+            .map_or(false, |driver| DPDK_COMPATIBLE_DRIVERS.contains(&driver))
+    }
+}
+
+fn read_hex(path: &Path) -> Result<u32, PciError> {
+    let contents = fs::read_to_string(path).map_err(io_error(path))?;
+    let trimmed = contents.trim();
+    u32::from_str_radix(trimmed.trim_start_matches("0x"), 16).map_err(|_| PciError::Io {
+        path: path.to_path_buf(),
+        source: std::io::Error::new(ErrorKind::InvalidData, format!("not hex: {trimmed}")),
+        backtrace: Backtrace::capture(),
+    })
+}
+
+/// Reads the sysfs entry of one device.
+pub fn pci_device(host: &Host, address: PCIAddress) -> Result<PciNic, PciError> {
+    let path = host.pci_device_path(address);
+    if !path.exists() {
+        return Err(PciError::NotFound {
+            address,
+            backtrace: Backtrace::capture(),
+        });
+    }
+
+    let interfaces = match fs::read_dir(path.join("net")) {
+        Ok(entries) => {
+            let mut interfaces = entries
+                .flatten()
+                .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                .collect::<Vec<_>>();
+            interfaces.sort();
+            interfaces
+        }
+        Err(_) => vec![],
+    };
+    let numa_node = fs::read_to_string(path.join("numa_node"))
+        .ok()
+        .and_then(|node| node.trim().parse::<i32>().ok())
+        .filter(|node| *node >= 0)
+        .map(|node| node as u32);
+
+    Ok(PciNic {
+        address,
+        vendor_id: read_hex(&path.join("vendor"))? as u16,
+        device_id: read_hex(&path.join("device"))? as u16,
+        class: read_hex(&path.join("class"))?,
+        driver: host.pci_driver(address),
+        interfaces,
+        numa_node,
+    })
+}
+
+/// Every PCI device of the network class, sorted by address. Devices that can not be read are
+/// skipped with a warning.
+pub fn scan_network_devices(host: &Host) -> Result<Vec<PciNic>, PciError> {
+    let devices_path = host.sysfs().join("bus/pci/devices");
+    let mut nics = vec![];
+    for entry in fs::read_dir(&devices_path).map_err(io_error(&devices_path))? {
+        let entry = entry.map_err(io_error(&devices_path))?;
+        let address: PCIAddress = match entry.file_name().to_string_lossy().parse() {
+            Ok(address) => address,
+            Err(_) => continue,
+        };
+        let nic = match pci_device(host, address) {
+            Ok(nic) => nic,
+            Err(err) => {
+                log::warn!("Skipping PCI device {address}: {err}");
+                continue;
+            }
+        };
+        if nic.class >> 16 == NETWORK_CLASS {
+            nics.push(nic);
+        }
+    }
+    nics.sort_by_key(|nic| nic.address);
+    Ok(nics)
+}
+
+fn write_sysfs(path: &Path, value: &str) -> Result<(), PciError> {
+    fs::write(path, value).map_err(io_error(path))
+}
+
+/// Detaches the device from its driver, does nothing if it is not bound.
+pub fn unbind(host: &Host, address: PCIAddress) -> Result<(), PciError> {
+    let nic = pci_device(host, address)?;
+    if nic.driver.is_none() {
+        return Ok(());
+    }
+    write_sysfs(
+        &host.pci_device_path(address).join("driver/unbind"),
+        &address.to_string(),
+    )
+}
+
+/// Makes the kernel only ever bind `driver` to the device, or any matching driver again if None.
+pub fn set_driver_override(
+    host: &Host,
+    address: PCIAddress,
+    driver: Option<&str>,
+) -> Result<(), PciError> {
+    // The kernel clears the override on a lone newline
+    write_sysfs(
+        &host.pci_device_path(address).join("driver_override"),
+        driver.unwrap_or("\n"),
+    )
+}
+
+/// Moves the device to `driver`, for example `"vfio-pci"` to use it from DPDK or `"ixgbe"` to
+/// hand it back to the kernel. Devices with an interface that is up are refused, like
+/// `dpdk-devbind.py` does, so the management interface of a VM is not taken away by accident.
+pub fn bind(host: &Host, address: PCIAddress, driver: &str) -> Result<(), PciError> {
+    let nic = pci_device(host, address)?;
+    if nic.driver.as_deref() == Some(driver) {
+        return Ok(());
+    }
+
+    let driver_path = host.sysfs().join("bus/pci/drivers").join(driver);
+    if !driver_path.exists() {
+        return Err(PciError::DriverNotLoaded {
+            driver: driver.to_string(),
+            backtrace: Backtrace::capture(),
+        });
+    }
+
+    let net_path = host.pci_device_path(address).join("net");
+    for interface in &nic.interfaces {
+        let operstate = fs::read_to_string(net_path.join(interface).join("operstate"))
+            .unwrap_or_default();
+        if operstate.trim() == "up" {
+            return Err(PciError::DeviceActive {
+                address,
+                interface: interface.clone(),
+                backtrace: Backtrace::capture(),
+            });
+        }
+    }
+
+    unbind(host, address)?;
+    set_driver_override(host, address, Some(driver))?;
+    write_sysfs(&driver_path.join("bind"), &address.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::sysfs::TestDir;
+
+    #[test]
+    fn scan_fixture() {
+        let dir = TestDir::new("pci");
+        let host = dir.host();
+        for (address, class, interface) in [
+            ("0000:00:1f.0", "0x060100", None),
+            ("0000:03:00.0", "0x020000", Some("ens3")),
+            ("0000:02:00.0", "0x020000", None),
+        ] {
+            let device = Path::new("sys/bus/pci/devices").join(address);
+            dir.write(device.join("vendor"), "0x8086\n");
+            dir.write(device.join("device"), "0x10fb\n");
+            dir.write(device.join("class"), class);
+            dir.write(device.join("numa_node"), "-1\n");
+            if let Some(interface) = interface {
+                dir.write(device.join("net").join(interface).join("operstate"), "down\n");
+            }
+        }
+        // Unreadable devices are skipped
+        dir.write("sys/bus/pci/devices/0000:04:00.0/vendor", "0x8086\n");
+
+        let nics = scan_network_devices(&host).unwrap();
+        assert_eq!(nics.len(), 2);
+        assert_eq!(nics[0].address.to_string(), "0000:02:00.0");
+        assert_eq!(nics[1].interfaces, ["ens3"]);
+        assert_eq!(nics[1].device_id, 0x10fb);
+        assert_eq!(nics[1].numa_node, None);
+        assert!(!nics[1].is_dpdk_compatible());
+
+        let address = nics[1].address;
+        assert!(matches!(
+            bind(&host, address, "vfio-pci"),
+            Err(PciError::DriverNotLoaded { .. })
+        ));
+        fs::create_dir_all(host.sysfs().join("bus/pci/drivers/vfio-pci")).unwrap();
+        bind(&host, address, "vfio-pci").unwrap();
+        let override_path = host.pci_device_path(address).join("driver_override");
+        assert_eq!(fs::read_to_string(override_path).unwrap(), "vfio-pci");
+        let bind_path = host.sysfs().join("bus/pci/drivers/vfio-pci/bind");
+        assert_eq!(fs::read_to_string(bind_path).unwrap(), "0000:03:00.0");
+    }
+}
@@ -0,0 +1,256 @@
+use std::{ffi::CStr, fmt::Display};
+
+use dpdk_sys::{rte_bus, rte_bus_scan_mode, rte_dev_is_probed, rte_dev_to_pci, rte_device};
+use libc::c_void;
+use tailq_iterator::TailQIterator;
+
+use crate::{config::PCIAddress, device::hotplug::Devargs, error::DpdkError};
+
+#[derive(TailQIterator)]
+pub struct RteBus {
+    inner: *mut rte_bus,
+}
+
+/// Whether `-a`/`-b` options were given for a bus, which decides what its scan picks up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BusScanMode {
+    /// No devices were allowed or blocked, every device is scanned
+    Undefined,
+    /// Only the allowed devices are scanned
+    Allowlist,
+    /// Every device but the blocked ones is scanned
+    Blocklist,
+}
+
+unsafe extern "C" fn match_any_device(_dev: *const rte_device, _data: *const c_void) -> i32 {
+    0
+}
+
+impl RteBus {
+    pub fn name(&self) -> String {
+        unsafe { CStr::from_ptr((*self.inner).name) }
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    pub fn scan_mode(&self) -> BusScanMode {
+        match unsafe { (*self.inner).conf.scan_mode } {
+            rte_bus_scan_mode::RTE_BUS_SCAN_UNDEFINED => BusScanMode::Undefined,
+            rte_bus_scan_mode::RTE_BUS_SCAN_ALLOWLIST => BusScanMode::Allowlist,
+            rte_bus_scan_mode::RTE_BUS_SCAN_BLOCKLIST => BusScanMode::Blocklist,
+        }
+    }
+
+    /// Devices found by the last scan of this bus, whether or not a driver took them.
+    pub fn devices(&self) -> impl Iterator<Item = RteDevice> {
+        let mut devices = vec![];
+        if let Some(find_device) = unsafe { (*self.inner).find_device } {
+            // find_device continues after `start`, so matching everything walks the whole list
+            let mut start: *const rte_device = std::ptr::null();
+            loop {
+                let device =
+                    unsafe { find_device(start, Some(match_any_device), std::ptr::null()) };
+                if device.is_null() {
+                    break;
+                }
+                devices.push(RteDevice { inner: device });
+                start = device;
+            }
+        }
+        devices.into_iter()
+    }
+}
+
+#[derive(TailQIterator)]
+pub struct RteDevice {
+    inner: *mut rte_device,
+}
+
+impl RteDevice {
+    pub fn name(&self) -> String {
+        unsafe { CStr::from_ptr((*self.inner).name) }
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    /// None until a driver has probed the device.
+    pub fn driver_name(&self) -> Option<String> {
+        let driver = unsafe { (*self.inner).driver };
+        if driver.is_null() {
+            None
+        } else {
+            Some(
+                unsafe { CStr::from_ptr((*driver).name) }
+                    .to_string_lossy()
+                    .into_owned(),
+            )
+        }
+    }
+
+    /// None for devices without NUMA affinity, like most virtual devices.
+    pub fn numa_node(&self) -> Option<u32> {
+        let node = unsafe { (*self.inner).numa_node };
+        if node < 0 {
+            None
+        } else {
+            Some(node as u32)
+        }
+    }
+
+    /// The devargs the device was last probed with, None if it was found by scanning alone.
+    pub fn devargs(&self) -> Option<Devargs> {
+        let devargs = unsafe { (*self.inner).devargs };
+        if devargs.is_null() {
+            None
+        } else {
+            Some(Devargs::from_raw(unsafe { &*devargs }))
+        }
+    }
+
+    pub fn is_probed(&self) -> bool {
+        unsafe { rte_dev_is_probed(self.inner) != 0 }
+    }
+
+    pub fn bus(&self) -> Option<RteBus> {
+        rte_bus_find_by_device(self)
+    }
+
+    /// Address and ids of devices on the PCI bus, None for every other bus.
+    pub fn pci(&self) -> Option<PciDeviceInfo> {
+        let bus = unsafe { (*self.inner).bus };
+        if bus.is_null() || unsafe { CStr::from_ptr((*bus).name) }.to_bytes() != b"pci" {
+            return None;
+        }
+
+        let pci = unsafe { &*rte_dev_to_pci(self.inner) };
+        Some(PciDeviceInfo {
+            address: pci.addr.into(),
+            vendor_id: pci.id.vendor_id,
+            device_id: pci.id.device_id,
+            subsystem_vendor_id: pci.id.subsystem_vendor_id,
+            subsystem_device_id: pci.id.subsystem_device_id,
+            class_id: pci.id.class_id,
+        })
+    }
+
+    pub fn info(&self) -> DeviceInfo {
+        DeviceInfo {
+            name: self.name(),
+            bus: self.bus().map(|bus| bus.name()),
+            driver: self.driver_name(),
+            numa_node: self.numa_node(),
+            devargs: self.devargs(),
+            pci: self.pci(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PciDeviceInfo {
+    pub address: PCIAddress,
+    pub vendor_id: u16,
+    pub device_id: u16,
+    pub subsystem_vendor_id: u16,
+    pub subsystem_device_id: u16,
+    pub class_id: u32,
+}
+
+/// Snapshot of a device, see [`RteDevice::info`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceInfo {
+    pub name: String,
+    pub bus: Option<String>,
+    pub driver: Option<String>,
+    pub numa_node: Option<u32>,
+    pub devargs: Option<Devargs>,
+    pub pci: Option<PciDeviceInfo>,
+}
+
+impl Display for DeviceInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)?;
+        if let Some(bus) = &self.bus {
+            write!(f, " on {bus}")?;
+        }
+        if let Some(pci) = &self.pci {
+            write!(f, " [{:04x}:{:04x}]", pci.vendor_id, pci.device_id)?;
+        }
+        match &self.driver {
+            Some(driver) => write!(f, ", driver {driver}")?,
+            None => write!(f, ", no driver")?,
+        }
+        if let Some(node) = self.numa_node {
+            write!(f, ", socket {node}")?;
+        }
+        if let Some(devargs) = self.devargs.as_ref().filter(|devargs| !devargs.args.is_empty()) {
+            write!(f, ", args {}", devargs.args)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn rte_bus_probe() {
+    unsafe {
+        dpdk_sys::rte_bus_probe();
+    }
+}
+
+// rte_bus_dump
+
+extern "C" {
+    static mut rte_bus_list: dpdk_sys::rte_bus_list;
+}
+
+pub fn rte_bus_iter() -> impl Iterator<Item = RteBus> {
+    let ptr = unsafe { rte_bus_list.tqh_first };
+    RteBusTailQIterator {
+        current: if ptr.is_null() {
+            None
+        } else {
+            Some(RteBus { inner: ptr })
+        },
+    }
+}
+
+/// Every device on every bus.
+pub fn iter_devices() -> impl Iterator<Item = RteDevice> {
+    rte_bus_iter().flat_map(|bus| bus.devices())
+}
+
+pub fn rte_bus_find_by_device(dev: &RteDevice) -> Option<RteBus> {
+    let bus = unsafe { dpdk_sys::rte_bus_find_by_device(dev.inner) };
+    if bus.is_null() {
+        None
+    } else {
+        Some(RteBus { inner: bus })
+    }
+}
+
+pub fn rte_bus_find_by_name(name: &CStr) -> Option<RteBus> {
+    let bus = unsafe { dpdk_sys::rte_bus_find_by_name(name.as_ptr()) };
+    if bus.is_null() {
+        None
+    } else {
+        Some(RteBus { inner: bus })
+    }
+}
+
+pub fn rte_bus_get_iommu_class() -> dpdk_sys::rte_iova_mode {
+    unsafe { dpdk_sys::rte_bus_get_iommu_class() }
+}
+
+pub fn rte_bus_register(bus: &mut RteBus) {
+    unsafe {
+        dpdk_sys::rte_bus_register(bus.inner);
+    }
+}
+
+pub fn rte_bus_scan() -> Result<(), DpdkError> {
+    DpdkError::check("rte_bus_scan", unsafe { dpdk_sys::rte_bus_scan() })
+}
+
+pub fn rte_bus_unregister(bus: &mut RteBus) {
+    unsafe {
+        dpdk_sys::rte_bus_unregister(bus.inner);
+    }
+}
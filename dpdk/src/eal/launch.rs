@@ -0,0 +1,209 @@
+use std::{
+    cell::RefCell,
+    marker::PhantomData,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use dpdk_sys::{rte_eal_remote_launch, rte_eal_wait_lcore, rte_lcore_foreach_worker};
+use libc::{c_int, c_void};
+use parking_lot::Mutex;
+
+use super::LCoreId;
+use crate::error::DpdkError;
+
+/// Where a launched closure leaves its return value, or the payload it panicked with.
+type LaunchResult<T> = Arc<Mutex<Option<std::thread::Result<T>>>>;
+
+struct LaunchPayload<F, T> {
+    f: F,
+    result: LaunchResult<T>,
+    /// Set once nothing of the closure is left on the lcore, for [`scope`]
+    done: Option<Arc<AtomicBool>>,
+}
+
+/// Entry point handed to `rte_eal_remote_launch`, runs the boxed closure on the target lcore.
+unsafe extern "C" fn launch_trampoline<F, T>(arg: *mut c_void) -> c_int
+where
+    F: FnOnce() -> T,
+{
+    let payload = unsafe { Box::from_raw(arg as *mut LaunchPayload<F, T>) };
+    let LaunchPayload { f, result, done } = *payload;
+
+    // Unwinding across the FFI boundary is UB, so panics are handed back through the join handle
+    let ret = panic::catch_unwind(AssertUnwindSafe(f));
+    let code = if ret.is_ok() { 0 } else { -1 };
+    *result.lock() = Some(ret);
+    // Drops the result here if the join handle is gone, it may borrow from the scope
+    drop(result);
+    if let Some(done) = done {
+        done.store(true, Ordering::Release);
+    }
+    code
+}
+
+/// Handle to a closure running on a worker lcore.
+///
+/// Dropping the handle detaches the closure, it keeps running and the EAL will still wait for
+/// it during cleanup.
+pub struct LcoreJoinHandle<T> {
+    lcore: LCoreId,
+    result: LaunchResult<T>,
+}
+
+impl<T> LcoreJoinHandle<T> {
+    pub fn lcore(&self) -> LCoreId {
+        self.lcore
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.result.lock().is_some()
+    }
+
+    /// Waits for the lcore to finish and returns the closure's result, or the panic payload
+    /// if it panicked.
+    pub fn join(self) -> std::thread::Result<T> {
+        unsafe { rte_eal_wait_lcore(self.lcore as u32) };
+        self.result
+            .lock()
+            .take()
+            .expect("lcore finished without storing a result")
+    }
+}
+
+/// Runs `f` on the worker lcore `lcore`.
+///
+/// Fails with `EBUSY` if the lcore is not waiting for work, either because it is still running
+/// something else or because it is the main lcore.
+pub fn launch_on<F, T>(lcore: LCoreId, f: F) -> Result<LcoreJoinHandle<T>, DpdkError>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    // 'static closures can not outlive anything they borrow
+    unsafe { launch_unchecked(lcore, f, None) }
+}
+
+/// # Safety
+///
+/// Whatever `f` and `T` borrow has to outlive the closure running on the lcore, until `done`
+/// is set.
+unsafe fn launch_unchecked<F, T>(
+    lcore: LCoreId,
+    f: F,
+    done: Option<Arc<AtomicBool>>,
+) -> Result<LcoreJoinHandle<T>, DpdkError>
+where
+    F: FnOnce() -> T + Send,
+    T: Send,
+{
+    let result: LaunchResult<T> = Arc::new(Mutex::new(None));
+    let payload = Box::into_raw(Box::new(LaunchPayload {
+        f,
+        result: result.clone(),
+        done,
+    }));
+
+    let ret = unsafe {
+        rte_eal_remote_launch(
+            Some(launch_trampoline::<F, T>),
+            payload as *mut c_void,
+            lcore as u32,
+        )
+    };
+
+    if ret == 0 {
+        Ok(LcoreJoinHandle { lcore, result })
+    } else {
+        // The lcore never took ownership of the closure
+        drop(unsafe { Box::from_raw(payload) });
+        Err(DpdkError::from_return("rte_eal_remote_launch", ret).with_lcore(lcore))
+    }
+}
+
+/// Launches closures that may borrow from outside of [`scope`], like [`std::thread::Scope`].
+pub struct LaunchScope<'scope, 'env: 'scope> {
+    /// Set when a closure launched in the scope is completely gone from its lcore
+    launched: RefCell<Vec<Arc<AtomicBool>>>,
+    _scope: PhantomData<&'scope mut &'scope ()>,
+    _env: PhantomData<&'env mut &'env ()>,
+}
+
+impl<'scope, 'env> LaunchScope<'scope, 'env> {
+    /// Runs `f` on the worker lcore `lcore`, see [`launch_on`].
+    pub fn launch_on<F, T>(
+        &'scope self,
+        lcore: LCoreId,
+        f: F,
+    ) -> Result<LcoreJoinHandle<T>, DpdkError>
+    where
+        F: FnOnce() -> T + Send + 'scope,
+        T: Send + 'scope,
+    {
+        let done = Arc::new(AtomicBool::new(false));
+        // The scope waits for `done` before anything the closure borrows goes away
+        let handle = unsafe { launch_unchecked(lcore, f, Some(done.clone()))? };
+        self.launched.borrow_mut().push(done);
+        Ok(handle)
+    }
+}
+
+/// Calls `f` with a [`LaunchScope`] and waits for every closure launched with it before
+/// returning, so the closures can borrow from the caller, for example the queue handles of an
+/// [`EthPort`](crate::device::eth::port::EthPort).
+pub fn scope<'env, F, T>(f: F) -> T
+where
+    F: for<'scope> FnOnce(&'scope LaunchScope<'scope, 'env>) -> T,
+{
+    let scope = LaunchScope {
+        launched: RefCell::new(vec![]),
+        _scope: PhantomData,
+        _env: PhantomData,
+    };
+    let ret = panic::catch_unwind(AssertUnwindSafe(|| f(&scope)));
+
+    for done in scope.launched.borrow().iter() {
+        while !done.load(Ordering::Acquire) {
+            std::hint::spin_loop();
+        }
+    }
+
+    match ret {
+        Ok(ret) => ret,
+        Err(payload) => panic::resume_unwind(payload),
+    }
+}
+
+/// Runs a copy of `f` on every worker lcore, passing it the id of the lcore it runs on.
+///
+/// Stops at the first lcore that could not be launched, the closures that were already started
+/// keep running.
+pub fn launch_on_all_workers<F, T>(f: F) -> Result<Vec<LcoreJoinHandle<T>>, DpdkError>
+where
+    F: FnOnce(LCoreId) -> T + Clone + Send + 'static,
+    T: Send + 'static,
+{
+    let mut handles = vec![];
+    let mut error = None;
+
+    rte_lcore_foreach_worker(|lcore| {
+        if error.is_some() {
+            return;
+        }
+
+        let lcore = lcore as LCoreId;
+        let f = f.clone();
+        match launch_on(lcore, move || f(lcore)) {
+            Ok(handle) => handles.push(handle),
+            Err(err) => error = Some(err),
+        }
+    });
+
+    match error {
+        Some(err) => Err(err),
+        None => Ok(handles),
+    }
+}
@@ -1,10 +1,18 @@
-use std::process::exit;
+use std::{
+    ffi::CString,
+    marker::PhantomData,
+    process::exit,
+    sync::atomic::{AtomicBool, Ordering},
+};
 
 use dpdk_sys::rte_exit;
+use itertools::Itertools;
+
+use crate::{config::MultiprocessingProcType, device::eth::dev::EthdevPortId, error::DpdkError};
 pub use dpdk_sys::{per_lcore__lcore_id, per_lcore__rte_errno, per_lcore__thread_id};
 
 #[repr(u32)]
-#[derive(num_derive::FromPrimitive, Debug)]
+#[derive(num_derive::FromPrimitive, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum RteErrnoValue {
     EPERM = dpdk_sys::EPERM,
     ENOENT = dpdk_sys::ENOENT,
@@ -144,9 +152,14 @@ pub enum RteErrnoValue {
 }
 
 impl RteErrnoValue {
-    pub fn most_recent() -> Self {
-        let errno = unsafe { per_lcore__rte_errno } as u32;
-        num::FromPrimitive::from_u32(errno).unwrap()
+    /// None if no error was recorded or the value is not one this crate knows about, see
+    /// [`crate::error::DpdkError::from_rte_errno`] to keep the raw value.
+    pub fn most_recent() -> Option<Self> {
+        Self::from_errno(unsafe { per_lcore__rte_errno })
+    }
+
+    pub fn from_errno(errno: i32) -> Option<Self> {
+        num::FromPrimitive::from_i32(errno)
     }
 
     pub fn clear() {
@@ -158,82 +171,117 @@ impl RteErrnoValue {
 
 pub type LCoreId = libc::c_int;
 
-pub mod bus {
-    use std::ffi::CStr;
+pub mod bus;
+pub mod launch;
+pub mod topology;
 
-    use dpdk_sys::rte_bus;
-    use tailq_iterator::TailQIterator;
+pub use launch::{launch_on, launch_on_all_workers, scope, LaunchScope, LcoreJoinHandle};
 
-    #[derive(TailQIterator)]
-    pub struct RteBus {
-        inner: *mut rte_bus,
-    }
+#[inline]
+pub fn current_lcore_id() -> i32 {
+    unsafe { per_lcore__lcore_id }
+}
 
-    pub fn rte_bus_probe() {
-        unsafe {
-            dpdk_sys::rte_bus_probe();
+/// Set once `rte_eal_init` has been called, the EAL may not be initialized twice in a process.
+static EAL_INITIALIZED: AtomicBool = AtomicBool::new(false);
+
+/// Owned handle to an initialized EAL.
+///
+/// Anything that is only valid between `rte_eal_init` and `rte_eal_cleanup` hangs off of this
+/// handle. Dropping it waits for all worker lcores to return and then releases the hugepage
+/// state held by the EAL, including when the owning thread is unwinding from a panic.
+pub struct Eal {
+    // rte_eal_cleanup must run on the main lcore
+    _not_send: PhantomData<*mut ()>,
+}
+
+impl Eal {
+    /// Calls `rte_eal_init` with the given argv, `args[0]` is treated as the program name.
+    ///
+    /// Fails with `EALREADY` if the EAL has already been initialized by this process.
+    pub(crate) fn init(args: &[CString]) -> Result<Eal, DpdkError> {
+        if EAL_INITIALIZED.swap(true, Ordering::SeqCst) {
+            return Err(DpdkError::from_errno(
+                "rte_eal_init",
+                RteErrnoValue::EALREADY as i32,
+            ));
         }
-    }
 
-    // rte_bus_dump
+        let mut c_args = args
+            .iter()
+            .map(|arg| arg.as_ptr() as *mut libc::c_char)
+            .collect_vec();
+        let ret = unsafe { dpdk_sys::rte_eal_init(c_args.len() as i32, c_args.as_mut_ptr()) };
+
+        if ret >= 0 {
+            crate::time::init();
+            Ok(Eal {
+                _not_send: PhantomData,
+            })
+        } else {
+            Err(DpdkError::from_rte_errno("rte_eal_init"))
+        }
+    }
 
-    extern "C" {
-        static mut rte_bus_list: dpdk_sys::rte_bus_list;
+    /// Returns true once `rte_eal_init` has been attempted in this process.
+    pub fn is_initialized() -> bool {
+        EAL_INITIALIZED.load(Ordering::SeqCst)
     }
 
-    pub fn rte_bus_iter() -> impl Iterator<Item = RteBus> {
-        let ptr = unsafe { rte_bus_list.tqh_first };
-        RteBusTailQIterator {
-            current: Option::from(RteBus { inner: ptr }),
+    /// Whether this process ended up as the primary or a secondary process.
+    pub fn process_type(&self) -> MultiprocessingProcType {
+        match unsafe { dpdk_sys::rte_eal_process_type() } {
+            dpdk_sys::rte_proc_type_t::RTE_PROC_PRIMARY => MultiprocessingProcType::Primary,
+            dpdk_sys::rte_proc_type_t::RTE_PROC_SECONDARY => MultiprocessingProcType::Secondary,
+            // Only reported before init has resolved the process type
+            _ => MultiprocessingProcType::Auto,
         }
     }
 
-    #[derive(TailQIterator)]
-    pub struct RteDevice {
-        inner: *mut dpdk_sys::rte_device,
+    pub fn main_lcore(&self) -> LCoreId {
+        unsafe { dpdk_sys::rte_get_main_lcore() as LCoreId }
     }
 
-    pub fn rte_bus_find_by_device(dev: &RteDevice) -> RteBus {
-        let bus = unsafe { dpdk_sys::rte_bus_find_by_device(dev.inner) };
-        RteBus { inner: bus }
+    pub fn lcore_count(&self) -> u32 {
+        unsafe { dpdk_sys::rte_lcore_count() }
     }
 
-    pub fn rte_bus_find_by_name(name: &CStr) -> RteBus {
-        let bus = unsafe { dpdk_sys::rte_bus_find_by_name(name.as_ptr()) };
-        RteBus { inner: bus }
+    /// All enabled lcores other than the main lcore.
+    pub fn worker_lcores(&self) -> Vec<LCoreId> {
+        let mut workers = vec![];
+        dpdk_sys::rte_lcore_foreach_worker(|lcore| workers.push(lcore as LCoreId));
+        workers
     }
 
-    pub fn rte_bus_get_iommu_class() -> dpdk_sys::rte_iova_mode {
-        unsafe { dpdk_sys::rte_bus_get_iommu_class() }
+    pub fn num_ports(&self) -> u16 {
+        crate::device::eth::dev::num_ports_available()
     }
 
-    pub fn rte_bus_register(bus: &mut RteBus) {
-        unsafe {
-            dpdk_sys::rte_bus_register(bus.inner);
-        }
+    pub fn ports(&self) -> std::ops::Range<EthdevPortId> {
+        crate::device::eth::dev::iter_ports()
     }
 
-    pub fn rte_bus_scan() -> Result<(), i32> {
-        unsafe {
-            let ret = dpdk_sys::rte_bus_scan();
-            if ret == 0 {
-                Ok(())
-            } else {
-                Err(ret)
-            }
-        }
+    pub fn topology(&self) -> topology::Topology {
+        topology::Topology::query()
     }
 
-    pub fn rte_bus_unregister(bus: &mut RteBus) {
-        unsafe {
-            dpdk_sys::rte_bus_unregister(bus.inner);
-        }
+    pub fn buses(&self) -> impl Iterator<Item = bus::RteBus> {
+        bus::rte_bus_iter()
+    }
+
+    /// Blocks until every worker lcore has returned from the function it was launched with.
+    pub fn wait_all_lcores(&self) {
+        unsafe { dpdk_sys::rte_eal_mp_wait_lcore() }
     }
 }
 
-#[inline]
-pub fn current_lcore_id() -> i32 {
-    unsafe { per_lcore__lcore_id }
+impl Drop for Eal {
+    fn drop(&mut self) {
+        self.wait_all_lcores();
+        unsafe {
+            dpdk_sys::rte_eal_cleanup();
+        }
+    }
 }
 
 pub fn dpdk_exit(exit_code: i32, message: &str) -> ! {
@@ -259,32 +307,19 @@ pub fn dpdk_exit(exit_code: i32, message: &str) -> ! {
 // rte_dev_dma_map,
 // rte_dev_dma_unmap,
 // rte_dev_event_callback_process,
-// rte_dev_event_callback_register,
-// rte_dev_event_callback_unregister,
-// rte_dev_event_monitor_start,
-// rte_dev_event_monitor_stop,
-// rte_dev_hotplug_handle_disable,
-// rte_dev_hotplug_handle_enable,
-// rte_dev_is_probed,
+// rte_dev_remove,
 // rte_dev_iterator_init,
 // rte_dev_iterator_next,
-// rte_dev_probe,
-// rte_dev_remove,
 // rte_devargs_add,
 // rte_devargs_dump,
 // rte_devargs_insert,
 // rte_devargs_next,
-// rte_devargs_parse,
 // rte_devargs_parsef,
 // rte_devargs_remove,
-// rte_devargs_reset,
 // rte_devargs_type_count,
 // rte_dump_physmem_layout,
 // rte_dump_stack,
 // rte_dump_tailq,
-// rte_eal_alarm_cancel,
-// rte_eal_alarm_set,
-// rte_eal_cleanup,
 // rte_eal_get_baseaddr,
 // rte_eal_get_lcore_state,
 // rte_eal_get_physmem_size,
@@ -292,19 +327,12 @@ pub fn dpdk_exit(exit_code: i32, message: &str) -> ! {
 // rte_eal_has_hugepages,
 // rte_eal_has_pci,
 // rte_eal_hotplug_add,
-// rte_eal_hotplug_remove,
-// rte_eal_init,
 // rte_eal_iova_mode,
-// rte_eal_lcore_role,
 // rte_eal_mbuf_user_pool_ops,
 // rte_eal_mp_remote_launch,
-// rte_eal_mp_wait_lcore,
-// rte_eal_process_type,
-// rte_eal_remote_launch,
 // rte_eal_tailq_lookup,
 // rte_eal_tailq_register,
 // rte_eal_using_phys_addrs,
-// rte_eal_wait_lcore,
 // rte_epoll_ctl,
 // rte_epoll_wait,
 // rte_epoll_wait_interruptible,
@@ -341,9 +369,7 @@ pub fn dpdk_exit(exit_code: i32, message: &str) -> ! {
 // rte_fbarray_set_used,
 // rte_firmware_read,
 // rte_free,
-// rte_get_main_lcore,
 // rte_get_next_lcore,
-// rte_get_tsc_hz,
 // rte_hexdump,
 // rte_hypervisor_get,
 // rte_intr_ack,
@@ -390,29 +416,15 @@ pub fn dpdk_exit(exit_code: i32, message: &str) -> ! {
 // rte_intr_vec_list_index_set,
 // rte_lcore_callback_register,
 // rte_lcore_callback_unregister,
-// rte_lcore_count,
-// rte_lcore_cpuset,
 // rte_lcore_dump,
 // rte_lcore_has_role,
 // rte_lcore_index,
-// rte_lcore_is_enabled,
 // rte_lcore_iterate,
-// rte_lcore_to_cpu_id,
-// rte_lcore_to_socket_id,
-// rte_log,
 // rte_log_can_log,
-// rte_log_cur_msg_loglevel,
-// rte_log_cur_msg_logtype,
 // rte_log_dump,
-// rte_log_get_global_level,
-// rte_log_get_level,
 // rte_log_get_stream,
 // rte_log_list_types,
 // rte_log_register,
-// rte_log_register_type_and_pick_level,
-// rte_log_set_global_level,
-// rte_log_set_level,
-// rte_log_set_level_pattern,
 // rte_log_set_level_regexp,
 // rte_malloc,
 // rte_malloc_dump_heaps,
@@ -476,20 +488,10 @@ pub fn dpdk_exit(exit_code: i32, message: &str) -> ! {
 // rte_memseg_walk,
 // rte_memseg_walk_thread_unsafe,
 // rte_memzone_dump,
-// rte_memzone_free,
-// rte_memzone_lookup,
 // rte_memzone_reserve,
-// rte_memzone_reserve_aligned,
 // rte_memzone_reserve_bounded,
-// rte_memzone_walk,
-// rte_mp_action_register,
-// rte_mp_action_unregister,
 // rte_mp_disable,
-// rte_mp_reply,
 // rte_mp_request_async,
-// rte_mp_request_sync,
-// rte_mp_sendmsg,
-// rte_openlog_stream,
 // rte_rand,
 // rte_rand_max,
 // rte_realloc,
@@ -497,43 +499,18 @@ pub fn dpdk_exit(exit_code: i32, message: &str) -> ! {
 // rte_reciprocal_value,
 // rte_reciprocal_value_u64,
 // rte_rtm_supported,
-// rte_service_attr_get,
-// rte_service_attr_reset_all,
-// rte_service_component_register,
-// rte_service_component_runstate_set,
-// rte_service_component_unregister,
 // rte_service_dump,
 // rte_service_finalize,
-// rte_service_get_by_name,
-// rte_service_get_count,
-// rte_service_get_name,
-// rte_service_lcore_add,
 // rte_service_lcore_attr_get,
 // rte_service_lcore_attr_reset_all,
 // rte_service_lcore_count,
-// rte_service_lcore_count_services,
-// rte_service_lcore_del,
-// rte_service_lcore_list,
 // rte_service_lcore_may_be_active,
 // rte_service_lcore_reset_all,
-// rte_service_lcore_start,
-// rte_service_lcore_stop,
-// rte_service_map_lcore_get,
-// rte_service_map_lcore_set,
-// rte_service_may_be_active,
-// rte_service_probe_capability,
-// rte_service_run_iter_on_app_lcore,
-// rte_service_runstate_get,
-// rte_service_runstate_set,
 // rte_service_set_runstate_mapped_check,
-// rte_service_set_stats_enable,
 // rte_service_start_with_defaults,
 // rte_set_application_usage_hook,
-// rte_socket_count,
 // rte_socket_id,
-// rte_socket_id_by_idx,
 // rte_srand,
-// rte_strerror,
 // rte_strscpy,
 // rte_strsplit,
 // rte_sys_gettid,
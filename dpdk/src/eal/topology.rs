@@ -0,0 +1,197 @@
+use std::fmt::Display;
+
+use dpdk_sys::{
+    rte_eal_lcore_role, rte_lcore_cpuset, rte_lcore_is_enabled, rte_lcore_role_t,
+    rte_lcore_to_cpu_id, rte_lcore_to_socket_id, rte_socket_count, rte_socket_id_by_idx,
+    RTE_MAX_LCORE,
+};
+
+use crate::device::eth::dev::{iter_ports, socket_id_for_port, EthdevPortId};
+
+use super::LCoreId;
+
+pub type SocketId = u32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LcoreRole {
+    /// Regular EAL lcore, may be handed work with `launch_on`
+    Rte,
+    /// Service core, runs services registered with the service API
+    Service,
+    /// Thread registered with the EAL that was not started by it
+    NonEal,
+}
+
+impl LcoreRole {
+    fn from_raw(role: rte_lcore_role_t) -> Option<Self> {
+        match role {
+            rte_lcore_role_t::ROLE_RTE => Some(Self::Rte),
+            rte_lcore_role_t::ROLE_SERVICE => Some(Self::Service),
+            rte_lcore_role_t::ROLE_NON_EAL => Some(Self::NonEal),
+            rte_lcore_role_t::ROLE_OFF => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LcoreInfo {
+    pub id: LCoreId,
+    pub role: LcoreRole,
+    pub socket: SocketId,
+    /// The physical cpu the lcore was first pinned to, None if the EAL does not know it
+    pub cpu: Option<u32>,
+    /// All physical cpus the lcore may run on
+    pub cpuset: Vec<u32>,
+    pub enabled: bool,
+}
+
+impl LcoreInfo {
+    /// Returns None for lcore ids that are not used by the EAL.
+    pub fn query(lcore: LCoreId) -> Option<LcoreInfo> {
+        let raw_lcore = lcore as u32;
+        if raw_lcore >= RTE_MAX_LCORE {
+            return None;
+        }
+
+        let role = LcoreRole::from_raw(unsafe { rte_eal_lcore_role(raw_lcore) })?;
+
+        let cpu = unsafe { rte_lcore_to_cpu_id(lcore) };
+        let cpuset = unsafe { rte_lcore_cpuset(raw_lcore) };
+        let bits_per_word = u64::BITS;
+        let cpuset = cpuset
+            .__bits
+            .iter()
+            .enumerate()
+            .flat_map(|(word_index, word)| {
+                (0..bits_per_word)
+                    .filter(move |bit| word & (1 << bit) != 0)
+                    .map(move |bit| word_index as u32 * bits_per_word + bit)
+            })
+            .collect();
+
+        Some(LcoreInfo {
+            id: lcore,
+            role,
+            socket: unsafe { rte_lcore_to_socket_id(raw_lcore) },
+            cpu: if cpu < 0 { None } else { Some(cpu as u32) },
+            cpuset,
+            enabled: unsafe { rte_lcore_is_enabled(raw_lcore) } == 1,
+        })
+    }
+}
+
+impl Display for LcoreInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "lcore {} ({:?}) on socket {}", self.id, self.role, self.socket)?;
+        if let Some(cpu) = self.cpu {
+            write!(f, ", cpu {cpu}")?;
+        }
+        if !self.enabled {
+            write!(f, ", disabled")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SocketInfo {
+    pub id: SocketId,
+    pub lcores: Vec<LCoreId>,
+    pub ports: Vec<EthdevPortId>,
+}
+
+/// Snapshot of the lcores, NUMA sockets and ethernet ports the EAL knows about.
+#[derive(Debug, Clone)]
+pub struct Topology {
+    pub lcores: Vec<LcoreInfo>,
+    pub sockets: Vec<SocketInfo>,
+}
+
+impl Topology {
+    /// Only meaningful after the EAL has been initialized, see [`super::Eal::topology`].
+    pub(crate) fn query() -> Topology {
+        let lcores = (0..RTE_MAX_LCORE as LCoreId)
+            .filter_map(LcoreInfo::query)
+            .collect::<Vec<_>>();
+
+        let ports = iter_ports()
+            .map(|port| (port, socket_id_for_port(port)))
+            .collect::<Vec<_>>();
+
+        let sockets = (0..unsafe { rte_socket_count() })
+            .map(|idx| unsafe { rte_socket_id_by_idx(idx) })
+            .filter(|socket| *socket >= 0)
+            .map(|socket| {
+                let socket = socket as SocketId;
+                SocketInfo {
+                    id: socket,
+                    lcores: lcores
+                        .iter()
+                        .filter(|lcore| lcore.socket == socket)
+                        .map(|lcore| lcore.id)
+                        .collect(),
+                    ports: ports
+                        .iter()
+                        .filter(|(_, port_socket)| *port_socket == Some(socket))
+                        .map(|(port, _)| *port)
+                        .collect(),
+                }
+            })
+            .collect();
+
+        Topology { lcores, sockets }
+    }
+
+    pub fn lcore(&self, lcore: LCoreId) -> Option<&LcoreInfo> {
+        self.lcores.iter().find(|info| info.id == lcore)
+    }
+
+    pub fn socket(&self, socket: SocketId) -> Option<&SocketInfo> {
+        self.sockets.iter().find(|info| info.id == socket)
+    }
+
+    /// The socket a port's memory lives on. Ports without NUMA affinity return None.
+    pub fn socket_of_port(&self, port: EthdevPortId) -> Option<SocketId> {
+        self.sockets
+            .iter()
+            .find(|socket| socket.ports.contains(&port))
+            .map(|socket| socket.id)
+    }
+
+    /// Enabled `Rte` lcores on the given socket, these are the ones work can be launched on.
+    pub fn worker_lcores_on_socket(&self, socket: SocketId) -> impl Iterator<Item = &LcoreInfo> {
+        self.lcores.iter().filter(move |lcore| {
+            lcore.socket == socket && lcore.role == LcoreRole::Rte && lcore.enabled
+        })
+    }
+
+    /// Enabled `Rte` lcores that share a socket with the port, so rx and tx loops for it do
+    /// not cross the NUMA interconnect. Falls back to every enabled `Rte` lcore if the port has
+    /// no NUMA affinity.
+    pub fn worker_lcores_near_port(&self, port: EthdevPortId) -> Vec<LCoreId> {
+        match self.socket_of_port(port) {
+            Some(socket) => self
+                .worker_lcores_on_socket(socket)
+                .map(|lcore| lcore.id)
+                .collect(),
+            None => self
+                .lcores
+                .iter()
+                .filter(|lcore| lcore.role == LcoreRole::Rte && lcore.enabled)
+                .map(|lcore| lcore.id)
+                .collect(),
+        }
+    }
+}
+
+impl Display for Topology {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for socket in &self.sockets {
+            writeln!(f, "socket {}: ports {:?}", socket.id, socket.ports)?;
+            for lcore in self.lcores.iter().filter(|lcore| lcore.socket == socket.id) {
+                writeln!(f, "  {lcore}")?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,183 @@
+use std::{backtrace::Backtrace, ffi::CStr, fmt::Display};
+
+use dpdk_sys::{per_lcore__rte_errno, rte_strerror};
+
+use crate::{
+    device::{
+        eth::dev::EthdevPortId,
+        event::{EventDeviceId, EventPortId},
+    },
+    eal::{LCoreId, RteErrnoValue},
+};
+
+/// The objects a failed call was made for, only the ones that apply are set.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ErrorContext {
+    /// Name of a ring, mempool, memzone, device, ...
+    pub name: Option<String>,
+    pub eventdev: Option<EventDeviceId>,
+    pub event_port: Option<EventPortId>,
+    pub port: Option<EthdevPortId>,
+    pub queue: Option<u16>,
+    pub adapter: Option<u8>,
+    pub service: Option<u32>,
+    pub lcore: Option<LCoreId>,
+}
+
+impl ErrorContext {
+    pub fn is_empty(&self) -> bool {
+        *self == ErrorContext::default()
+    }
+}
+
+/// Renders as ` for eventdev 0, port 1` so it can be dropped into a sentence, empty if nothing
+/// is set.
+impl Display for ErrorContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut parts = vec![];
+        if let Some(name) = &self.name {
+            parts.push(name.clone());
+        }
+        if let Some(eventdev) = self.eventdev {
+            parts.push(format!("eventdev {eventdev}"));
+        }
+        if let Some(event_port) = self.event_port {
+            parts.push(format!("event port {event_port}"));
+        }
+        if let Some(port) = self.port {
+            parts.push(format!("port {port}"));
+        }
+        if let Some(queue) = self.queue {
+            parts.push(format!("queue {queue}"));
+        }
+        if let Some(adapter) = self.adapter {
+            parts.push(format!("adapter {adapter}"));
+        }
+        if let Some(service) = self.service {
+            parts.push(format!("service {service}"));
+        }
+        if let Some(lcore) = self.lcore {
+            parts.push(format!("lcore {lcore}"));
+        }
+
+        if parts.is_empty() {
+            Ok(())
+        } else {
+            write!(f, " for {}", parts.join(", "))
+        }
+    }
+}
+
+/// A failed call into DPDK. Carries the function that failed, what it was called for and the
+/// errno it reported, along with the text `rte_strerror` has for it.
+#[derive(Debug, thiserror::Error)]
+#[error("{operation} failed{context}: {message} (errno {errno})")]
+pub struct DpdkError {
+    operation: &'static str,
+    context: ErrorContext,
+    errno: i32,
+    message: String,
+    backtrace: Backtrace,
+}
+
+impl DpdkError {
+    /// `errno` is a positive errno value.
+    pub fn from_errno(operation: &'static str, errno: i32) -> Self {
+        let message = unsafe { CStr::from_ptr(rte_strerror(errno)) }
+            .to_string_lossy()
+            .into_owned();
+        Self {
+            operation,
+            context: ErrorContext::default(),
+            errno,
+            message,
+            backtrace: Backtrace::capture(),
+        }
+    }
+
+    /// For functions that return a negative errno on failure.
+    pub fn from_return(operation: &'static str, ret: i32) -> Self {
+        Self::from_errno(operation, -ret)
+    }
+
+    /// For functions that set `rte_errno` on failure.
+    pub fn from_rte_errno(operation: &'static str) -> Self {
+        Self::from_errno(operation, unsafe { per_lcore__rte_errno })
+    }
+
+    /// Turns the usual `0 or -errno` return code into a result.
+    pub fn check(operation: &'static str, ret: i32) -> Result<(), Self> {
+        if ret == 0 {
+            Ok(())
+        } else {
+            Err(Self::from_return(operation, ret))
+        }
+    }
+
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.context.name = Some(name.into());
+        self
+    }
+
+    pub fn with_eventdev(mut self, eventdev: EventDeviceId) -> Self {
+        self.context.eventdev = Some(eventdev);
+        self
+    }
+
+    pub fn with_event_port(mut self, event_port: EventPortId) -> Self {
+        self.context.event_port = Some(event_port);
+        self
+    }
+
+    pub fn with_port(mut self, port: EthdevPortId) -> Self {
+        self.context.port = Some(port);
+        self
+    }
+
+    pub fn with_queue(mut self, queue: u16) -> Self {
+        self.context.queue = Some(queue);
+        self
+    }
+
+    pub fn with_adapter(mut self, adapter: u8) -> Self {
+        self.context.adapter = Some(adapter);
+        self
+    }
+
+    pub fn with_service(mut self, service: u32) -> Self {
+        self.context.service = Some(service);
+        self
+    }
+
+    pub fn with_lcore(mut self, lcore: LCoreId) -> Self {
+        self.context.lcore = Some(lcore);
+        self
+    }
+
+    /// Name of the DPDK function that failed.
+    pub fn operation(&self) -> &'static str {
+        self.operation
+    }
+
+    pub fn context(&self) -> &ErrorContext {
+        &self.context
+    }
+
+    pub fn errno(&self) -> i32 {
+        self.errno
+    }
+
+    /// None for errno values this crate does not know about.
+    pub fn errno_value(&self) -> Option<RteErrnoValue> {
+        RteErrnoValue::from_errno(self.errno)
+    }
+
+    pub fn is(&self, errno: RteErrnoValue) -> bool {
+        self.errno == errno as i32
+    }
+
+    /// What `rte_strerror` says about the errno.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
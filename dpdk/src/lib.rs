@@ -7,15 +7,25 @@
 #[macro_use]
 extern crate derive_builder;
 
+mod callback;
 pub mod config;
+pub mod error;
 #[allow(dead_code)]
 pub mod ring;
 pub mod util;
 pub mod memory;
+pub mod multiprocess;
 pub mod device;
 pub mod hash;
 pub mod ip_frag;
 pub mod rss;
+pub mod service;
+pub mod sysfs;
+pub mod telemetry;
+pub mod time;
+pub mod timer;
+pub mod trace;
+pub mod logging;
 
 pub mod raw {
     pub use dpdk_sys::*;
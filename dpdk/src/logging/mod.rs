@@ -0,0 +1,315 @@
+use std::{cell::RefCell, ffi::CString, fmt::Display, str::FromStr};
+
+use dpdk_sys::{
+    cookie_io_functions_t, fopencookie, rte_log, rte_log_cur_msg_loglevel,
+    rte_log_cur_msg_logtype, rte_log_get_global_level, rte_log_get_level,
+    rte_log_register_type_and_pick_level, rte_log_set_global_level, rte_log_set_level,
+    rte_log_set_level_pattern, rte_openlog_stream, FILE, LCORE_ID_ANY, RTE_LOGTYPE_FIRST_EXT_ID,
+};
+use libc::c_void;
+use num_derive::FromPrimitive;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::ConfigError,
+    eal::current_lcore_id,
+    error::DpdkError,
+    util::str_to_c_string,
+};
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, FromPrimitive, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+#[repr(u32)]
+pub enum LogLevel {
+    Emergency = dpdk_sys::RTE_LOG_EMERG,
+    Alert = dpdk_sys::RTE_LOG_ALERT,
+    Critical = dpdk_sys::RTE_LOG_CRIT,
+    Error = dpdk_sys::RTE_LOG_ERR,
+    Warning = dpdk_sys::RTE_LOG_WARNING,
+    Notice = dpdk_sys::RTE_LOG_NOTICE,
+    Info = dpdk_sys::RTE_LOG_INFO,
+    Debug = dpdk_sys::RTE_LOG_DEBUG,
+}
+
+impl LogLevel {
+    /// `log` has fewer levels than syslog, everything above a warning is an error and notices
+    /// are info.
+    pub fn to_log_level(self) -> log::Level {
+        match self {
+            LogLevel::Emergency | LogLevel::Alert | LogLevel::Critical | LogLevel::Error => {
+                log::Level::Error
+            }
+            LogLevel::Warning => log::Level::Warn,
+            LogLevel::Notice | LogLevel::Info => log::Level::Info,
+            LogLevel::Debug => log::Level::Debug,
+        }
+    }
+
+    /// DPDK has no trace level, it maps to debug.
+    pub fn from_log_level(level: log::Level) -> Self {
+        match level {
+            log::Level::Error => LogLevel::Error,
+            log::Level::Warn => LogLevel::Warning,
+            log::Level::Info => LogLevel::Info,
+            log::Level::Debug | log::Level::Trace => LogLevel::Debug,
+        }
+    }
+}
+
+/// The names accepted by the `--log-level` EAL option.
+impl Display for LogLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            LogLevel::Emergency => "emergency",
+            LogLevel::Alert => "alert",
+            LogLevel::Critical => "critical",
+            LogLevel::Error => "error",
+            LogLevel::Warning => "warning",
+            LogLevel::Notice => "notice",
+            LogLevel::Info => "info",
+            LogLevel::Debug => "debug",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl FromStr for LogLevel {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "emergency" => Ok(LogLevel::Emergency),
+            "alert" => Ok(LogLevel::Alert),
+            "critical" => Ok(LogLevel::Critical),
+            "error" => Ok(LogLevel::Error),
+            "warning" => Ok(LogLevel::Warning),
+            "notice" => Ok(LogLevel::Notice),
+            "info" => Ok(LogLevel::Info),
+            "debug" => Ok(LogLevel::Debug),
+            _ => Err(ConfigError::invalid("log level", s, "expected a syslog level name")),
+        }
+    }
+}
+
+/// Names of the static logtypes, indexed by their id. The ids in between are unused.
+const STATIC_LOGTYPES: [&str; RTE_LOGTYPE_FIRST_EXT_ID as usize] = [
+    "eal", "malloc", "ring", "mempool", "timer", "pmd", "hash", "lpm", "kni", "acl", "power",
+    "meter", "sched", "port", "table", "pipeline", "mbuf", "cryptodev", "efd", "eventdev", "gso",
+    "", "", "", "user1", "user2", "user3", "user4", "user5", "user6", "user7", "user8",
+];
+
+/// DPDK has no way to get the name of a dynamic logtype back, so the ones registered from Rust
+/// are remembered here.
+static REGISTERED_LOGTYPES: Mutex<Vec<(u32, String)>> = Mutex::new(Vec::new());
+
+fn logtype_target(logtype: i32) -> String {
+    let name = match logtype {
+        id if id < 0 => None,
+        id if (id as u32) < RTE_LOGTYPE_FIRST_EXT_ID => Some(STATIC_LOGTYPES[id as usize])
+            .filter(|name| !name.is_empty())
+            .map(str::to_string),
+        id => REGISTERED_LOGTYPES
+            .lock()
+            .iter()
+            .find(|(registered, _)| *registered == id as u32)
+            .map(|(_, name)| name.clone()),
+    };
+    match name {
+        Some(name) => format!("dpdk::{name}"),
+        None => format!("dpdk::logtype{logtype}"),
+    }
+}
+
+/// A logtype registered with the EAL, so its level can be changed with the `--log-level` EAL
+/// option and [`set_level_pattern`] like the ones of the drivers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LogType {
+    id: u32,
+}
+
+impl LogType {
+    /// Registers `name`, or returns the existing logtype if it is already registered. The level
+    /// is taken from the EAL options if one matches, `default_level` otherwise.
+    pub fn register(name: &str, default_level: LogLevel) -> Result<Self, DpdkError> {
+        let c_name = str_to_c_string(name);
+        let ret =
+            unsafe { rte_log_register_type_and_pick_level(c_name.as_ptr(), default_level as u32) };
+        if ret < 0 {
+            return Err(
+                DpdkError::from_return("rte_log_register_type_and_pick_level", ret).with_name(name),
+            );
+        }
+
+        let id = ret as u32;
+        let mut registered = REGISTERED_LOGTYPES.lock();
+        if !registered.iter().any(|(registered, _)| *registered == id) {
+            registered.push((id, name.to_string()));
+        }
+        Ok(Self { id })
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn level(&self) -> Option<LogLevel> {
+        num::FromPrimitive::from_i32(unsafe { rte_log_get_level(self.id) })
+    }
+
+    pub fn set_level(&self, level: LogLevel) -> Result<(), DpdkError> {
+        DpdkError::check("rte_log_set_level", unsafe {
+            rte_log_set_level(self.id, level as u32)
+        })
+    }
+
+    /// Logs through the EAL, so the message goes to whatever stream DPDK logs to and is
+    /// filtered by the level of this logtype.
+    pub fn log(&self, level: LogLevel, message: impl Display) {
+        let message = CString::new(message.to_string().replace('\0', "\\0"))
+            .expect("NUL bytes were replaced");
+        unsafe {
+            rte_log(
+                level as u32,
+                self.id,
+                b"%s\n\0".as_ptr() as *const libc::c_char,
+                message.as_ptr(),
+            );
+        }
+    }
+}
+
+/// Sets the level of every logtype whose name matches the glob `pattern`, for example
+/// `"pmd.net.*"`. Also applies to logtypes registered later.
+pub fn set_level_pattern(pattern: &str, level: LogLevel) -> Result<(), DpdkError> {
+    let c_pattern = str_to_c_string(pattern);
+    let ret = unsafe { rte_log_set_level_pattern(c_pattern.as_ptr(), level as u32) };
+    DpdkError::check("rte_log_set_level_pattern", ret).map_err(|err| err.with_name(pattern))
+}
+
+/// Messages above this level are dropped before the level of their logtype is checked.
+pub fn global_level() -> Option<LogLevel> {
+    num::FromPrimitive::from_u32(unsafe { rte_log_get_global_level() })
+}
+
+pub fn set_global_level(level: LogLevel) {
+    unsafe { rte_log_set_global_level(level as u32) }
+}
+
+thread_local! {
+    /// DPDK may hand a message to the stream in several writes, each lcore is its own thread so
+    /// partial lines are kept apart until they are complete.
+    static PENDING_LINE: RefCell<Vec<u8>> = RefCell::new(Vec::new());
+}
+
+fn forward_line(line: &[u8]) {
+    let line = String::from_utf8_lossy(line);
+    let line = line.trim_end();
+    if line.is_empty() {
+        return;
+    }
+
+    let level = num::FromPrimitive::from_i32(unsafe { rte_log_cur_msg_loglevel() })
+        .unwrap_or(LogLevel::Info)
+        .to_log_level();
+    let target = logtype_target(unsafe { rte_log_cur_msg_logtype() });
+    let lcore = current_lcore_id();
+
+    let logger = log::logger();
+    if lcore as u32 == LCORE_ID_ANY {
+        logger.log(
+            &log::Record::builder()
+                .level(level)
+                .target(&target)
+                .args(format_args!("[non-EAL thread] {line}"))
+                .build(),
+        );
+    } else {
+        logger.log(
+            &log::Record::builder()
+                .level(level)
+                .target(&target)
+                .args(format_args!("[lcore {lcore}] {line}"))
+                .build(),
+        );
+    }
+}
+
+unsafe extern "C" fn log_stream_write(
+    _cookie: *mut c_void,
+    buf: *const libc::c_char,
+    size: libc::size_t,
+) -> libc::ssize_t {
+    let bytes = unsafe { std::slice::from_raw_parts(buf as *const u8, size) };
+
+    // This runs inside of rte_log, a panicking logger may not unwind into C
+    let result = std::panic::catch_unwind(|| {
+        PENDING_LINE.with(|pending| {
+            let mut pending = pending.borrow_mut();
+            pending.extend_from_slice(bytes);
+            while let Some(end) = pending.iter().position(|byte| *byte == b'\n') {
+                let line = pending.drain(..=end).collect::<Vec<_>>();
+                forward_line(&line);
+            }
+        })
+    });
+    if result.is_err() {
+        return -1;
+    }
+    size as libc::ssize_t
+}
+
+static LOG_STREAM: Mutex<Option<usize>> = Mutex::new(None);
+
+/// Replaces the stderr/syslog output of the EAL and the drivers with the `log` facade. Messages
+/// are logged with the target `dpdk::<logtype>` and prefixed with the lcore that logged them.
+///
+/// Call this before [`crate::config::DPDKConfig::apply`] to also capture the EAL init logs.
+/// Calling it again does nothing.
+pub fn redirect_to_log() -> Result<(), DpdkError> {
+    let mut stream = LOG_STREAM.lock();
+    if stream.is_some() {
+        return Ok(());
+    }
+
+    let io_funcs = cookie_io_functions_t {
+        write: Some(log_stream_write),
+        ..Default::default()
+    };
+    let file = unsafe {
+        fopencookie(
+            std::ptr::null_mut(),
+            b"w\0".as_ptr() as *const libc::c_char,
+            io_funcs,
+        )
+    };
+    if file.is_null() {
+        return Err(DpdkError::from_errno(
+            "fopencookie",
+            std::io::Error::last_os_error().raw_os_error().unwrap_or(libc::ENOMEM),
+        ));
+    }
+
+    let ret = unsafe { rte_openlog_stream(file) };
+    if ret != 0 {
+        unsafe { libc::fclose(file as *mut libc::FILE) };
+        return Err(DpdkError::from_return("rte_openlog_stream", ret));
+    }
+
+    // The EAL keeps using the stream until the process exits, so it is never closed
+    *stream = Some(file as usize);
+    Ok(())
+}
+
+/// Sends DPDK logs back to its default output.
+pub fn restore_default_stream() {
+    let mut stream = LOG_STREAM.lock();
+    if let Some(file) = stream.take() {
+        unsafe {
+            rte_openlog_stream(std::ptr::null_mut());
+            libc::fclose(file as *mut FILE as *mut libc::FILE);
+        }
+    }
+}
@@ -2,7 +2,7 @@ use std::marker::PhantomData;
 
 use dpdk_sys::{rte_mempool, rte_mempool_create, rte_mempool_free};
 
-use crate::eal::RteErrnoValue;
+use crate::error::DpdkError;
 
 #[repr(transparent)]
 pub struct MbufPool<'mempool, T> {
@@ -15,7 +15,7 @@ impl<'mempool, T> MbufPool<'mempool, T> {
         name: &'static str,
         num_elements: u32,
         cache_size: u32,
-    ) -> Result<Self, RteErrnoValue> {
+    ) -> Result<Self, DpdkError> {
         let c_name = name.as_ptr() as *const i8;
         let pool = unsafe {
             rte_mempool_create(
@@ -34,7 +34,7 @@ impl<'mempool, T> MbufPool<'mempool, T> {
         };
 
         if pool == std::ptr::null_mut() {
-            Err(RteErrnoValue::most_recent())
+            Err(DpdkError::from_rte_errno("rte_mempool_create").with_name(name))
         } else {
             Ok(Self {
                 pool,
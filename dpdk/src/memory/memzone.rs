@@ -0,0 +1,289 @@
+use std::{
+    backtrace::Backtrace,
+    ffi::CStr,
+    marker::PhantomData,
+    mem::{align_of, size_of},
+    ops::Deref,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use bitflags::bitflags;
+use dpdk_sys::{
+    rte_memzone, rte_memzone_free, rte_memzone_lookup, rte_memzone_reserve_aligned,
+    rte_memzone_walk, RTE_CACHE_LINE_SIZE, RTE_MEMZONE_16GB, RTE_MEMZONE_16MB, RTE_MEMZONE_1GB,
+    RTE_MEMZONE_256KB, RTE_MEMZONE_256MB, RTE_MEMZONE_2MB, RTE_MEMZONE_4GB, RTE_MEMZONE_512MB,
+    RTE_MEMZONE_IOVA_CONTIG, RTE_MEMZONE_NAMESIZE, RTE_MEMZONE_SIZE_HINT_ONLY, SOCKET_ID_ANY,
+};
+use libc::c_void;
+
+use crate::{error::DpdkError, util::str_to_c_string};
+
+bitflags! {
+    pub struct MemzoneFlags: u32 {
+        const PAGE_2MB = RTE_MEMZONE_2MB;
+        const PAGE_1GB = RTE_MEMZONE_1GB;
+        const PAGE_16MB = RTE_MEMZONE_16MB;
+        const PAGE_16GB = RTE_MEMZONE_16GB;
+        const PAGE_256KB = RTE_MEMZONE_256KB;
+        const PAGE_256MB = RTE_MEMZONE_256MB;
+        const PAGE_512MB = RTE_MEMZONE_512MB;
+        const PAGE_4GB = RTE_MEMZONE_4GB;
+        /// Fall back to other page sizes if the requested one is not available
+        const SIZE_HINT_ONLY = RTE_MEMZONE_SIZE_HINT_ONLY;
+        /// The memzone must be IOVA-contiguous, for example for DMA
+        const IOVA_CONTIG = RTE_MEMZONE_IOVA_CONTIG;
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum MemzoneError {
+    #[error("Invalid memzone name {name:?}, names must be shorter than {RTE_MEMZONE_NAMESIZE} bytes and may not contain NUL")]
+    InvalidName { name: String, backtrace: Backtrace },
+    #[error(transparent)]
+    Dpdk(#[from] DpdkError),
+    #[error("No memzone named {name}")]
+    NotFound { name: String, backtrace: Backtrace },
+    #[error("Memzone {name} was not created by Memzone<T> or is not initialized yet")]
+    NotTyped { name: String, backtrace: Backtrace },
+    #[error("Memzone {name} holds a value of type {found_size} bytes aligned to {found_align}, expected {expected} ({expected_size} bytes aligned to {expected_align})")]
+    TypeMismatch {
+        name: String,
+        expected: &'static str,
+        expected_size: usize,
+        expected_align: usize,
+        found_size: usize,
+        found_align: usize,
+        backtrace: Backtrace,
+    },
+}
+
+const MEMZONE_MAGIC: u64 = u64::from_be_bytes(*b"RSMZONE1");
+
+/// Written at the start of every memzone made by [`Memzone::reserve`], so lookups from other
+/// processes can check what they are attaching to.
+#[repr(C)]
+struct MemzoneHeader {
+    /// Stored last, once the value is initialized
+    magic: AtomicU64,
+    type_hash: u64,
+    size: u64,
+    align: u64,
+}
+
+/// FNV-1a of the type name. `TypeId` is not stable between binaries, the name is as long as
+/// both processes were built from the same source.
+fn type_hash<T>() -> u64 {
+    std::any::type_name::<T>()
+        .bytes()
+        .fold(0xcbf29ce484222325, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        })
+}
+
+const fn value_offset<T>() -> usize {
+    let header = size_of::<MemzoneHeader>();
+    let align = align_of::<T>();
+    (header + align - 1) / align * align
+}
+
+fn check_name(name: &str) -> Result<(), MemzoneError> {
+    if name.len() >= RTE_MEMZONE_NAMESIZE as usize || name.contains('\0') {
+        Err(MemzoneError::InvalidName {
+            name: name.to_string(),
+            backtrace: Backtrace::capture(),
+        })
+    } else {
+        Ok(())
+    }
+}
+
+/// A `T` living in a named memzone, which other processes of the same application can find
+/// with [`Memzone::lookup`].
+///
+/// Memzones are mapped at the same address in every process, but `T` should still only point
+/// into DPDK memory. Anything allocated with the Rust global allocator is private to the
+/// process that allocated it.
+pub struct Memzone<T> {
+    inner: *const rte_memzone,
+    /// Memzones found with [`Memzone::lookup`] belong to whichever process reserved them
+    owned: bool,
+    _phantom: PhantomData<T>,
+}
+
+unsafe impl<T: Send> Send for Memzone<T> {}
+unsafe impl<T: Sync> Sync for Memzone<T> {}
+
+impl<T> Memzone<T> {
+    /// Reserves a memzone on `socket_id`, or any socket if None, and moves `value` into it.
+    pub fn reserve(
+        name: &str,
+        socket_id: Option<u32>,
+        flags: MemzoneFlags,
+        value: T,
+    ) -> Result<Self, MemzoneError> {
+        check_name(name)?;
+        let c_name = str_to_c_string(name);
+        let len = value_offset::<T>() + size_of::<T>();
+        let align = align_of::<T>()
+            .max(align_of::<MemzoneHeader>())
+            .max(RTE_CACHE_LINE_SIZE as usize);
+
+        let inner = unsafe {
+            rte_memzone_reserve_aligned(
+                c_name.as_ptr(),
+                len as dpdk_sys::size_t,
+                socket_id.map(|id| id as i32).unwrap_or(SOCKET_ID_ANY),
+                flags.bits(),
+                align as u32,
+            )
+        };
+        if inner.is_null() {
+            return Err(DpdkError::from_rte_errno("rte_memzone_reserve_aligned")
+                .with_name(name)
+                .into());
+        }
+
+        let memzone = Self {
+            inner,
+            owned: true,
+            _phantom: PhantomData,
+        };
+        unsafe {
+            let header = memzone.base() as *mut MemzoneHeader;
+            header.write(MemzoneHeader {
+                magic: AtomicU64::new(0),
+                type_hash: type_hash::<T>(),
+                size: size_of::<T>() as u64,
+                align: align_of::<T>() as u64,
+            });
+            (memzone.value_ptr() as *mut T).write(value);
+            (*header).magic.store(MEMZONE_MAGIC, Ordering::Release);
+        }
+        Ok(memzone)
+    }
+
+    /// Finds a memzone reserved by [`Memzone::reserve`] in this or another process, checking
+    /// that it holds a `T`. The memzone is not freed when the handle is dropped.
+    pub fn lookup(name: &str) -> Result<Self, MemzoneError> {
+        check_name(name)?;
+        let c_name = str_to_c_string(name);
+        let inner = unsafe { rte_memzone_lookup(c_name.as_ptr()) };
+        if inner.is_null() {
+            return Err(MemzoneError::NotFound {
+                name: name.to_string(),
+                backtrace: Backtrace::capture(),
+            });
+        }
+
+        let memzone = Self {
+            inner,
+            owned: false,
+            _phantom: PhantomData,
+        };
+
+        if memzone.info().len < value_offset::<T>() {
+            return Err(MemzoneError::NotTyped {
+                name: name.to_string(),
+                backtrace: Backtrace::capture(),
+            });
+        }
+        let header = unsafe { &*(memzone.base() as *const MemzoneHeader) };
+        if header.magic.load(Ordering::Acquire) != MEMZONE_MAGIC {
+            return Err(MemzoneError::NotTyped {
+                name: name.to_string(),
+                backtrace: Backtrace::capture(),
+            });
+        }
+        if header.type_hash != type_hash::<T>()
+            || header.size != size_of::<T>() as u64
+            || header.align != align_of::<T>() as u64
+        {
+            return Err(MemzoneError::TypeMismatch {
+                name: name.to_string(),
+                expected: std::any::type_name::<T>(),
+                expected_size: size_of::<T>(),
+                expected_align: align_of::<T>(),
+                found_size: header.size as usize,
+                found_align: header.align as usize,
+                backtrace: Backtrace::capture(),
+            });
+        }
+
+        Ok(memzone)
+    }
+
+    pub fn info(&self) -> MemzoneInfo {
+        MemzoneInfo::from_raw(unsafe { &*self.inner })
+    }
+
+    fn base(&self) -> *mut c_void {
+        unsafe { (*self.inner).__bindgen_anon_1.addr }
+    }
+
+    fn value_ptr(&self) -> *const T {
+        unsafe { (self.base() as *const u8).add(value_offset::<T>()) as *const T }
+    }
+}
+
+impl<T: Sync> Deref for Memzone<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.value_ptr() }
+    }
+}
+
+impl<T> Drop for Memzone<T> {
+    fn drop(&mut self) {
+        if self.owned {
+            unsafe {
+                std::ptr::drop_in_place(self.value_ptr() as *mut T);
+                rte_memzone_free(self.inner);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MemzoneInfo {
+    pub name: String,
+    pub addr: *mut c_void,
+    pub iova: u64,
+    pub len: usize,
+    pub hugepage_size: u64,
+    pub socket_id: i32,
+    pub flags: MemzoneFlags,
+}
+
+impl MemzoneInfo {
+    fn from_raw(memzone: &rte_memzone) -> Self {
+        Self {
+            name: unsafe { CStr::from_ptr(memzone.name.as_ptr()) }
+                .to_string_lossy()
+                .into_owned(),
+            addr: unsafe { memzone.__bindgen_anon_1.addr },
+            iova: memzone.iova,
+            len: memzone.len as usize,
+            hugepage_size: memzone.hugepage_sz,
+            socket_id: memzone.socket_id,
+            flags: MemzoneFlags::from_bits_truncate(memzone.flags),
+        }
+    }
+}
+
+unsafe extern "C" fn collect_memzone(memzone: *const rte_memzone, arg: *mut c_void) {
+    let memzones = unsafe { &mut *(arg as *mut Vec<MemzoneInfo>) };
+    memzones.push(MemzoneInfo::from_raw(unsafe { &*memzone }));
+}
+
+/// Every memzone currently reserved, by any process and by DPDK itself.
+pub fn iter_memzones() -> impl Iterator<Item = MemzoneInfo> {
+    let mut memzones: Vec<MemzoneInfo> = vec![];
+    unsafe {
+        rte_memzone_walk(
+            Some(collect_memzone),
+            &mut memzones as *mut Vec<MemzoneInfo> as *mut c_void,
+        )
+    };
+    memzones.into_iter()
+}
@@ -2,3 +2,4 @@ pub mod allocator;
 pub mod mempool;
 pub mod pktmbuf_pool;
 pub mod mbuf;
+pub mod memzone;
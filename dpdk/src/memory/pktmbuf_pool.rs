@@ -1,30 +1,62 @@
 use dpdk_sys::{
     rte_pktmbuf_pool_create, rte_mempool, RTE_MBUF_DEFAULT_BUF_SIZE, rte_socket_id, rte_mempool_free,
+    rte_mempool_lookup,
 };
 
-use crate::eal::RteErrnoValue;
+use crate::{error::DpdkError, util::str_to_c_string};
 
-#[repr(transparent)]
 pub struct PktMbufPool {
-    pool: *mut rte_mempool
+    pool: *mut rte_mempool,
+    /// Pools found with [`PktMbufPool::lookup`] belong to whichever process created them
+    owned: bool,
 }
 
 impl PktMbufPool {
-    pub fn new(
-        name: &'static str,
+    pub fn new(name: &str, num_elements: u32, cache_size: u32) -> Result<Self, DpdkError> {
+        Self::new_on_socket(name, num_elements, cache_size, unsafe { rte_socket_id() } as i32)
+    }
+
+    /// Allocates the pool on the memory of `socket_id`, or anywhere with `SOCKET_ID_ANY`.
+    pub fn new_on_socket(
+        name: &str,
         num_elements: u32,
         cache_size: u32,
-    ) -> Result<Self, RteErrnoValue> {
-        let c_name = name.as_ptr() as *const i8;
+        socket_id: i32,
+    ) -> Result<Self, DpdkError> {
+        let c_name = str_to_c_string(name);
         let pool = unsafe {
-            rte_pktmbuf_pool_create(c_name, num_elements, cache_size, 0, RTE_MBUF_DEFAULT_BUF_SIZE as u16, rte_socket_id() as i32)
+            rte_pktmbuf_pool_create(
+                c_name.as_ptr(),
+                num_elements,
+                cache_size,
+                0,
+                RTE_MBUF_DEFAULT_BUF_SIZE as u16,
+                socket_id,
+            )
         };
 
         if pool == std::ptr::null_mut() {
-            Err(RteErrnoValue::most_recent())
+            Err(DpdkError::from_rte_errno("rte_pktmbuf_pool_create").with_name(name))
+        } else {
+            Ok(Self {
+                pool,
+                owned: true,
+            })
+        }
+    }
+
+    /// Finds a pool created by this or another process, such as the primary process of a
+    /// multi-process application. The pool is not freed when the handle is dropped.
+    pub fn lookup(name: &str) -> Result<Self, DpdkError> {
+        let c_name = str_to_c_string(name);
+        let pool = unsafe { rte_mempool_lookup(c_name.as_ptr()) };
+
+        if pool == std::ptr::null_mut() {
+            Err(DpdkError::from_rte_errno("rte_mempool_lookup").with_name(name))
         } else {
             Ok(Self {
                 pool,
+                owned: false,
             })
         }
     }
@@ -32,8 +64,10 @@ impl PktMbufPool {
 
 impl Drop for PktMbufPool {
     fn drop(&mut self) {
-        unsafe {
-            rte_mempool_free(self.pool)
+        if self.owned {
+            unsafe {
+                rte_mempool_free(self.pool)
+            }
         }
     }
 }
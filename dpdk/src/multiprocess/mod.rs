@@ -0,0 +1,283 @@
+use std::{
+    backtrace::Backtrace,
+    ffi::{CStr, CString},
+    marker::PhantomData,
+    sync::Arc,
+    time::Duration,
+};
+
+use dpdk_sys::{
+    rte_mp_action_register, rte_mp_action_unregister, rte_mp_msg, rte_mp_reply,
+    rte_mp_request_sync, rte_mp_sendmsg, timespec, RTE_MP_MAX_FD_NUM, RTE_MP_MAX_NAME_LEN,
+    RTE_MP_MAX_PARAM_LEN,
+};
+use libc::c_void;
+use parking_lot::Mutex;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{callback::catch_panic, error::DpdkError};
+
+/// Messages are serialized with bincode into the parameter area of an `rte_mp_msg`
+pub const MAX_MESSAGE_LEN: usize = RTE_MP_MAX_PARAM_LEN as usize;
+
+/// A message that can be sent to the other processes. `NAME` selects the action that handles
+/// it on the receiving side.
+pub trait MpMessage: Serialize + DeserializeOwned {
+    const NAME: &'static str;
+}
+
+/// A message that expects a reply from every process that handles it.
+pub trait MpRequest: MpMessage {
+    type Reply: Serialize + DeserializeOwned;
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum MpError {
+    #[error("Invalid action name {name:?}, names must be shorter than {RTE_MP_MAX_NAME_LEN} bytes and may not contain NUL")]
+    InvalidName { name: String, backtrace: Backtrace },
+    #[error("Message for {name} is {size} bytes, at most {MAX_MESSAGE_LEN} bytes fit in an rte_mp_msg")]
+    MessageTooLarge {
+        name: String,
+        size: usize,
+        backtrace: Backtrace,
+    },
+    #[error("Unable to serialize or deserialize message for {name}")]
+    Serialization {
+        name: String,
+        #[source]
+        source: bincode::Error,
+        backtrace: Backtrace,
+    },
+    #[error("An action for {name} is already registered")]
+    ActionExists { name: String, backtrace: Backtrace },
+    #[error(transparent)]
+    Dpdk(#[from] DpdkError),
+}
+
+fn encode_name(name: &str) -> Result<[libc::c_char; RTE_MP_MAX_NAME_LEN as usize], MpError> {
+    if name.len() >= RTE_MP_MAX_NAME_LEN as usize || name.contains('\0') {
+        return Err(MpError::InvalidName {
+            name: name.to_string(),
+            backtrace: Backtrace::capture(),
+        });
+    }
+    let mut encoded = [0; RTE_MP_MAX_NAME_LEN as usize];
+    for (dst, src) in encoded.iter_mut().zip(name.bytes()) {
+        *dst = src as libc::c_char;
+    }
+    Ok(encoded)
+}
+
+fn encode_message<M: Serialize>(name: &str, message: &M) -> Result<rte_mp_msg, MpError> {
+    let bytes = bincode::serialize(message).map_err(|source| MpError::Serialization {
+        name: name.to_string(),
+        source,
+        backtrace: Backtrace::capture(),
+    })?;
+    if bytes.len() > MAX_MESSAGE_LEN {
+        return Err(MpError::MessageTooLarge {
+            name: name.to_string(),
+            size: bytes.len(),
+            backtrace: Backtrace::capture(),
+        });
+    }
+
+    let mut msg = rte_mp_msg {
+        name: encode_name(name)?,
+        len_param: bytes.len() as i32,
+        num_fds: 0,
+        param: [0; RTE_MP_MAX_PARAM_LEN as usize],
+        fds: [0; RTE_MP_MAX_FD_NUM as usize],
+    };
+    msg.param[..bytes.len()].copy_from_slice(&bytes);
+    Ok(msg)
+}
+
+fn decode_message<M: DeserializeOwned>(name: &str, msg: &rte_mp_msg) -> Result<M, MpError> {
+    let len = (msg.len_param.max(0) as usize).min(MAX_MESSAGE_LEN);
+    bincode::deserialize(&msg.param[..len]).map_err(|source| MpError::Serialization {
+        name: name.to_string(),
+        source,
+        backtrace: Backtrace::capture(),
+    })
+}
+
+fn driver_error(operation: &'static str, name: &str) -> MpError {
+    DpdkError::from_rte_errno(operation).with_name(name).into()
+}
+
+/// Sends a message to the other processes without waiting for it to be handled.
+pub fn send<M: MpMessage>(message: &M) -> Result<(), MpError> {
+    let mut msg = encode_message(M::NAME, message)?;
+    let ret = unsafe { rte_mp_sendmsg(&mut msg) };
+    if ret == 0 {
+        Ok(())
+    } else {
+        Err(driver_error("rte_mp_sendmsg", M::NAME))
+    }
+}
+
+/// Replies collected by [`request_sync`].
+pub struct MpReplies<T> {
+    /// How many processes the request was sent to
+    pub sent: usize,
+    /// One entry per process that replied before the timeout
+    pub replies: Vec<Result<T, MpError>>,
+}
+
+impl<T> MpReplies<T> {
+    pub fn all_replied(&self) -> bool {
+        self.sent == self.replies.len()
+    }
+}
+
+/// Sends a request to the other processes and waits up to `timeout` for their replies. A
+/// secondary process only talks to the primary, the primary talks to every secondary.
+pub fn request_sync<R: MpRequest>(
+    request: &R,
+    timeout: Duration,
+) -> Result<MpReplies<R::Reply>, MpError> {
+    let mut msg = encode_message(R::NAME, request)?;
+    let mut reply = rte_mp_reply {
+        nb_sent: 0,
+        nb_received: 0,
+        msgs: std::ptr::null_mut(),
+    };
+    let ts = timespec {
+        tv_sec: timeout.as_secs() as _,
+        tv_nsec: timeout.subsec_nanos() as _,
+    };
+
+    let ret = unsafe { rte_mp_request_sync(&mut msg, &mut reply, &ts) };
+    let result = if ret == 0 {
+        let msgs = if reply.msgs.is_null() {
+            &[][..]
+        } else {
+            unsafe { std::slice::from_raw_parts(reply.msgs, reply.nb_received.max(0) as usize) }
+        };
+        Ok(MpReplies {
+            sent: reply.nb_sent.max(0) as usize,
+            replies: msgs.iter().map(|msg| decode_message(R::NAME, msg)).collect(),
+        })
+    } else {
+        Err(driver_error("rte_mp_request_sync", R::NAME))
+    };
+
+    // The reply array is allocated by the EAL with malloc
+    unsafe { libc::free(reply.msgs as *mut c_void) };
+    result
+}
+
+/// The process a message came from, used to send it a reply.
+pub struct MpPeer<'msg> {
+    msg: &'msg rte_mp_msg,
+    peer: *const c_void,
+}
+
+impl<'msg> MpPeer<'msg> {
+    fn reply<T: Serialize>(&self, reply: &T) -> Result<(), MpError> {
+        let name = unsafe { CStr::from_ptr(self.msg.name.as_ptr()) }.to_string_lossy();
+        let mut msg = encode_message(&name, reply)?;
+        let ret = unsafe { rte_mp_reply(&mut msg, self.peer as *const libc::c_char) };
+        if ret == 0 {
+            Ok(())
+        } else {
+            Err(driver_error("rte_mp_reply", &name))
+        }
+    }
+}
+
+type ActionHandler = dyn Fn(&rte_mp_msg, MpPeer) -> Result<(), MpError> + Send + Sync;
+
+/// `rte_mp_t` has no user data pointer, so handlers are found by the message name.
+static ACTIONS: Mutex<Vec<(String, Arc<ActionHandler>)>> = Mutex::new(Vec::new());
+
+unsafe extern "C" fn action_trampoline(msg: *const rte_mp_msg, peer: *const c_void) -> i32 {
+    let msg = unsafe { &*msg };
+    let name = unsafe { CStr::from_ptr(msg.name.as_ptr()) }.to_string_lossy();
+    let handler = ACTIONS
+        .lock()
+        .iter()
+        .find(|(action, _)| *action == name)
+        .map(|(_, handler)| handler.clone());
+
+    let handler = match handler {
+        Some(handler) => handler,
+        None => return -1,
+    };
+
+    let action = || handler(msg, MpPeer { msg, peer });
+    match catch_panic(format_args!("Multi-process action {name}"), action) {
+        Some(Ok(())) => 0,
+        Some(Err(err)) => {
+            log::error!("Multi-process action {name} failed: {err}");
+            -1
+        }
+        None => -1,
+    }
+}
+
+/// A registered IPC action, unregistered when dropped.
+pub struct MpAction<M> {
+    name: CString,
+    _phantom: PhantomData<fn(M)>,
+}
+
+impl<M: MpMessage> MpAction<M> {
+    fn register(handler: Arc<ActionHandler>) -> Result<Self, MpError> {
+        encode_name(M::NAME)?;
+        let name = CString::new(M::NAME).expect("Checked by encode_name");
+
+        let mut actions = ACTIONS.lock();
+        if actions.iter().any(|(action, _)| action == M::NAME) {
+            return Err(MpError::ActionExists {
+                name: M::NAME.to_string(),
+                backtrace: Backtrace::capture(),
+            });
+        }
+
+        actions.push((M::NAME.to_string(), handler));
+        let ret = unsafe { rte_mp_action_register(name.as_ptr(), Some(action_trampoline)) };
+        if ret != 0 {
+            actions.retain(|(action, _)| action != M::NAME);
+            return Err(driver_error("rte_mp_action_register", M::NAME));
+        }
+
+        Ok(MpAction {
+            name,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Calls `f` for every `M` sent to this process with [`send`].
+    pub fn on_message<F>(f: F) -> Result<Self, MpError>
+    where
+        F: Fn(M) + Send + Sync + 'static,
+    {
+        Self::register(Arc::new(move |msg, _peer| {
+            f(decode_message(M::NAME, msg)?);
+            Ok(())
+        }))
+    }
+}
+
+impl<R: MpRequest> MpAction<R> {
+    /// Answers every `R` sent to this process with [`request_sync`] with the value `f` returns.
+    pub fn on_request<F>(f: F) -> Result<Self, MpError>
+    where
+        F: Fn(R) -> R::Reply + Send + Sync + 'static,
+    {
+        Self::register(Arc::new(move |msg, peer| {
+            let reply = f(decode_message(R::NAME, msg)?);
+            peer.reply(&reply)
+        }))
+    }
+}
+
+impl<M> Drop for MpAction<M> {
+    fn drop(&mut self) {
+        unsafe { rte_mp_action_unregister(self.name.as_ptr()) };
+        let name = self.name.to_string_lossy();
+        ACTIONS.lock().retain(|(action, _)| *action != name);
+    }
+}
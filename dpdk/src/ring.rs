@@ -2,7 +2,7 @@ use std::{sync::Arc, marker::PhantomData};
 
 use dpdk_sys;
 
-use crate::{eal::RteErrnoValue, util::str_to_c_string};
+use crate::{error::DpdkError, util::str_to_c_string};
 
 pub enum RingType {
     Single,
@@ -44,6 +44,8 @@ pub struct RteRingMCHandle<T> {
 
 pub struct RteRing<T> {
     inner: *mut dpdk_sys::rte_ring,
+    /// Rings found with [`RteRing::lookup`] belong to whichever process created them
+    owned: bool,
     _phantom: PhantomData<*mut T>
 }
 
@@ -54,7 +56,7 @@ impl<T> RteRing<T> {
         numa_socket_id: i32,
         producer_type: RingType,
         consumer_type: RingType,
-    ) -> Result<Arc<Self>, RteErrnoValue> {
+    ) -> Result<Arc<Self>, DpdkError> {
         let str = str_to_c_string(name.as_ref());
         let str = Box::new(str);
         let str = Box::leak(str);
@@ -76,18 +78,33 @@ impl<T> RteRing<T> {
         let ptr = unsafe { dpdk_sys::rte_ring_create(str, size, numa_socket_id, flags) };
 
         if ptr.is_null() {
-            Err(RteErrnoValue::most_recent())
+            Err(DpdkError::from_rte_errno("rte_ring_create").with_name(name.as_ref()))
         } else {
-            let handle = Arc::new(Self { inner: ptr, _phantom: Default::default() });
+            let handle = Arc::new(Self { inner: ptr, owned: true, _phantom: Default::default() });
             Ok(handle)
         }
     }
+
+    /// Finds a ring created by this or another process, such as the primary process of a
+    /// multi-process application. The ring is not freed when the handle is dropped.
+    pub fn lookup(name: impl AsRef<str>) -> Result<Arc<Self>, DpdkError> {
+        let str = str_to_c_string(name.as_ref());
+        let ptr = unsafe { dpdk_sys::rte_ring_lookup(str.as_ptr()) };
+
+        if ptr.is_null() {
+            Err(DpdkError::from_rte_errno("rte_ring_lookup").with_name(name.as_ref()))
+        } else {
+            Ok(Arc::new(Self { inner: ptr, owned: false, _phantom: Default::default() }))
+        }
+    }
 }
 
 impl<T> Drop for RteRing<T> {
     fn drop(&mut self) {
-        unsafe {
-            dpdk_sys::rte_ring_free(self.inner)
+        if self.owned {
+            unsafe {
+                dpdk_sys::rte_ring_free(self.inner)
+            }
         }
     }
 }
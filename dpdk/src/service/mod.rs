@@ -0,0 +1,388 @@
+use std::{backtrace::Backtrace, ffi::CStr, ops::Deref};
+
+use dpdk_sys::{
+    rte_event_dev_service_id_get, rte_event_eth_rx_adapter_service_id_get,
+    rte_event_eth_tx_adapter_service_id_get, rte_service_attr_get, rte_service_attr_reset_all,
+    rte_service_component_register, rte_service_component_runstate_set,
+    rte_service_component_unregister, rte_service_get_by_name, rte_service_get_count,
+    rte_service_get_name, rte_service_lcore_add, rte_service_lcore_count_services,
+    rte_service_lcore_del, rte_service_lcore_list, rte_service_lcore_start,
+    rte_service_lcore_stop, rte_service_map_lcore_get, rte_service_map_lcore_set,
+    rte_service_may_be_active, rte_service_probe_capability, rte_service_run_iter_on_app_lcore,
+    rte_service_runstate_get, rte_service_runstate_set, rte_service_set_stats_enable,
+    rte_service_spec, EAGAIN, ESRCH, RTE_MAX_LCORE, RTE_SERVICE_ATTR_CALL_COUNT,
+    RTE_SERVICE_ATTR_CYCLES, RTE_SERVICE_CAP_MT_SAFE, RTE_SERVICE_NAME_MAX, SOCKET_ID_ANY,
+};
+use libc::c_void;
+use parking_lot::Mutex;
+
+use crate::{
+    callback::catch_panic,
+    device::event::{
+        eth::{rx::rx_adapter::RxAdapterId, tx::tx_adapter::TxAdapterId},
+        EventDeviceId,
+    },
+    eal::LCoreId,
+    error::DpdkError,
+};
+
+pub type ServiceId = u32;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ServiceError {
+    #[error("Invalid service name {name:?}, names must be shorter than {RTE_SERVICE_NAME_MAX} bytes and may not contain NUL")]
+    InvalidName { name: String, backtrace: Backtrace },
+    #[error("No service named {name}")]
+    NotFound { name: String, backtrace: Backtrace },
+    #[error(transparent)]
+    Dpdk(#[from] DpdkError),
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ServiceStats {
+    /// Cycles spent inside the service callback, only counted while stats are enabled
+    pub cycles: u64,
+    /// Number of times the service callback was invoked, only counted while stats are enabled
+    pub calls: u64,
+}
+
+/// Handle to a service registered with the EAL, either by a driver or from Rust.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Service {
+    id: ServiceId,
+}
+
+impl Service {
+    pub fn from_id(id: ServiceId) -> Option<Service> {
+        if id < service_count() && !unsafe { rte_service_get_name(id) }.is_null() {
+            Some(Service { id })
+        } else {
+            None
+        }
+    }
+
+    pub fn by_name(name: &str) -> Result<Service, ServiceError> {
+        let c_name = service_name_to_c_string(name)?;
+        let mut id: ServiceId = 0;
+        let ret = unsafe { rte_service_get_by_name(c_name.as_ptr() as *const i8, &mut id) };
+        if ret == 0 {
+            Ok(Service { id })
+        } else {
+            Err(ServiceError::NotFound {
+                name: name.to_string(),
+                backtrace: Backtrace::capture(),
+            })
+        }
+    }
+
+    /// The scheduling service of a software event device, None if the device schedules in HW.
+    pub fn for_event_dev(device_id: EventDeviceId) -> Option<Service> {
+        let mut id: ServiceId = 0;
+        let ret = unsafe { rte_event_dev_service_id_get(device_id, &mut id) };
+        service_from_id_get(ret, id)
+    }
+
+    /// The service moving packets for an rx adapter, None if the adapter has an internal port.
+    pub fn for_rx_adapter(adapter_id: RxAdapterId) -> Option<Service> {
+        let mut id: ServiceId = 0;
+        let ret = unsafe { rte_event_eth_rx_adapter_service_id_get(adapter_id, &mut id) };
+        service_from_id_get(ret, id)
+    }
+
+    /// The service moving packets for a tx adapter, None if the adapter has an internal port.
+    pub fn for_tx_adapter(adapter_id: TxAdapterId) -> Option<Service> {
+        let mut id: ServiceId = 0;
+        let ret = unsafe { rte_event_eth_tx_adapter_service_id_get(adapter_id, &mut id) };
+        service_from_id_get(ret, id)
+    }
+
+    pub fn id(&self) -> ServiceId {
+        self.id
+    }
+
+    pub fn name(&self) -> String {
+        let name = unsafe { rte_service_get_name(self.id) };
+        if name.is_null() {
+            String::new()
+        } else {
+            unsafe { CStr::from_ptr(name) }.to_string_lossy().into_owned()
+        }
+    }
+
+    /// Whether several lcores may run this service at the same time.
+    pub fn is_mt_safe(&self) -> bool {
+        unsafe { rte_service_probe_capability(self.id, RTE_SERVICE_CAP_MT_SAFE) == 1 }
+    }
+
+    pub fn map_lcore(&self, lcore: LCoreId, enable: bool) -> Result<(), DpdkError> {
+        let ret = unsafe { rte_service_map_lcore_set(self.id, lcore as u32, enable as u32) };
+        DpdkError::check("rte_service_map_lcore_set", ret)
+            .map_err(|err| err.with_service(self.id).with_lcore(lcore))
+    }
+
+    pub fn is_mapped_to(&self, lcore: LCoreId) -> bool {
+        unsafe { rte_service_map_lcore_get(self.id, lcore as u32) == 1 }
+    }
+
+    /// Service lcores this service is currently mapped to.
+    pub fn mapped_lcores(&self) -> Vec<LCoreId> {
+        service_lcores()
+            .into_iter()
+            .filter(|lcore| self.is_mapped_to(*lcore))
+            .collect()
+    }
+
+    /// Sets the application runstate, a service only runs once both the application and the
+    /// component that registered it set it to running.
+    pub fn set_running(&self, running: bool) -> Result<(), DpdkError> {
+        let ret = unsafe { rte_service_runstate_set(self.id, running as u32) };
+        DpdkError::check("rte_service_runstate_set", ret).map_err(|err| err.with_service(self.id))
+    }
+
+    pub fn is_running(&self) -> bool {
+        unsafe { rte_service_runstate_get(self.id) == 1 }
+    }
+
+    /// Whether any lcore may still be inside the service callback.
+    pub fn may_be_active(&self) -> bool {
+        unsafe { rte_service_may_be_active(self.id) == 1 }
+    }
+
+    /// Runs one iteration of the service on the calling lcore instead of a service core.
+    pub fn run_iter_on_app_lcore(&self, serialize_mt_unsafe: bool) -> Result<(), DpdkError> {
+        let ret = unsafe { rte_service_run_iter_on_app_lcore(self.id, serialize_mt_unsafe as u32) };
+        DpdkError::check("rte_service_run_iter_on_app_lcore", ret)
+            .map_err(|err| err.with_service(self.id))
+    }
+
+    pub fn set_stats_enabled(&self, enable: bool) -> Result<(), DpdkError> {
+        let ret = unsafe { rte_service_set_stats_enable(self.id, enable as i32) };
+        DpdkError::check("rte_service_set_stats_enable", ret)
+            .map_err(|err| err.with_service(self.id))
+    }
+
+    pub fn stats(&self) -> Result<ServiceStats, DpdkError> {
+        Ok(ServiceStats {
+            cycles: self.attr(RTE_SERVICE_ATTR_CYCLES)?,
+            calls: self.attr(RTE_SERVICE_ATTR_CALL_COUNT)?,
+        })
+    }
+
+    pub fn reset_stats(&self) -> Result<(), DpdkError> {
+        let ret = unsafe { rte_service_attr_reset_all(self.id) };
+        DpdkError::check("rte_service_attr_reset_all", ret).map_err(|err| err.with_service(self.id))
+    }
+
+    fn attr(&self, attr_id: u32) -> Result<u64, DpdkError> {
+        let mut value = 0;
+        let ret = unsafe { rte_service_attr_get(self.id, attr_id, &mut value) };
+        DpdkError::check("rte_service_attr_get", ret).map_err(|err| err.with_service(self.id))?;
+        Ok(value)
+    }
+}
+
+fn service_from_id_get(ret: i32, id: ServiceId) -> Option<Service> {
+    if ret == 0 {
+        Some(Service { id })
+    } else {
+        debug_assert_eq!(ret, -(ESRCH as i32), "Unexpected error getting service id");
+        None
+    }
+}
+
+fn service_name_to_c_string(name: &str) -> Result<Vec<u8>, ServiceError> {
+    if name.len() >= RTE_SERVICE_NAME_MAX as usize || name.contains('\0') {
+        return Err(ServiceError::InvalidName {
+            name: name.to_string(),
+            backtrace: Backtrace::capture(),
+        });
+    }
+    let mut bytes = name.as_bytes().to_vec();
+    bytes.push(0);
+    Ok(bytes)
+}
+
+/// What a service callback did in one iteration, idle iterations are not counted as busy
+/// cycles by the EAL.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServiceIteration {
+    DidWork,
+    Idle,
+}
+
+enum ServiceCallback {
+    /// The EAL never runs a service without `RTE_SERVICE_CAP_MT_SAFE` on two lcores at once,
+    /// so this lock is never contended
+    Exclusive(Mutex<Box<dyn FnMut() -> ServiceIteration + Send>>),
+    Shared(Box<dyn Fn() -> ServiceIteration + Send + Sync>),
+}
+
+unsafe extern "C" fn service_trampoline(arg: *mut c_void) -> i32 {
+    let callback = unsafe { &*(arg as *const ServiceCallback) };
+    let ret = catch_panic(format_args!("Service callback"), || match callback {
+        ServiceCallback::Exclusive(f) => (f.lock())(),
+        ServiceCallback::Shared(f) => f(),
+    });
+    match ret {
+        Some(ServiceIteration::DidWork) => 0,
+        // A panicked iteration is not counted as busy
+        Some(ServiceIteration::Idle) | None => -(EAGAIN as i32),
+    }
+}
+
+/// A service implemented by a Rust closure. The service is unregistered when this is dropped.
+///
+/// Dereferences to [`Service`] for mapping, runstate and statistics.
+pub struct ComponentService {
+    service: Service,
+    callback: *mut ServiceCallback,
+}
+
+// The callback is Send, and only the EAL calls it until the service is dropped
+unsafe impl Send for ComponentService {}
+
+impl ComponentService {
+    /// Registers a service that the EAL will only ever run on one lcore at a time.
+    pub fn register<F>(name: &str, socket_id: Option<u32>, f: F) -> Result<Self, ServiceError>
+    where
+        F: FnMut() -> ServiceIteration + Send + 'static,
+    {
+        let callback = ServiceCallback::Exclusive(Mutex::new(Box::new(f)));
+        Self::register_inner(name, socket_id, 0, callback)
+    }
+
+    /// Registers a service that may run on several service lcores at the same time.
+    pub fn register_mt_safe<F>(
+        name: &str,
+        socket_id: Option<u32>,
+        f: F,
+    ) -> Result<Self, ServiceError>
+    where
+        F: Fn() -> ServiceIteration + Send + Sync + 'static,
+    {
+        let callback = ServiceCallback::Shared(Box::new(f));
+        Self::register_inner(name, socket_id, RTE_SERVICE_CAP_MT_SAFE, callback)
+    }
+
+    fn register_inner(
+        name: &str,
+        socket_id: Option<u32>,
+        capabilities: u32,
+        callback: ServiceCallback,
+    ) -> Result<Self, ServiceError> {
+        let c_name = service_name_to_c_string(name)?;
+        let callback = Box::into_raw(Box::new(callback));
+
+        let mut spec = rte_service_spec {
+            name: [0; RTE_SERVICE_NAME_MAX as usize],
+            callback: Some(service_trampoline),
+            callback_userdata: callback as *mut c_void,
+            capabilities,
+            socket_id: socket_id.map(|id| id as i32).unwrap_or(SOCKET_ID_ANY),
+        };
+        for (dst, src) in spec.name.iter_mut().zip(c_name.iter()) {
+            *dst = *src as i8;
+        }
+
+        let mut id: ServiceId = 0;
+        let ret = unsafe { rte_service_component_register(&spec, &mut id) };
+        if ret != 0 {
+            drop(unsafe { Box::from_raw(callback) });
+            return Err(DpdkError::from_return("rte_service_component_register", ret)
+                .with_name(name)
+                .into());
+        }
+
+        let service = ComponentService {
+            service: Service { id },
+            callback,
+        };
+        // The closure is ready as soon as it exists, so the component side is always running
+        service.set_component_running(true)?;
+        Ok(service)
+    }
+
+    fn set_component_running(&self, running: bool) -> Result<(), DpdkError> {
+        let ret = unsafe { rte_service_component_runstate_set(self.service.id, running as u32) };
+        DpdkError::check("rte_service_component_runstate_set", ret)
+            .map_err(|err| err.with_service(self.service.id))
+    }
+}
+
+impl Deref for ComponentService {
+    type Target = Service;
+
+    fn deref(&self) -> &Self::Target {
+        &self.service
+    }
+}
+
+impl Drop for ComponentService {
+    fn drop(&mut self) {
+        let _ = self.service.set_running(false);
+        let _ = self.set_component_running(false);
+        // The callback may not be freed while a service core is still inside of it
+        while self.service.may_be_active() {
+            std::hint::spin_loop();
+        }
+        unsafe {
+            rte_service_component_unregister(self.service.id);
+            drop(Box::from_raw(self.callback));
+        }
+    }
+}
+
+pub fn service_count() -> u32 {
+    unsafe { rte_service_get_count() }
+}
+
+pub fn services() -> impl Iterator<Item = Service> {
+    (0..service_count()).filter_map(Service::from_id)
+}
+
+/// Turns an lcore into a service core, it must not be running anything else.
+pub fn add_service_lcore(lcore: LCoreId) -> Result<(), DpdkError> {
+    lcore_result("rte_service_lcore_add", lcore, unsafe {
+        rte_service_lcore_add(lcore as u32)
+    })
+}
+
+pub fn remove_service_lcore(lcore: LCoreId) -> Result<(), DpdkError> {
+    lcore_result("rte_service_lcore_del", lcore, unsafe {
+        rte_service_lcore_del(lcore as u32)
+    })
+}
+
+/// Starts running the services mapped to a service lcore.
+pub fn start_service_lcore(lcore: LCoreId) -> Result<(), DpdkError> {
+    lcore_result("rte_service_lcore_start", lcore, unsafe {
+        rte_service_lcore_start(lcore as u32)
+    })
+}
+
+pub fn stop_service_lcore(lcore: LCoreId) -> Result<(), DpdkError> {
+    lcore_result("rte_service_lcore_stop", lcore, unsafe {
+        rte_service_lcore_stop(lcore as u32)
+    })
+}
+
+/// Number of services mapped to a service lcore, None if the lcore is not a service lcore.
+pub fn services_on_lcore(lcore: LCoreId) -> Option<u32> {
+    let ret = unsafe { rte_service_lcore_count_services(lcore as u32) };
+    if ret < 0 {
+        None
+    } else {
+        Some(ret as u32)
+    }
+}
+
+pub fn service_lcores() -> Vec<LCoreId> {
+    let mut lcores = vec![0u32; RTE_MAX_LCORE as usize];
+    let ret = unsafe { rte_service_lcore_list(lcores.as_mut_ptr(), lcores.len() as u32) };
+    lcores.truncate(ret.max(0) as usize);
+    lcores.into_iter().map(|lcore| lcore as LCoreId).collect()
+}
+
+fn lcore_result(operation: &'static str, lcore: LCoreId, ret: i32) -> Result<(), DpdkError> {
+    DpdkError::check(operation, ret).map_err(|err| err.with_lcore(lcore))
+}
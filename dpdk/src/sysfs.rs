@@ -0,0 +1,142 @@
+//! The parts of sysfs and procfs that are read before EAL init, under a root that tests can
+//! point at a fake tree.
+
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::config::{parse_id_list, PCIAddress};
+
+/// Where to find `sys` and `proc`, `/` unless testing. Used by
+/// [`DPDKConfig::validate_on`](crate::config::DPDKConfig::validate_on) and [`crate::device::pci`].
+#[derive(Debug, Clone)]
+pub struct Host {
+    root: PathBuf,
+}
+
+impl Default for Host {
+    fn default() -> Self {
+        Self::at("/")
+    }
+}
+
+impl Host {
+    pub fn at(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn sysfs(&self) -> PathBuf {
+        self.root.join("sys")
+    }
+
+    pub fn pci_device_path(&self, address: PCIAddress) -> PathBuf {
+        self.sysfs()
+            .join("bus/pci/devices")
+            .join(address.to_string())
+    }
+
+    /// The kernel driver `address` is bound to, None if it is unbound or does not exist.
+    pub fn pci_driver(&self, address: PCIAddress) -> Option<String> {
+        let driver = fs::read_link(self.pci_device_path(address).join("driver")).ok()?;
+        driver
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+    }
+
+    pub(crate) fn cpu_list(&self, name: &str) -> Option<HashSet<u32>> {
+        let path = self.sysfs().join("devices/system/cpu").join(name);
+        let list = read_trimmed(&path)?;
+        match parse_id_list("CPU list", &list) {
+            Ok(cpus) => Some(cpus.into_iter().collect()),
+            Err(err) => {
+                log::debug!("Unable to parse {}: {err}", path.display());
+                None
+            }
+        }
+    }
+
+    /// Mount points of hugetlbfs.
+    pub(crate) fn hugetlbfs_mounts(&self) -> Option<Vec<PathBuf>> {
+        let mounts = fs::read_to_string(self.root.join("proc/mounts")).ok()?;
+        Some(
+            mounts
+                .lines()
+                .filter_map(|line| {
+                    let mut fields = line.split_whitespace();
+                    let mount_point = fields.nth(1)?;
+                    (fields.next()? == "hugetlbfs").then(|| PathBuf::from(mount_point))
+                })
+                .collect(),
+        )
+    }
+
+    /// Free hugepage memory in MB, summed over all page sizes.
+    pub(crate) fn free_hugepage_mb(&self) -> Option<u64> {
+        let pools = fs::read_dir(self.sysfs().join("kernel/mm/hugepages")).ok()?;
+        let mut free_kb = 0;
+        for pool in pools.flatten() {
+            let name = pool.file_name();
+            let page_kb: u64 = match name
+                .to_str()
+                .and_then(|name| name.strip_prefix("hugepages-"))
+                .and_then(|size| size.strip_suffix("kB"))
+                .and_then(|size| size.parse().ok())
+            {
+                Some(size) => size,
+                None => continue,
+            };
+            let free_pages: u64 = read_trimmed(&pool.path().join("free_hugepages"))
+                .and_then(|free| free.parse().ok())
+                .unwrap_or(0);
+            free_kb += page_kb * free_pages;
+        }
+        Some(free_kb / 1024)
+    }
+}
+
+fn read_trimmed(path: &Path) -> Option<String> {
+    fs::read_to_string(path)
+        .ok()
+        .map(|contents| contents.trim().to_string())
+}
+
+/// A directory under the temp dir for tests to build fake trees in, removed when dropped so
+/// a failing test does not leave it behind.
+#[cfg(test)]
+pub(crate) struct TestDir {
+    root: PathBuf,
+}
+
+#[cfg(test)]
+impl TestDir {
+    pub(crate) fn new(name: &str) -> Self {
+        let root = std::env::temp_dir().join(format!("dpdk-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        Self { root }
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.root
+    }
+
+    pub(crate) fn host(&self) -> Host {
+        Host::at(&self.root)
+    }
+
+    /// Writes `contents` to `path` below the root, creating the directories on the way.
+    pub(crate) fn write(&self, path: impl AsRef<Path>, contents: &str) {
+        let path = self.root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+}
+
+#[cfg(test)]
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.root);
+    }
+}
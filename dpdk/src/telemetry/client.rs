@@ -0,0 +1,456 @@
+//! Client for the telemetry socket of a running DPDK process, speaking the same protocol as
+//! `dpdk-telemetry.py`. Nothing in here needs an initialized EAL.
+
+use std::{
+    backtrace::Backtrace,
+    collections::BTreeMap,
+    io::Write,
+    os::unix::{
+        ffi::OsStrExt,
+        io::{AsRawFd, FromRawFd, OwnedFd},
+    },
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::Value;
+
+/// Name of the socket in the runtime directory of a DPDK process.
+pub const SOCKET_NAME: &str = "dpdk_telemetry.v2";
+
+/// The file prefix DPDK uses if `--file-prefix` is not given.
+pub const DEFAULT_FILE_PREFIX: &str = "rte";
+
+#[derive(Debug, thiserror::Error)]
+pub enum TelemetryClientError {
+    #[error("Telemetry socket {path} is unavailable")]
+    Connect {
+        path: PathBuf,
+        source: std::io::Error,
+        backtrace: Backtrace,
+    },
+    #[error("Telemetry socket I/O failed")]
+    Io {
+        #[from]
+        source: std::io::Error,
+        backtrace: Backtrace,
+    },
+    #[error("Invalid reply to {command}")]
+    InvalidReply {
+        command: String,
+        source: serde_json::Error,
+        backtrace: Backtrace,
+    },
+    #[error("Command {command} is unknown or failed")]
+    CommandFailed { command: String, backtrace: Backtrace },
+}
+
+/// The runtime directory DPDK uses for `file_prefix`, picked like `eal_create_runtime_dir` does
+/// from the real user id.
+pub fn runtime_dir(file_prefix: &str) -> PathBuf {
+    let base = if unsafe { libc::getuid() } == 0 {
+        PathBuf::from("/var/run")
+    } else {
+        std::env::var_os("XDG_RUNTIME_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("/tmp"))
+    };
+    base.join("dpdk").join(file_prefix)
+}
+
+pub fn socket_path(file_prefix: &str) -> PathBuf {
+    runtime_dir(file_prefix).join(SOCKET_NAME)
+}
+
+/// Sent by the process when a client connects.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ServerInfo {
+    pub version: String,
+    pub pid: u32,
+    pub max_output_len: usize,
+}
+
+/// Reply to `/ethdev/stats`.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct EthdevStats {
+    pub ipackets: u64,
+    pub opackets: u64,
+    pub ibytes: u64,
+    pub obytes: u64,
+    pub imissed: u64,
+    pub ierrors: u64,
+    pub oerrors: u64,
+    pub rx_nombuf: u64,
+    #[serde(default)]
+    pub q_ipackets: Vec<u64>,
+    #[serde(default)]
+    pub q_opackets: Vec<u64>,
+    #[serde(default)]
+    pub q_ibytes: Vec<u64>,
+    #[serde(default)]
+    pub q_obytes: Vec<u64>,
+    #[serde(default)]
+    pub q_errors: Vec<u64>,
+}
+
+/// Reply to the `xstats` commands, counter name to value.
+pub type Xstats = BTreeMap<String, u64>;
+
+pub struct TelemetryClient {
+    fd: OwnedFd,
+    info: ServerInfo,
+}
+
+impl TelemetryClient {
+    /// Connects to the process started with `--file-prefix=<file_prefix>`.
+    pub fn connect(file_prefix: &str) -> Result<Self, TelemetryClientError> {
+        Self::connect_to(&socket_path(file_prefix))
+    }
+
+    pub fn connect_to(path: &Path) -> Result<Self, TelemetryClientError> {
+        let connect_error = |source| TelemetryClientError::Connect {
+            path: path.to_path_buf(),
+            source,
+            backtrace: Backtrace::capture(),
+        };
+
+        // The telemetry socket is SOCK_SEQPACKET, which std has no type for
+        let fd = unsafe { libc::socket(libc::AF_UNIX, libc::SOCK_SEQPACKET, 0) };
+        if fd < 0 {
+            return Err(connect_error(std::io::Error::last_os_error()));
+        }
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+
+        let mut addr: libc::sockaddr_un = unsafe { std::mem::zeroed() };
+        addr.sun_family = libc::AF_UNIX as libc::sa_family_t;
+        let path_bytes = path.as_os_str().as_bytes();
+        if path_bytes.len() >= addr.sun_path.len() {
+            return Err(connect_error(std::io::Error::from_raw_os_error(
+                libc::ENAMETOOLONG,
+            )));
+        }
+        for (dst, src) in addr.sun_path.iter_mut().zip(path_bytes) {
+            *dst = *src as libc::c_char;
+        }
+        let ret = unsafe {
+            libc::connect(
+                fd.as_raw_fd(),
+                &addr as *const libc::sockaddr_un as *const libc::sockaddr,
+                std::mem::size_of::<libc::sockaddr_un>() as libc::socklen_t,
+            )
+        };
+        if ret < 0 {
+            return Err(connect_error(std::io::Error::last_os_error()));
+        }
+
+        // The greeting is small, the real limit is only known after reading it
+        let greeting = recv_message(&fd, 1024)?;
+        let info = serde_json::from_slice(&greeting).map_err(|source| {
+            TelemetryClientError::InvalidReply {
+                command: "connect".to_string(),
+                source,
+                backtrace: Backtrace::capture(),
+            }
+        })?;
+        Ok(Self { fd, info })
+    }
+
+    pub fn info(&self) -> &ServerInfo {
+        &self.info
+    }
+
+    /// Runs `command` with optional comma separated `params` and returns the value of the
+    /// reply, which DPDK wraps in an object keyed by the command.
+    pub fn raw(
+        &mut self,
+        command: &str,
+        params: Option<&str>,
+    ) -> Result<Value, TelemetryClientError> {
+        let request = match params {
+            Some(params) => format!("{command},{params}"),
+            None => command.to_string(),
+        };
+        let sent = unsafe {
+            libc::send(
+                self.fd.as_raw_fd(),
+                request.as_ptr() as *const libc::c_void,
+                request.len(),
+                0,
+            )
+        };
+        if sent < 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+
+        let reply = recv_message(&self.fd, self.info.max_output_len)?;
+        let mut reply: Value =
+            serde_json::from_slice(&reply).map_err(|source| TelemetryClientError::InvalidReply {
+                command: request.clone(),
+                source,
+                backtrace: Backtrace::capture(),
+            })?;
+        match reply.get_mut(command).map(Value::take) {
+            Some(Value::Null) | None => Err(TelemetryClientError::CommandFailed {
+                command: request,
+                backtrace: Backtrace::capture(),
+            }),
+            Some(value) => Ok(value),
+        }
+    }
+
+    pub fn query<T: DeserializeOwned>(
+        &mut self,
+        command: &str,
+        params: Option<&str>,
+    ) -> Result<T, TelemetryClientError> {
+        let value = self.raw(command, params)?;
+        serde_json::from_value(value).map_err(|source| TelemetryClientError::InvalidReply {
+            command: command.to_string(),
+            source,
+            backtrace: Backtrace::capture(),
+        })
+    }
+
+    /// Every command the process has registered.
+    pub fn commands(&mut self) -> Result<Vec<String>, TelemetryClientError> {
+        self.query("/", None)
+    }
+
+    pub fn help(&mut self, command: &str) -> Result<String, TelemetryClientError> {
+        let help: BTreeMap<String, String> = self.query("/help", Some(command))?;
+        Ok(help.into_values().next().unwrap_or_default())
+    }
+
+    pub fn ethdev_list(&mut self) -> Result<Vec<u16>, TelemetryClientError> {
+        self.query("/ethdev/list", None)
+    }
+
+    pub fn ethdev_stats(&mut self, port: u16) -> Result<EthdevStats, TelemetryClientError> {
+        self.query("/ethdev/stats", Some(&port.to_string()))
+    }
+
+    pub fn ethdev_xstats(&mut self, port: u16) -> Result<Xstats, TelemetryClientError> {
+        self.query("/ethdev/xstats", Some(&port.to_string()))
+    }
+
+    pub fn eventdev_list(&mut self) -> Result<Vec<u8>, TelemetryClientError> {
+        self.query("/eventdev/dev_list", None)
+    }
+
+    pub fn eventdev_ports(&mut self, eventdev: u8) -> Result<Vec<u8>, TelemetryClientError> {
+        self.query("/eventdev/port_list", Some(&eventdev.to_string()))
+    }
+
+    pub fn eventdev_queues(&mut self, eventdev: u8) -> Result<Vec<u8>, TelemetryClientError> {
+        self.query("/eventdev/queue_list", Some(&eventdev.to_string()))
+    }
+
+    pub fn eventdev_xstats(&mut self, eventdev: u8) -> Result<Xstats, TelemetryClientError> {
+        self.query("/eventdev/dev_xstats", Some(&eventdev.to_string()))
+    }
+
+    pub fn eventdev_port_xstats(
+        &mut self,
+        eventdev: u8,
+        port: u8,
+    ) -> Result<Xstats, TelemetryClientError> {
+        self.query("/eventdev/port_xstats", Some(&format!("{eventdev},{port}")))
+    }
+
+    pub fn eventdev_queue_xstats(
+        &mut self,
+        eventdev: u8,
+        queue: u8,
+    ) -> Result<Xstats, TelemetryClientError> {
+        self.query("/eventdev/queue_xstats", Some(&format!("{eventdev},{queue}")))
+    }
+
+    /// Runs `command` every `interval` and writes one CSV row per reply, `count` times or until
+    /// an error if `count` is None. Nested values become columns like `q_ipackets.0`, the
+    /// columns are taken from the first reply.
+    pub fn poll_csv<W: Write>(
+        &mut self,
+        command: &str,
+        params: Option<&str>,
+        interval: Duration,
+        count: Option<usize>,
+        mut out: W,
+    ) -> Result<(), TelemetryClientError> {
+        let mut columns: Option<Vec<String>> = None;
+        let mut rows = 0;
+        while count.map_or(true, |count| rows < count) {
+            let started = std::time::Instant::now();
+            let mut values = BTreeMap::new();
+            flatten("", &self.raw(command, params)?, &mut values);
+            let timestamp = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs_f64();
+
+            if columns.is_none() {
+                let header = std::iter::once("timestamp".to_string())
+                    .chain(values.keys().map(|column| csv_field(column)))
+                    .collect::<Vec<_>>()
+                    .join(",");
+                writeln!(out, "{header}")?;
+                columns = Some(values.keys().cloned().collect());
+            }
+            let columns = columns.as_ref().expect("set above");
+            let row = std::iter::once(format!("{timestamp:.6}"))
+                .chain(columns.iter().map(|column| {
+                    values
+                        .get(column)
+                        .map(|value| csv_field(value))
+                        .unwrap_or_default()
+                }))
+                .collect::<Vec<_>>()
+                .join(",");
+            writeln!(out, "{row}")?;
+            out.flush()?;
+
+            rows += 1;
+            if count.map_or(true, |count| rows < count) {
+                std::thread::sleep(interval.saturating_sub(started.elapsed()));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn recv_message(fd: &OwnedFd, max_len: usize) -> Result<Vec<u8>, TelemetryClientError> {
+    let mut buf = vec![0u8; max_len.max(1)];
+    let len = unsafe {
+        libc::recv(
+            fd.as_raw_fd(),
+            buf.as_mut_ptr() as *mut libc::c_void,
+            buf.len(),
+            0,
+        )
+    };
+    if len < 0 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+    buf.truncate(len as usize);
+    Ok(buf)
+}
+
+fn flatten(prefix: &str, value: &Value, out: &mut BTreeMap<String, String>) {
+    let key = |name: &str| {
+        if prefix.is_empty() {
+            name.to_string()
+        } else {
+            format!("{prefix}.{name}")
+        }
+    };
+    match value {
+        Value::Object(map) => {
+            for (name, value) in map {
+                flatten(&key(name), value, out);
+            }
+        }
+        Value::Array(values) => {
+            for (index, value) in values.iter().enumerate() {
+                flatten(&key(&index.to_string()), value, out);
+            }
+        }
+        Value::Null => {}
+        scalar => {
+            // A command that replies with a single value gets a single column
+            let name = if prefix.is_empty() { "value" } else { prefix };
+            let text = match scalar {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            out.insert(name.to_string(), text);
+        }
+    }
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::os::unix::io::{AsRawFd, FromRawFd, OwnedFd};
+
+    use super::TelemetryClient;
+    use crate::sysfs::TestDir;
+
+    /// Serves one client the way the telemetry thread of a primary process does.
+    fn fake_server(path: &std::path::Path, replies: Vec<(&'static str, &'static str)>) {
+        let fd = unsafe { libc::socket(libc::AF_UNIX, libc::SOCK_SEQPACKET, 0) };
+        assert!(fd >= 0);
+        let listener = unsafe { OwnedFd::from_raw_fd(fd) };
+        let mut addr: libc::sockaddr_un = unsafe { std::mem::zeroed() };
+        addr.sun_family = libc::AF_UNIX as libc::sa_family_t;
+        for (dst, src) in addr.sun_path.iter_mut().zip(path.to_str().unwrap().bytes()) {
+            *dst = src as libc::c_char;
+        }
+        unsafe {
+            assert_eq!(
+                libc::bind(
+                    listener.as_raw_fd(),
+                    &addr as *const libc::sockaddr_un as *const libc::sockaddr,
+                    std::mem::size_of::<libc::sockaddr_un>() as libc::socklen_t,
+                ),
+                0
+            );
+            assert_eq!(libc::listen(listener.as_raw_fd(), 1), 0);
+        }
+
+        std::thread::spawn(move || {
+            let conn = unsafe {
+                OwnedFd::from_raw_fd(libc::accept(
+                    listener.as_raw_fd(),
+                    std::ptr::null_mut(),
+                    std::ptr::null_mut(),
+                ))
+            };
+            let send = |msg: &str| unsafe {
+                libc::send(conn.as_raw_fd(), msg.as_ptr() as *const libc::c_void, msg.len(), 0);
+            };
+            send(r#"{"version": "DPDK 22.07.0", "pid": 42, "max_output_len": 16384}"#);
+            for (request, reply) in replies {
+                let mut buf = [0u8; 1024];
+                let len = unsafe {
+                    libc::recv(conn.as_raw_fd(), buf.as_mut_ptr() as *mut libc::c_void, 1024, 0)
+                };
+                assert_eq!(&buf[..len as usize], request.as_bytes());
+                send(reply);
+            }
+        });
+    }
+
+    #[test]
+    fn test_client_decodes_replies() {
+        let dir = TestDir::new("telemetry");
+        let path = dir.path().join("dpdk_telemetry.v2");
+        fake_server(
+            &path,
+            vec![
+                ("/", r#"{"/": ["/", "/ethdev/list", "/ethdev/stats"]}"#),
+                (
+                    "/ethdev/stats,0",
+                    r#"{"/ethdev/stats": {"ipackets": 10, "opackets": 9, "ibytes": 640, "obytes": 576, "imissed": 1, "ierrors": 0, "oerrors": 0, "rx_nombuf": 0, "q_ipackets": [10, 0]}}"#,
+                ),
+                ("/ethdev/xstats,7", r#"{"/ethdev/xstats": null}"#),
+            ],
+        );
+
+        let mut client = TelemetryClient::connect_to(&path).unwrap();
+        assert_eq!(client.info().pid, 42);
+        assert_eq!(client.commands().unwrap().len(), 3);
+
+        let stats = client.ethdev_stats(0).unwrap();
+        assert_eq!(stats.ipackets, 10);
+        assert_eq!(stats.q_ipackets, vec![10, 0]);
+
+        assert!(client.ethdev_xstats(7).is_err());
+    }
+}
@@ -0,0 +1,288 @@
+use std::{
+    collections::HashMap,
+    ffi::CStr,
+    fmt::Display,
+    sync::Arc,
+};
+
+use dpdk_sys::{
+    rte_tel_data, rte_tel_data_add_array_container, rte_tel_data_add_array_int,
+    rte_tel_data_add_array_string, rte_tel_data_add_array_u64, rte_tel_data_add_dict_container,
+    rte_tel_data_add_dict_int, rte_tel_data_add_dict_string, rte_tel_data_add_dict_u64,
+    rte_tel_data_alloc, rte_tel_data_free, rte_tel_data_start_array, rte_tel_data_start_dict,
+    rte_tel_data_string, rte_tel_value_type, rte_telemetry_register_cmd, EEXIST, EINVAL,
+    RTE_TEL_MAX_ARRAY_ENTRIES, RTE_TEL_MAX_DICT_ENTRIES, RTE_TEL_MAX_STRING_LEN,
+};
+use parking_lot::Mutex;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{callback::catch_panic, error::DpdkError, util::str_to_c_string};
+
+pub mod client;
+
+type CommandCallback = Arc<dyn Fn(&str) -> Result<Value, String> + Send + Sync>;
+
+/// `telemetry_cb` gets no user data, so the callbacks are looked up by their command.
+static COMMANDS: Mutex<Option<HashMap<String, CommandCallback>>> = Mutex::new(None);
+
+/// Registers `cmd`, for example `"/thesis/clients"`, with the telemetry socket. `f` gets the
+/// parameters after the command, the empty string if there are none, and runs on the telemetry
+/// thread.
+///
+/// The value is sent as the JSON it serializes to, within the limits of telemetry data:
+/// - objects and arrays can only be nested one level deep, deeper values are sent as JSON text
+/// - integers that fit neither `u64` nor `i32`, floats and mixed arrays are sent as strings
+/// - booleans are sent as 0 or 1 and null dict entries are left out
+/// - strings are cut to 127 bytes, dicts to 256 and arrays to 512 entries
+///
+/// Commands stay registered until the process exits.
+pub fn register_command<F, V, E>(cmd: &str, help: &str, f: F) -> Result<(), DpdkError>
+where
+    F: Fn(&str) -> Result<V, E> + Send + Sync + 'static,
+    V: Serialize,
+    E: Display,
+{
+    let mut commands = COMMANDS.lock();
+    let commands = commands.get_or_insert_with(HashMap::new);
+    if commands.contains_key(cmd) {
+        return Err(
+            DpdkError::from_errno("rte_telemetry_register_cmd", EEXIST as i32).with_name(cmd),
+        );
+    }
+
+    let c_cmd = str_to_c_string(cmd);
+    let c_help = str_to_c_string(help);
+    // DPDK keeps the help text but not the command, it copies that
+    let c_help = Box::leak(c_help.into_boxed_c_str());
+    let ret = unsafe {
+        rte_telemetry_register_cmd(c_cmd.as_ptr(), Some(command_trampoline), c_help.as_ptr())
+    };
+    DpdkError::check("rte_telemetry_register_cmd", ret).map_err(|err| err.with_name(cmd))?;
+
+    commands.insert(
+        cmd.to_string(),
+        Arc::new(move |params| {
+            let value = f(params).map_err(|err| err.to_string())?;
+            serde_json::to_value(value).map_err(|err| err.to_string())
+        }),
+    );
+    Ok(())
+}
+
+unsafe extern "C" fn command_trampoline(
+    cmd: *const libc::c_char,
+    params: *const libc::c_char,
+    info: *mut rte_tel_data,
+) -> libc::c_int {
+    let cmd = unsafe { CStr::from_ptr(cmd) }.to_string_lossy();
+    let params = if params.is_null() {
+        String::new()
+    } else {
+        unsafe { CStr::from_ptr(params) }.to_string_lossy().into_owned()
+    };
+
+    // Cloned out so a slow command does not block registering others
+    let callback = COMMANDS
+        .lock()
+        .as_ref()
+        .and_then(|commands| commands.get(cmd.as_ref()).cloned());
+    let callback = match callback {
+        Some(callback) => callback,
+        None => return -(EINVAL as i32),
+    };
+
+    match catch_panic(format_args!("Telemetry command {cmd}"), || callback(&params)) {
+        Some(Ok(value)) => unsafe { write_value(info, &value) },
+        Some(Err(err)) => {
+            log::warn!("Telemetry command {cmd} failed: {err}");
+            -(EINVAL as i32)
+        }
+        None => -(EINVAL as i32),
+    }
+}
+
+fn truncate(s: &str) -> String {
+    let max = RTE_TEL_MAX_STRING_LEN as usize - 1;
+    if s.len() <= max {
+        return s.to_string();
+    }
+    let mut end = max;
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    s[..end].to_string()
+}
+
+/// Scalars as they go into telemetry data, anything else is sent as a string.
+enum Scalar {
+    U64(u64),
+    Int(i32),
+    String(String),
+}
+
+fn scalar(value: &Value) -> Option<Scalar> {
+    match value {
+        Value::Null => None,
+        Value::Bool(b) => Some(Scalar::U64(*b as u64)),
+        Value::Number(n) => Some(if let Some(n) = n.as_u64() {
+            Scalar::U64(n)
+        } else if let Some(n) = n.as_i64().and_then(|n| i32::try_from(n).ok()) {
+            Scalar::Int(n)
+        } else {
+            Scalar::String(n.to_string())
+        }),
+        Value::String(s) => Some(Scalar::String(truncate(s))),
+        Value::Array(_) | Value::Object(_) => Some(Scalar::String(truncate(&value.to_string()))),
+    }
+}
+
+/// Top level value of a reply.
+unsafe fn write_value(d: *mut rte_tel_data, value: &Value) -> libc::c_int {
+    match value {
+        Value::Object(map) => unsafe { write_dict(d, map, true) },
+        Value::Array(values) => unsafe { write_array(d, values, true) },
+        other => match scalar(other) {
+            Some(Scalar::U64(n)) => unsafe { write_string(d, &n.to_string()) },
+            Some(Scalar::Int(n)) => unsafe { write_string(d, &n.to_string()) },
+            Some(Scalar::String(s)) => unsafe { write_string(d, &s) },
+            None => unsafe { write_string(d, "null") },
+        },
+    }
+}
+
+unsafe fn write_string(d: *mut rte_tel_data, s: &str) -> libc::c_int {
+    let s = str_to_c_string(s.replace('\0', ""));
+    unsafe { rte_tel_data_string(d, s.as_ptr()) }
+}
+
+/// Containers can only hold plain values, `nest` is whether this one may hold containers.
+unsafe fn write_dict(
+    d: *mut rte_tel_data,
+    map: &serde_json::Map<String, Value>,
+    nest: bool,
+) -> libc::c_int {
+    let ret = unsafe { rte_tel_data_start_dict(d) };
+    if ret != 0 {
+        return ret;
+    }
+
+    for (key, value) in map.iter().take(RTE_TEL_MAX_DICT_ENTRIES as usize) {
+        let key = str_to_c_string(truncate(&key.replace('\0', "")));
+        let ret = match value {
+            Value::Object(_) | Value::Array(_) if nest => unsafe {
+                let container = rte_tel_data_alloc();
+                if container.is_null() {
+                    return -(dpdk_sys::ENOMEM as i32);
+                }
+                let ret = match value {
+                    Value::Object(inner) => write_dict(container, inner, false),
+                    Value::Array(inner) => write_array(container, inner, false),
+                    _ => unreachable!(),
+                };
+                if ret != 0 {
+                    rte_tel_data_free(container);
+                    return ret;
+                }
+                rte_tel_data_add_dict_container(d, key.as_ptr(), container, 0)
+            },
+            value => match scalar(value) {
+                Some(Scalar::U64(n)) => unsafe { rte_tel_data_add_dict_u64(d, key.as_ptr(), n) },
+                Some(Scalar::Int(n)) => unsafe { rte_tel_data_add_dict_int(d, key.as_ptr(), n) },
+                Some(Scalar::String(s)) => unsafe {
+                    let s = str_to_c_string(s.replace('\0', ""));
+                    rte_tel_data_add_dict_string(d, key.as_ptr(), s.as_ptr())
+                },
+                None => 0,
+            },
+        };
+        if ret != 0 {
+            return ret;
+        }
+    }
+    0
+}
+
+unsafe fn write_array(d: *mut rte_tel_data, values: &[Value], nest: bool) -> libc::c_int {
+    let values = &values[..values.len().min(RTE_TEL_MAX_ARRAY_ENTRIES as usize)];
+    let scalars = values.iter().filter_map(scalar).collect::<Vec<_>>();
+
+    // Telemetry arrays hold a single type
+    let containers = nest
+        && !values.is_empty()
+        && values
+            .iter()
+            .all(|value| matches!(value, Value::Object(_) | Value::Array(_)));
+    let all_u64 = scalars.iter().all(|s| matches!(s, Scalar::U64(_)));
+    let all_int = scalars
+        .iter()
+        .all(|s| matches!(s, Scalar::Int(_)) || matches!(s, Scalar::U64(n) if *n <= i32::MAX as u64));
+
+    if containers {
+        let ret = unsafe { rte_tel_data_start_array(d, rte_tel_value_type::RTE_TEL_CONTAINER) };
+        if ret != 0 {
+            return ret;
+        }
+        for value in values {
+            let ret = unsafe {
+                let container = rte_tel_data_alloc();
+                if container.is_null() {
+                    return -(dpdk_sys::ENOMEM as i32);
+                }
+                let ret = match value {
+                    Value::Object(inner) => write_dict(container, inner, false),
+                    Value::Array(inner) => write_array(container, inner, false),
+                    _ => unreachable!(),
+                };
+                if ret != 0 {
+                    rte_tel_data_free(container);
+                    return ret;
+                }
+                rte_tel_data_add_array_container(d, container, 0)
+            };
+            if ret != 0 {
+                return ret;
+            }
+        }
+        return 0;
+    }
+
+    let value_type = if all_u64 {
+        rte_tel_value_type::RTE_TEL_U64_VAL
+    } else if all_int {
+        rte_tel_value_type::RTE_TEL_INT_VAL
+    } else {
+        rte_tel_value_type::RTE_TEL_STRING_VAL
+    };
+    let ret = unsafe { rte_tel_data_start_array(d, value_type) };
+    if ret != 0 {
+        return ret;
+    }
+
+    for value in scalars {
+        let ret = match (value_type, value) {
+            (rte_tel_value_type::RTE_TEL_U64_VAL, Scalar::U64(n)) => unsafe {
+                rte_tel_data_add_array_u64(d, n)
+            },
+            (rte_tel_value_type::RTE_TEL_INT_VAL, Scalar::U64(n)) => unsafe {
+                rte_tel_data_add_array_int(d, n as i32)
+            },
+            (rte_tel_value_type::RTE_TEL_INT_VAL, Scalar::Int(n)) => unsafe {
+                rte_tel_data_add_array_int(d, n)
+            },
+            (_, value) => {
+                let s = match value {
+                    Scalar::U64(n) => n.to_string(),
+                    Scalar::Int(n) => n.to_string(),
+                    Scalar::String(s) => s,
+                };
+                let s = str_to_c_string(s.replace('\0', ""));
+                unsafe { rte_tel_data_add_array_string(d, s.as_ptr()) }
+            }
+        };
+        if ret != 0 {
+            return ret;
+        }
+    }
+    0
+}
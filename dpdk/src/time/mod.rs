@@ -0,0 +1,214 @@
+use std::{
+    ops::{Add, Sub},
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, SystemTime},
+};
+
+use dpdk_sys::{rte_event_dequeue_timeout_ticks, rte_get_tsc_hz};
+use serde::{Deserialize, Serialize};
+
+use crate::{device::event::EventDeviceId, error::DpdkError};
+
+const NANOS_PER_SEC: u128 = 1_000_000_000;
+
+/// Fractional bits of the fixed point factors below.
+const SCALE_SHIFT: u32 = 32;
+
+// Filled in once by `init` after `rte_eal_init`, the TSC frequency does not change afterwards.
+// Conversions are per packet, so they must not go through FFI or divide.
+static TSC_HZ: AtomicU64 = AtomicU64::new(0);
+/// Nanoseconds per cycle, shifted left by `SCALE_SHIFT`
+static NANOS_PER_CYCLE: AtomicU64 = AtomicU64::new(0);
+/// Cycles per nanosecond, shifted left by `SCALE_SHIFT`
+static CYCLES_PER_NANO: AtomicU64 = AtomicU64::new(0);
+
+/// Caches the TSC frequency the EAL measured. Called by [`crate::eal::Eal`] right after
+/// `rte_eal_init` succeeded.
+pub(crate) fn init() {
+    let hz = unsafe { rte_get_tsc_hz() };
+    TSC_HZ.store(hz, Ordering::Relaxed);
+    NANOS_PER_CYCLE.store(nanos_per_cycle(hz), Ordering::Relaxed);
+    CYCLES_PER_NANO.store(cycles_per_nano(hz), Ordering::Relaxed);
+}
+
+fn nanos_per_cycle(hz: u64) -> u64 {
+    ((NANOS_PER_SEC << SCALE_SHIFT) / hz as u128) as u64
+}
+
+fn cycles_per_nano(hz: u64) -> u64 {
+    (((hz as u128) << SCALE_SHIFT) / NANOS_PER_SEC) as u64
+}
+
+/// Cycles per second of the TSC, measured by the EAL during init. 0 before the EAL is
+/// initialized.
+#[inline]
+pub fn tsc_hz() -> u64 {
+    TSC_HZ.load(Ordering::Relaxed)
+}
+
+/// Reads the TSC. This is `rte_rdtsc`, which is only available as an inline function.
+#[inline(always)]
+pub fn rdtsc() -> u64 {
+    unsafe { core::arch::x86_64::_rdtsc() }
+}
+
+#[inline]
+fn scale(value: u128, factor: u64) -> u64 {
+    debug_assert!(factor != 0, "TSC conversion before the EAL was initialized");
+    let scaled = (value * factor as u128) >> SCALE_SHIFT;
+    scaled.min(u64::MAX as u128) as u64
+}
+
+#[inline]
+pub fn cycles_to_duration(cycles: u64) -> Duration {
+    Duration::from_nanos(scale(cycles as u128, NANOS_PER_CYCLE.load(Ordering::Relaxed)))
+}
+
+/// Saturates at `u64::MAX` cycles.
+#[inline]
+pub fn duration_to_cycles(duration: Duration) -> u64 {
+    // Durations above u64::MAX nanoseconds would overflow the multiplication
+    let nanos = duration.as_nanos().min(u64::MAX as u128);
+    scale(nanos, CYCLES_PER_NANO.load(Ordering::Relaxed))
+}
+
+/// A point in time read from the TSC, like [`std::time::Instant`] without the syscall.
+///
+/// The TSC is synchronized between cores on anything DPDK runs on, so timestamps taken on
+/// different lcores can be compared. It is not synchronized between machines. The raw cycle
+/// count is what gets serialized.
+#[repr(transparent)]
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub struct TscInstant(u64);
+
+impl TscInstant {
+    #[inline(always)]
+    pub fn now() -> Self {
+        Self(rdtsc())
+    }
+
+    pub const fn from_cycles(cycles: u64) -> Self {
+        Self(cycles)
+    }
+
+    pub const fn cycles(&self) -> u64 {
+        self.0
+    }
+
+    /// Zero if `earlier` is later than `self`.
+    #[inline]
+    pub fn duration_since(&self, earlier: TscInstant) -> Duration {
+        cycles_to_duration(self.0.saturating_sub(earlier.0))
+    }
+
+    #[inline]
+    pub fn elapsed(&self) -> Duration {
+        TscInstant::now().duration_since(*self)
+    }
+
+    pub fn checked_add(&self, duration: Duration) -> Option<TscInstant> {
+        self.0.checked_add(duration_to_cycles(duration)).map(Self)
+    }
+
+    pub fn checked_sub(&self, duration: Duration) -> Option<TscInstant> {
+        self.0.checked_sub(duration_to_cycles(duration)).map(Self)
+    }
+}
+
+impl Add<Duration> for TscInstant {
+    type Output = TscInstant;
+
+    fn add(self, rhs: Duration) -> Self::Output {
+        self.checked_add(rhs)
+            .expect("overflow when adding duration to TSC instant")
+    }
+}
+
+impl Sub<Duration> for TscInstant {
+    type Output = TscInstant;
+
+    fn sub(self, rhs: Duration) -> Self::Output {
+        self.checked_sub(rhs)
+            .expect("overflow when subtracting duration from TSC instant")
+    }
+}
+
+impl Sub<TscInstant> for TscInstant {
+    type Output = Duration;
+
+    fn sub(self, rhs: TscInstant) -> Self::Output {
+        self.duration_since(rhs)
+    }
+}
+
+/// Maps TSC instants to wall clock time, from one reading of both clocks.
+///
+/// The TSC and the system clock drift apart over hours, recalibrate if the mapping has to stay
+/// accurate to more than a few microseconds.
+#[derive(Debug, Clone, Copy)]
+pub struct WallClock {
+    tsc: TscInstant,
+    system: SystemTime,
+}
+
+impl WallClock {
+    /// Reads the system clock between two TSC reads and pairs it with their midpoint.
+    pub fn calibrate() -> Self {
+        let before = rdtsc();
+        let system = SystemTime::now();
+        let after = rdtsc();
+        Self {
+            tsc: TscInstant(before + (after - before) / 2),
+            system,
+        }
+    }
+
+    pub fn to_system_time(&self, instant: TscInstant) -> SystemTime {
+        if instant >= self.tsc {
+            self.system + instant.duration_since(self.tsc)
+        } else {
+            self.system - self.tsc.duration_since(instant)
+        }
+    }
+
+    /// None for times the TSC cannot represent, before the machine booted.
+    pub fn to_tsc_instant(&self, time: SystemTime) -> Option<TscInstant> {
+        match time.duration_since(self.system) {
+            Ok(after) => self.tsc.checked_add(after),
+            Err(before) => self.tsc.checked_sub(before.duration()),
+        }
+    }
+}
+
+/// The `timeout_ticks` to pass to [`crate::device::event::event_interface::dequeue_events`]
+/// for waiting up to `timeout`. Only has an effect on event devices configured with
+/// `RTE_EVENT_DEV_CFG_PER_DEQUEUE_TIMEOUT`.
+pub fn dequeue_timeout_ticks(
+    eventdev: EventDeviceId,
+    timeout: Duration,
+) -> Result<u64, DpdkError> {
+    let mut ticks = 0;
+    let ret = unsafe {
+        rte_event_dequeue_timeout_ticks(eventdev, timeout.as_nanos() as u64, &mut ticks)
+    };
+    DpdkError::check("rte_event_dequeue_timeout_ticks", ret)
+        .map_err(|err| err.with_eventdev(eventdev))?;
+    Ok(ticks)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn fixed_point_factors() {
+        let hz = 2_500_000_000;
+        let nanos = nanos_per_cycle(hz);
+        let cycles = cycles_per_nano(hz);
+        assert_eq!(scale(hz as u128, nanos), 999_999_999);
+        assert_eq!(scale(1_000_000_000, cycles), hz);
+        assert_eq!(scale(u64::MAX as u128, cycles), u64::MAX);
+    }
+}
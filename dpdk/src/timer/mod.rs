@@ -0,0 +1,339 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use dpdk_sys::{
+    rte_eal_alarm_cancel, rte_eal_alarm_set, rte_timer, rte_timer_init,
+    rte_timer_manage, rte_timer_next_ticks, rte_timer_pending, rte_timer_reset,
+    rte_timer_stop, rte_timer_stop_sync, rte_timer_subsystem_init, rte_timer_type, EALREADY, EBUSY,
+};
+use libc::c_void;
+use parking_lot::Mutex;
+
+use crate::{
+    callback::catch_panic,
+    eal::LCoreId,
+    error::DpdkError,
+    time::{cycles_to_duration, duration_to_cycles, rdtsc},
+};
+
+/// What an alarm or a timer runs.
+enum Callback {
+    Once(Option<Box<dyn FnOnce() + Send>>),
+    Periodic(Box<dyn FnMut() + Send>),
+}
+
+impl Callback {
+    /// Returns whether the callback has to run again. Panics are handled as described in
+    /// [`crate::callback`].
+    fn run(&mut self, what: &str) -> bool {
+        match self {
+            Callback::Once(f) => {
+                if let Some(f) = f.take() {
+                    catch_panic(format_args!("{what} callback"), f);
+                }
+                false
+            }
+            Callback::Periodic(f) => {
+                catch_panic(format_args!("{what} callback"), f);
+                true
+            }
+        }
+    }
+}
+
+struct AlarmState {
+    cancelled: AtomicBool,
+    period_us: u64,
+    callback: Mutex<Callback>,
+}
+
+/// Every armed alarm owns one strong reference to its state, passed to the EAL as `cb_arg`.
+unsafe extern "C" fn alarm_trampoline(arg: *mut c_void) {
+    let state = unsafe { Arc::from_raw(arg as *const AlarmState) };
+    if state.cancelled.load(Ordering::Acquire) {
+        return;
+    }
+
+    let rearm = state.callback.lock().run("Alarm");
+
+    if rearm && !state.cancelled.load(Ordering::Acquire) {
+        let period_us = state.period_us;
+        let arg = Arc::into_raw(state) as *mut c_void;
+        let ret = unsafe { rte_eal_alarm_set(period_us, Some(alarm_trampoline), arg) };
+        if ret != 0 {
+            log::error!("Unable to re-arm periodic alarm, received error {ret}");
+            drop(unsafe { Arc::from_raw(arg as *const AlarmState) });
+        }
+    }
+}
+
+/// Handle to an EAL alarm. Alarms run on the EAL interrupt thread, not on an lcore.
+///
+/// Dropping the handle cancels the alarm, use [`AlarmHandle::detach`] to let it run anyway.
+#[must_use = "dropping an AlarmHandle cancels the alarm"]
+pub struct AlarmHandle {
+    state: Option<Arc<AlarmState>>,
+}
+
+impl AlarmHandle {
+    fn arm(
+        delay: Duration,
+        period: Duration,
+        callback: Callback,
+    ) -> Result<Self, DpdkError> {
+        let state = Arc::new(AlarmState {
+            cancelled: AtomicBool::new(false),
+            period_us: period.as_micros() as u64,
+            callback: Mutex::new(callback),
+        });
+
+        let arg = Arc::into_raw(state.clone()) as *mut c_void;
+        let delay_us = delay.as_micros() as u64;
+        let ret = unsafe { rte_eal_alarm_set(delay_us, Some(alarm_trampoline), arg) };
+        if ret == 0 {
+            Ok(AlarmHandle { state: Some(state) })
+        } else {
+            drop(unsafe { Arc::from_raw(arg as *const AlarmState) });
+            Err(DpdkError::from_return("rte_eal_alarm_set", ret))
+        }
+    }
+
+    /// Cancels the alarm, waiting for the callback if it is currently running on the
+    /// interrupt thread. Returns false if a one-shot alarm had already fired.
+    pub fn cancel(mut self) -> bool {
+        self.cancel_inner()
+    }
+
+    /// Lets the alarm keep running without a handle, a detached periodic alarm runs until the
+    /// process exits.
+    pub fn detach(mut self) {
+        self.state.take();
+    }
+
+    fn cancel_inner(&mut self) -> bool {
+        let state = match self.state.take() {
+            Some(state) => state,
+            None => return false,
+        };
+        state.cancelled.store(true, Ordering::Release);
+
+        let arg = Arc::as_ptr(&state) as *mut c_void;
+        let cancelled = unsafe { rte_eal_alarm_cancel(Some(alarm_trampoline), arg) };
+        for _ in 0..cancelled.max(0) {
+            // Reclaim the references held by the alarms that will now never fire
+            drop(unsafe { Arc::from_raw(arg as *const AlarmState) });
+        }
+        cancelled > 0
+    }
+}
+
+impl Drop for AlarmHandle {
+    fn drop(&mut self) {
+        self.cancel_inner();
+    }
+}
+
+/// Runs `f` once on the EAL interrupt thread after `delay`, with microsecond resolution.
+pub fn alarm_after<F>(delay: Duration, f: F) -> Result<AlarmHandle, DpdkError>
+where
+    F: FnOnce() + Send + 'static,
+{
+    AlarmHandle::arm(delay, Duration::ZERO, Callback::Once(Some(Box::new(f))))
+}
+
+/// Runs `f` on the EAL interrupt thread every `period`, until the handle is cancelled or
+/// dropped. The next alarm is armed after `f` returns, so the period does not include the
+/// time spent in `f`.
+pub fn alarm_every<F>(period: Duration, f: F) -> Result<AlarmHandle, DpdkError>
+where
+    F: FnMut() + Send + 'static,
+{
+    AlarmHandle::arm(period, period, Callback::Periodic(Box::new(f)))
+}
+
+/// Must be called once after EAL init before any [`Timer`] is scheduled. Calling it again is
+/// not an error.
+pub fn timer_subsystem_init() -> Result<(), DpdkError> {
+    let ret = unsafe { rte_timer_subsystem_init() };
+    if ret == -(EALREADY as i32) {
+        Ok(())
+    } else {
+        DpdkError::check("rte_timer_subsystem_init", ret)
+    }
+}
+
+/// Runs the callbacks of all expired timers owned by the calling lcore.
+#[inline]
+pub fn manage() {
+    unsafe {
+        rte_timer_manage();
+    }
+}
+
+/// Time until the next timer of the calling lcore expires, None if it has no pending timers.
+pub fn next_expiry() -> Option<Duration> {
+    let ticks = unsafe { rte_timer_next_ticks() };
+    if ticks < 0 {
+        None
+    } else {
+        Some(cycles_to_duration(ticks as u64))
+    }
+}
+
+/// Calls [`manage`] at most once per resolution period, so a poll loop can call
+/// [`TimerPoller::poll`] every iteration without paying for a skiplist walk each time.
+pub struct TimerPoller {
+    resolution_cycles: u64,
+    last_manage: u64,
+}
+
+impl TimerPoller {
+    pub fn new(resolution: Duration) -> Self {
+        Self {
+            resolution_cycles: duration_to_cycles(resolution),
+            last_manage: 0,
+        }
+    }
+
+    #[inline]
+    pub fn poll(&mut self) {
+        let now = rdtsc();
+        if now.wrapping_sub(self.last_manage) >= self.resolution_cycles {
+            manage();
+            self.last_manage = now;
+        }
+    }
+}
+
+struct TimerInner {
+    timer: rte_timer,
+    callback: Option<Callback>,
+}
+
+unsafe extern "C" fn timer_trampoline(_timer: *mut rte_timer, arg: *mut c_void) {
+    let inner = unsafe { &mut *(arg as *mut TimerInner) };
+    if let Some(callback) = inner.callback.as_mut() {
+        callback.run("Timer");
+    }
+}
+
+/// A timer on the per-lcore `rte_timer` wheel. Callbacks run inside [`manage`] on the lcore
+/// the timer was scheduled on.
+///
+/// The timer is stopped when dropped, waiting for its callback if it is running on another
+/// lcore.
+pub struct Timer {
+    // rte_timer is linked into the skiplist of its lcore, so it may not move
+    inner: Box<TimerInner>,
+}
+
+// The callback is Send, and rte_timer's own state machine handles cross-lcore access
+unsafe impl Send for Timer {}
+
+impl Timer {
+    pub fn new() -> Self {
+        let mut inner = Box::new(TimerInner {
+            timer: unsafe { std::mem::MaybeUninit::zeroed().assume_init() },
+            callback: None,
+        });
+        unsafe { rte_timer_init(&mut inner.timer) };
+        Self { inner }
+    }
+
+    /// Runs `f` once on `lcore` after `delay`, replacing anything previously scheduled.
+    pub fn schedule_once<F>(
+        &mut self,
+        delay: Duration,
+        lcore: LCoreId,
+        f: F,
+    ) -> Result<(), DpdkError>
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        let callback = Callback::Once(Some(Box::new(f)));
+        self.schedule(delay, rte_timer_type::SINGLE, lcore, callback)
+    }
+
+    /// Runs `f` on `lcore` every `period`, replacing anything previously scheduled.
+    pub fn schedule_periodic<F>(
+        &mut self,
+        period: Duration,
+        lcore: LCoreId,
+        f: F,
+    ) -> Result<(), DpdkError>
+    where
+        F: FnMut() + Send + 'static,
+    {
+        let callback = Callback::Periodic(Box::new(f));
+        self.schedule(period, rte_timer_type::PERIODICAL, lcore, callback)
+    }
+
+    fn schedule(
+        &mut self,
+        ticks: Duration,
+        timer_type: rte_timer_type,
+        lcore: LCoreId,
+        callback: Callback,
+    ) -> Result<(), DpdkError> {
+        // The old callback may be running on another lcore and must not be swapped out under it
+        self.stop_sync();
+        self.inner.callback = Some(callback);
+
+        let arg = &mut *self.inner as *mut TimerInner as *mut c_void;
+        let ret = unsafe {
+            rte_timer_reset(
+                &mut self.inner.timer,
+                duration_to_cycles(ticks),
+                timer_type,
+                lcore as u32,
+                Some(timer_trampoline),
+                arg,
+            )
+        };
+
+        if ret == 0 {
+            Ok(())
+        } else {
+            self.inner.callback = None;
+            // -1 means the timer is being modified on another lcore, anything else is an errno
+            let err = if ret == -1 {
+                DpdkError::from_errno("rte_timer_reset", EBUSY as i32)
+            } else {
+                DpdkError::from_return("rte_timer_reset", ret)
+            };
+            Err(err.with_lcore(lcore))
+        }
+    }
+
+    /// Stops the timer. Returns false if its callback is running on another lcore right now.
+    pub fn stop(&mut self) -> bool {
+        unsafe { rte_timer_stop(&mut self.inner.timer) == 0 }
+    }
+
+    /// Stops the timer, spinning until its callback has returned if it is currently running.
+    pub fn stop_sync(&mut self) {
+        unsafe { rte_timer_stop_sync(&mut self.inner.timer) }
+    }
+
+    pub fn is_pending(&self) -> bool {
+        let timer = &self.inner.timer as *const rte_timer as *mut rte_timer;
+        unsafe { rte_timer_pending(timer) == 1 }
+    }
+}
+
+impl Default for Timer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for Timer {
+    fn drop(&mut self) {
+        self.stop_sync();
+    }
+}
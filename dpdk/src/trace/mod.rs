@@ -0,0 +1,436 @@
+use std::{
+    backtrace::Backtrace,
+    cell::RefCell,
+    ffi::CString,
+    fmt::Display,
+    marker::PhantomData,
+    str::FromStr,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use dpdk_sys::{
+    __rte_trace_mem_per_thread_alloc, __rte_trace_point_emit_field, __rte_trace_point_register,
+    per_lcore_trace_mem, per_lcore_trace_point_sz, rte_trace_is_enabled, rte_trace_mode,
+    rte_trace_mode_get, rte_trace_mode_set, rte_trace_pattern, rte_trace_point_disable,
+    rte_trace_point_enable, rte_trace_point_is_enabled, rte_trace_point_lookup,
+    rte_trace_point_t, rte_trace_regexp, rte_trace_save, EEXIST,
+    __RTE_TRACE_EMIT_STRING_LEN_MAX, __RTE_TRACE_EVENT_HEADER_ID_SHIFT,
+    __RTE_TRACE_EVENT_HEADER_SZ, __RTE_TRACE_FIELD_ENABLE_DISCARD,
+    __RTE_TRACE_FIELD_ENABLE_MASK, __RTE_TRACE_FIELD_ID_MASK, __RTE_TRACE_FIELD_ID_SHIFT,
+    __RTE_TRACE_FIELD_SIZE_MASK,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::ConfigError, eal::Eal, error::DpdkError, time::rdtsc, util::str_to_c_string,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TraceMode {
+    /// Old events are overwritten once the buffer of a thread is full
+    Overwrite,
+    /// New events are dropped once the buffer of a thread is full
+    Discard,
+}
+
+/// The value of the `--trace-mode` EAL option.
+impl Display for TraceMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TraceMode::Overwrite => write!(f, "overwrite"),
+            TraceMode::Discard => write!(f, "discard"),
+        }
+    }
+}
+
+impl FromStr for TraceMode {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "overwrite" => Ok(TraceMode::Overwrite),
+            "discard" => Ok(TraceMode::Discard),
+            _ => Err(ConfigError::invalid(
+                "trace mode",
+                s,
+                "expected overwrite or discard",
+            )),
+        }
+    }
+}
+
+/// Whether tracing was switched on with the `trace` option of the EAL config. Without it no
+/// tracepoint records anything and [`save`] writes nothing.
+pub fn is_enabled() -> bool {
+    unsafe { rte_trace_is_enabled() }
+}
+
+pub fn mode() -> TraceMode {
+    match unsafe { rte_trace_mode_get() } {
+        rte_trace_mode::RTE_TRACE_MODE_OVERWRITE => TraceMode::Overwrite,
+        rte_trace_mode::RTE_TRACE_MODE_DISCARD => TraceMode::Discard,
+    }
+}
+
+/// Applies to every registered tracepoint, including ones enabled later.
+pub fn set_mode(mode: TraceMode) {
+    let mode = match mode {
+        TraceMode::Overwrite => rte_trace_mode::RTE_TRACE_MODE_OVERWRITE,
+        TraceMode::Discard => rte_trace_mode::RTE_TRACE_MODE_DISCARD,
+    };
+    unsafe { rte_trace_mode_set(mode) }
+}
+
+/// Returns whether any tracepoint matched.
+fn pattern_result(operation: &'static str, pattern: &str, ret: i32) -> Result<bool, DpdkError> {
+    if ret < 0 {
+        Err(DpdkError::from_return(operation, ret).with_name(pattern))
+    } else {
+        Ok(ret == 1)
+    }
+}
+
+/// Enables the tracepoints matching the glob `pattern`, for example `"lib.eal.*"`. Returns
+/// whether any tracepoint matched.
+pub fn enable_pattern(pattern: &str) -> Result<bool, DpdkError> {
+    let c_pattern = str_to_c_string(pattern);
+    let ret = unsafe { rte_trace_pattern(c_pattern.as_ptr(), true) };
+    pattern_result("rte_trace_pattern", pattern, ret)
+}
+
+pub fn disable_pattern(pattern: &str) -> Result<bool, DpdkError> {
+    let c_pattern = str_to_c_string(pattern);
+    let ret = unsafe { rte_trace_pattern(c_pattern.as_ptr(), false) };
+    pattern_result("rte_trace_pattern", pattern, ret)
+}
+
+/// Like [`enable_pattern`] with a POSIX regular expression.
+pub fn enable_regex(regex: &str) -> Result<bool, DpdkError> {
+    let c_regex = str_to_c_string(regex);
+    let ret = unsafe { rte_trace_regexp(c_regex.as_ptr(), true) };
+    pattern_result("rte_trace_regexp", regex, ret)
+}
+
+pub fn disable_regex(regex: &str) -> Result<bool, DpdkError> {
+    let c_regex = str_to_c_string(regex);
+    let ret = unsafe { rte_trace_regexp(c_regex.as_ptr(), false) };
+    pattern_result("rte_trace_regexp", regex, ret)
+}
+
+/// Writes the trace buffers and the CTF metadata to the trace directory, which can be set
+/// with the `trace_dir` option of the EAL config. Can be called repeatedly, every call
+/// overwrites the previous output.
+pub fn save() -> Result<(), DpdkError> {
+    DpdkError::check("rte_trace_save", unsafe { rte_trace_save() })
+}
+
+/// A value that can be recorded in a tracepoint, with the CTF type it shows up as.
+pub trait TraceValue {
+    /// One of the type aliases in the metadata DPDK writes
+    const CTF_TYPE: &'static str;
+    const SIZE: usize;
+
+    fn ctf_field(name: &str) -> String {
+        name.to_string()
+    }
+
+    /// `buf` is exactly `SIZE` bytes long.
+    fn write_to(&self, buf: &mut [u8]);
+}
+
+macro_rules! impl_trace_value {
+    ($($ty:ty => $ctf_type:literal),* $(,)?) => {
+        $(
+            impl TraceValue for $ty {
+                const CTF_TYPE: &'static str = $ctf_type;
+                const SIZE: usize = std::mem::size_of::<$ty>();
+
+                #[inline(always)]
+                fn write_to(&self, buf: &mut [u8]) {
+                    buf.copy_from_slice(&self.to_ne_bytes());
+                }
+            }
+        )*
+    };
+}
+
+impl_trace_value! {
+    u8 => "uint8_t",
+    i8 => "int8_t",
+    u16 => "uint16_t",
+    i16 => "int16_t",
+    u32 => "uint32_t",
+    i32 => "int32_t",
+    u64 => "uint64_t",
+    i64 => "int64_t",
+    usize => "uintptr_t",
+    f32 => "float",
+    f64 => "double",
+}
+
+impl TraceValue for bool {
+    const CTF_TYPE: &'static str = "uint8_t";
+    const SIZE: usize = 1;
+
+    #[inline(always)]
+    fn write_to(&self, buf: &mut [u8]) {
+        buf[0] = *self as u8;
+    }
+}
+
+/// Recorded as a NUL terminated string of at most 31 bytes, longer strings are cut off.
+impl TraceValue for String {
+    const CTF_TYPE: &'static str = "string_bounded_t";
+    const SIZE: usize = __RTE_TRACE_EMIT_STRING_LEN_MAX as usize;
+
+    fn ctf_field(name: &str) -> String {
+        format!("{name}[{}]", Self::SIZE)
+    }
+
+    fn write_to(&self, buf: &mut [u8]) {
+        let len = self.len().min(Self::SIZE - 1);
+        buf[..len].copy_from_slice(&self.as_bytes()[..len]);
+        buf[len..].fill(0);
+    }
+}
+
+/// The fields of a tracepoint, in the order [`TraceEvent::emit`] writes them.
+#[derive(Debug, Default)]
+pub struct TraceFields {
+    fields: Vec<(CString, CString, usize)>,
+}
+
+impl TraceFields {
+    pub fn add<T: TraceValue>(&mut self, name: &str) -> &mut Self {
+        self.fields.push((
+            str_to_c_string(T::ctf_field(name)),
+            str_to_c_string(T::CTF_TYPE),
+            T::SIZE,
+        ));
+        self
+    }
+}
+
+/// Writes the fields of one event into the trace buffer.
+pub struct TraceWriter<'a> {
+    buf: &'a mut [u8],
+}
+
+impl<'a> TraceWriter<'a> {
+    /// Values that do not fit into the space registered for the event are dropped.
+    #[inline(always)]
+    pub fn write<T: TraceValue>(&mut self, value: &T) {
+        if self.buf.len() < T::SIZE {
+            debug_assert!(false, "trace event wrote more than it registered");
+            return;
+        }
+        let buf = std::mem::take(&mut self.buf);
+        let (field, rest) = buf.split_at_mut(T::SIZE);
+        value.write_to(field);
+        self.buf = rest;
+    }
+}
+
+/// The payload of a tracepoint. Usually implemented with [`crate::trace_event`].
+pub trait TraceEvent {
+    fn fields(fields: &mut TraceFields);
+
+    /// Has to write the fields in the same order and with the same types as [`Self::fields`].
+    fn emit(&self, writer: &mut TraceWriter);
+}
+
+/// Declares a struct and implements [`TraceEvent`] for it, with one field per struct field.
+///
+/// ```ignore
+/// dpdk::trace_event! {
+///     pub struct MessageDequeued {
+///         pub client_id: u32,
+///         pub message_id: u32,
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! trace_event {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $($field_vis:vis $field:ident : $ty:ty),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis struct $name {
+            $($field_vis $field: $ty),*
+        }
+
+        impl $crate::trace::TraceEvent for $name {
+            fn fields(fields: &mut $crate::trace::TraceFields) {
+                $(fields.add::<$ty>(stringify!($field));)*
+            }
+
+            #[inline(always)]
+            fn emit(&self, writer: &mut $crate::trace::TraceWriter) {
+                $(writer.write(&self.$field);)*
+            }
+        }
+    };
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum TraceError {
+    #[error("Tracepoint {name} has to be registered before the EAL is initialized, the CTF metadata is generated during init")]
+    EalInitialized { name: String, backtrace: Backtrace },
+    #[error(transparent)]
+    Dpdk(#[from] DpdkError),
+}
+
+thread_local! {
+    /// Fields of the tracepoint being registered, `__rte_trace_point_register` takes a callback
+    /// without an argument.
+    static REGISTERING: RefCell<TraceFields> = RefCell::new(TraceFields::default());
+}
+
+unsafe extern "C" fn register_fields() {
+    unsafe { per_lcore_trace_point_sz = __RTE_TRACE_EVENT_HEADER_SZ as i32 };
+    REGISTERING.with(|fields| {
+        for (name, ctf_type, size) in &fields.borrow().fields {
+            unsafe { __rte_trace_point_emit_field(*size as _, name.as_ptr(), ctf_type.as_ptr()) };
+        }
+    });
+}
+
+/// A tracepoint defined in Rust. Its events go into the same per-thread buffers as the ones of
+/// DPDK, so they show up next to each other in babeltrace.
+pub struct TracePoint<E> {
+    /// DPDK keeps a pointer to the handle to flip the enable bits
+    handle: &'static AtomicU64,
+    _phantom: PhantomData<fn(&E)>,
+}
+
+impl<E> Clone for TracePoint<E> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<E> Copy for TracePoint<E> {}
+
+impl<E: TraceEvent> TracePoint<E> {
+    /// Registers a tracepoint called `name`, for example `"thesis.message.dequeued"`. Has to be
+    /// called before the EAL is initialized. Tracepoints are never unregistered.
+    pub fn register(name: &str) -> Result<Self, TraceError> {
+        if Eal::is_initialized() {
+            return Err(TraceError::EalInitialized {
+                name: name.to_string(),
+                backtrace: Backtrace::capture(),
+            });
+        }
+
+        let c_name = str_to_c_string(name);
+        if !unsafe { rte_trace_point_lookup(c_name.as_ptr()) }.is_null() {
+            return Err(DpdkError::from_errno("__rte_trace_point_register", EEXIST as i32)
+                .with_name(name)
+                .into());
+        }
+
+        let handle: &'static AtomicU64 = Box::leak(Box::new(AtomicU64::new(0)));
+        let mut fields = TraceFields::default();
+        E::fields(&mut fields);
+        REGISTERING.with(|registering| *registering.borrow_mut() = fields);
+        let ret = unsafe {
+            __rte_trace_point_register(
+                handle as *const AtomicU64 as *mut rte_trace_point_t,
+                // DPDK copies the name
+                c_name.as_ptr(),
+                Some(register_fields),
+            )
+        };
+        REGISTERING.with(|registering| *registering.borrow_mut() = TraceFields::default());
+        if ret < 0 {
+            return Err(DpdkError::from_return("__rte_trace_point_register", ret)
+                .with_name(name)
+                .into());
+        }
+
+        Ok(Self {
+            handle,
+            _phantom: PhantomData,
+        })
+    }
+
+    fn raw(&self) -> *mut rte_trace_point_t {
+        self.handle as *const AtomicU64 as *mut rte_trace_point_t
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        unsafe { rte_trace_point_is_enabled(self.raw()) }
+    }
+
+    pub fn enable(&self) -> Result<(), DpdkError> {
+        DpdkError::check("rte_trace_point_enable", unsafe {
+            rte_trace_point_enable(self.raw())
+        })
+    }
+
+    pub fn disable(&self) -> Result<(), DpdkError> {
+        DpdkError::check("rte_trace_point_disable", unsafe {
+            rte_trace_point_disable(self.raw())
+        })
+    }
+
+    /// Records `event` in the trace buffer of the calling thread. This is a single load while
+    /// the tracepoint is disabled.
+    #[inline(always)]
+    pub fn emit(&self, event: &E) {
+        let handle = self.handle.load(Ordering::Acquire);
+        if handle & __RTE_TRACE_FIELD_ENABLE_MASK == 0 {
+            return;
+        }
+
+        let size = (handle & __RTE_TRACE_FIELD_SIZE_MASK) as usize;
+        let mem = match unsafe { trace_mem_get(handle) } {
+            Some(mem) => mem,
+            None => return,
+        };
+        let buf = unsafe { std::slice::from_raw_parts_mut(mem, size) };
+        let (header, payload) = buf.split_at_mut(__RTE_TRACE_EVENT_HEADER_SZ);
+
+        // Event header, tracepoint id in the top 16 bits and the TSC in the rest
+        let id = (handle & __RTE_TRACE_FIELD_ID_MASK)
+            << (__RTE_TRACE_EVENT_HEADER_ID_SHIFT - __RTE_TRACE_FIELD_ID_SHIFT);
+        let timestamp = rdtsc() & !(0xffff << __RTE_TRACE_EVENT_HEADER_ID_SHIFT);
+        header.copy_from_slice(&(timestamp | id).to_ne_bytes());
+
+        event.emit(&mut TraceWriter { buf: payload });
+    }
+}
+
+/// `__rte_trace_mem_get`, reserves space for one event in the buffer of the calling thread.
+#[inline(always)]
+unsafe fn trace_mem_get(handle: u64) -> Option<*mut u8> {
+    let size = (handle & __RTE_TRACE_FIELD_SIZE_MASK) as u32;
+
+    let mut trace = unsafe { per_lcore_trace_mem };
+    if trace.is_null() {
+        unsafe { __rte_trace_mem_per_thread_alloc() };
+        trace = unsafe { per_lcore_trace_mem };
+        if trace.is_null() {
+            return None;
+        }
+    }
+    let trace = unsafe { &mut *trace };
+
+    let mut offset = trace.offset;
+    if offset + size >= trace.len {
+        if handle & __RTE_TRACE_FIELD_ENABLE_DISCARD != 0 {
+            return None;
+        }
+        offset = 0;
+    }
+    let align = __RTE_TRACE_EVENT_HEADER_SZ as u32;
+    offset = (offset + align - 1) / align * align;
+    trace.offset = offset + size;
+
+    Some(unsafe { trace.mem.as_mut_ptr().add(offset as usize) })
+}
@@ -22,7 +22,7 @@ impl LogMessage {
         *unsafe { std::mem::transmute::<&Self, &u128>(self) }
 
         // // safe version
-        // ((self.timestamp as u128) << 64) | ((self.client_id.0 as u128) << 32) | (self.message_id.0 as u128)
+        // ((self.timestamp.cycles() as u128) << 64) | ((self.client_id.0 as u128) << 32) | (self.message_id.0 as u128)
     }
 }
 
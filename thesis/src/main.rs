@@ -12,24 +12,32 @@ pub mod log;
 
 use dpdk::{
     self,
-    config::{DPDKConfig, VirtualDevice, PCIAddress, IOVAMode},
+    config::{
+        vdev::EventSwVdev,
+        ConfigFile, DPDKConfig, PCIAddress, IOVAMode, PortConfig, VdevOptions,
+    },
+    eal::Eal,
     device::{
-        eth::dev::{EthdevPortId, EventQueueId, configure_port, setup_port_queues},
+        eth::{
+            config::EthPortConfig,
+            dev::{EthdevPortId, EventQueueId},
+            info::EthDeviceInfo,
+            port::EthPort,
+        },
         event::{
             eth::{rx::rx_adapter::stop_rx_adapter, tx::tx_adapter::stop_tx_adapter},
             EventDeviceId, EventPortId,
         },
     },
-    raw::{rte_eal_cleanup, rte_eal_mp_wait_lcore, rte_trace_save, rte_eth_conf, RTE_ETH_LINK_SPEED_AUTONEG, rte_eth_rxmode},
 };
 
 use semaphore::SpinSemaphore;
 use std::{
     collections::HashMap,
-    ptr::null_mut,
     sync::atomic::{AtomicBool},
+    time::Duration,
 };
-use workers::lcore_init;
+use workers::launch_workers;
 
 /// Services are set up
 static SERVICE_SETUP_DONE: AtomicBool = AtomicBool::new(false);
@@ -45,7 +53,7 @@ macro_rules! swap_with_uninit {
     };
 }
 
-fn apply_config() {
+fn default_config() -> DPDKConfig {
     DPDKConfig {
         cores: dpdk::config::CoreConfig::List(vec![1, 2, 3]),
         main_lcore: None,
@@ -57,84 +65,82 @@ fn apply_config() {
             ],
         },
         virtual_devices: vec![
-            VirtualDevice {
-                driver: "event_sw".to_string(),
-                id: 0,
-                options: HashMap::from([
-                    // ("credit_quanta".to_string(), "64".to_string())
-                ]),
-            },
-            // VirtualDevice {
-            //     driver: "net_pcap".to_string(),
-            //     id: 0,
-            //     options: HashMap::from([
-            //         (
-            //             "rx_pcap".to_string(),
-            //             "./inputs/a.pcap".to_string(),
-            //         ),
-            //         (
-            //             "tx_pcap".to_string(),
-            //             "./outputs/a.pcap".to_string(),
-            //         ),
-            //         ("infinite_rx".to_string(), "1".to_string()),
-            //     ]),
-            // },
+            EventSwVdev::default()
+                .into_vdev(0)
+                .expect("Invalid event_sw options"),
+            // dpdk::config::vdev::PcapVdev {
+            //     rx_pcap: Some("./inputs/a.pcap".into()),
+            //     tx_pcap: Some("./outputs/a.pcap".into()),
+            //     infinite_rx: true,
+            //     ..Default::default()
+            // }
+            // .into_vdev(0)
+            // .expect("Invalid pcap options"),
         ],
         num_memory_channels: None,
         enable_telemetry: true,
         // trace: Some(".*".to_string()),
         trace: None,
+        trace_dir: None,
+        trace_buffer_size: None,
+        trace_mode: None,
         // iova_mode: Some(IOVAMode::PA),
         iova_mode: Some(IOVAMode::PA),
+        proc_type: None,
+        no_huge: false,
+        memory_mb: None,
+        socket_mem: None,
+        file_prefix: None,
+        huge_dir: None,
+        in_memory: false,
+        log_levels: vec![],
+        base_virtaddr: None,
     }
-    .apply()
-    .expect("Error configuring EAL");
-
-    let config = rte_eth_conf {
-        link_speeds: todo!(),
-        rxmode: todo!(),
-        txmode: todo!(),
-        lpbk_mode: todo!(),
-        rx_adv_conf: todo!(),
-        tx_adv_conf: todo!(),
-        dcb_capability_en: todo!(),
-        fdir_conf: todo!(),
-        intr_conf: todo!(),
-    };
-
-    let port_conf = rte_eth_conf {
-        link_speeds: todo!(),
-        rxmode: todo!(),
-        txmode: todo!(),
-        lpbk_mode: todo!(),
-        rx_adv_conf: todo!(),
-        tx_adv_conf: todo!(),
-        dcb_capability_en: todo!(),
-        fdir_conf: todo!(),
-        intr_conf: todo!(),
-    };
+}
 
-    let config = rte_eth_conf {
-        link_speeds: RTE_ETH_LINK_SPEED_AUTONEG,
-        rxmode: rte_eth_rxmode {
-            mq_mode: dpdk::raw::rte_eth_rx_mq_mode::RTE_ETH_MQ_RX_RSS,
-            mtu: todo!(),
-            max_lro_pkt_size: todo!(),
-            split_hdr_size: todo!(),
-            offloads: todo!(),
-            reserved_64s: todo!(),
-            reserved_ptrs: todo!(),
+/// Uses the config file named by `THESIS_CONFIG` if it is set, `DPDK_*` variables override
+/// either one. Returns the port configs of the file.
+fn apply_config() -> (Eal, Vec<PortConfig>) {
+    let config = match std::env::var_os("THESIS_CONFIG") {
+        Some(path) => ConfigFile::load(path).expect("Unable to load config file"),
+        None => ConfigFile {
+            eal: default_config(),
+            ports: vec![],
         },
-        txmode: todo!(),
-        lpbk_mode: todo!(),
-        rx_adv_conf: todo!(),
-        tx_adv_conf: todo!(),
-        dcb_capability_en: todo!(),
-        fdir_conf: todo!(),
-        intr_conf: todo!(),
     };
+    let config = config
+        .with_env_overrides()
+        .expect("Invalid config override");
+    if let Err(err) = config.eal.validate() {
+        ::log::warn!("{err}");
+    }
+    let eal = config.eal.apply().expect("Error configuring EAL");
+
+    for device in dpdk::eal::bus::iter_devices() {
+        ::log::info!("Found device {}", device.info());
+    }
 
-    setup_port_queues(0, 1, 1, )
+    (eal, config.ports)
+}
+
+fn setup_port<'eal>(eal: &'eal Eal, ports: &[PortConfig]) -> EthPort<'eal> {
+    let info = EthDeviceInfo::get(ETHDEV_PORT_ID).expect("Unable to query port");
+    ::log::info!(
+        "Port {ETHDEV_PORT_ID} uses driver {}, up to {} rx and {} tx queues",
+        info.driver_name, info.max_rx_queues, info.max_tx_queues
+    );
+    let port_config = match ports.iter().find(|port| port.port == ETHDEV_PORT_ID) {
+        Some(port) => EthPortConfig::from_port_config(port, &info).expect("Invalid port config"),
+        None => EthPortConfig::for_device(&info, 1, 1),
+    };
+    let mut port =
+        EthPort::new(eal, ETHDEV_PORT_ID, port_config).expect("Unable to set up port");
+    port.start().expect("Unable to start port");
+    match port.wait_for_link_up(Duration::from_secs(5)) {
+        Ok(status) => ::log::info!("Port {ETHDEV_PORT_ID} link {status}"),
+        Err(err) => ::log::warn!("{err}"),
+    }
+    port
 }
 
 // eventdev setup
@@ -156,26 +162,55 @@ const ETHDEV_PORT_ID: EthdevPortId = 0;
 const ETHDEV_QUEUE_ID: u16 = 0;
 
 fn main() -> Result<(), anyhow::Error> {
-    apply_config();
-
-    dpdk::raw::rte_lcore_foreach_worker(|lcore_id| unsafe {
-        dpdk::raw::rte_eal_remote_launch(Some(lcore_init), null_mut(), lcore_id);
-    });
+    let (eal, ports) = apply_config();
+    let port = setup_port(&eal, &ports);
+
+    dpdk::telemetry::register_command(
+        "/thesis/workers",
+        "Whether the workers have been told to stop. No parameters",
+        |_| {
+            Ok::<_, std::convert::Infallible>(HashMap::from([(
+                "terminating",
+                TERMINATE.load(std::sync::atomic::Ordering::Relaxed),
+            )]))
+        },
+    )?;
 
-    unsafe {
-        rte_trace_save();
+    if let Err(err) = dpdk::trace::save() {
+        ::log::warn!("Unable to save trace: {err}");
     }
 
-    let mut buf = String::new();
-    std::io::stdin().read_line(&mut buf).ok();
-
-    TERMINATE.store(true, std::sync::atomic::Ordering::SeqCst);
-    RUNNING.take_max(); // wait for everything to finish
-
-    unsafe {
-        rte_eal_mp_wait_lcore();
-        rte_eal_cleanup();
-    }
+    // The workers borrow the queue handles of the port, the scope waits for them to return
+    dpdk::eal::scope(|scope| -> Result<(), anyhow::Error> {
+        let rx_queue = port.rx_queue(ETHDEV_QUEUE_ID)?;
+        let tx_queue = port.tx_queue(ETHDEV_QUEUE_ID)?;
+        let workers = match launch_workers(scope, rx_queue, tx_queue) {
+            Ok(workers) => workers,
+            Err(err) => {
+                // The scope only returns once workers that did start have stopped
+                TERMINATE.store(true, std::sync::atomic::Ordering::SeqCst);
+                return Err(err.into());
+            }
+        };
+
+        let mut buf = String::new();
+        std::io::stdin().read_line(&mut buf).ok();
+
+        TERMINATE.store(true, std::sync::atomic::Ordering::SeqCst);
+        RUNNING.take_max(); // wait for everything to finish
+
+        for worker in workers {
+            let lcore = worker.lcore();
+            if worker.join().is_err() {
+                ::log::error!("Worker on lcore {lcore} panicked");
+            }
+        }
+        Ok(())
+    })?;
+
+    drop(port);
+    // waits for the lcores and cleans up the EAL
+    drop(eal);
     Ok(())
     // dpdk_exit(0, "Success");
 }
@@ -11,6 +11,22 @@ pub struct ClientMessagePacketHeader {
     pub starting_message_id: MessageId,
 }
 
+impl ClientMessagePacketHeader {
+    /// A header stamped with the current time.
+    pub fn new(
+        client_id: ClientId,
+        starting_message_id: MessageId,
+        number_of_messages: MessageCount,
+    ) -> Self {
+        Self {
+            timestamp: MessageTimestamp::now(),
+            client_id,
+            number_of_messages,
+            starting_message_id,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct ClientMessagePacket {
     pub header: ClientMessagePacketHeader,
@@ -19,7 +35,7 @@ pub struct ClientMessagePacket {
 
 #[cfg(test)]
 mod test {
-    use crate::message::{ClientId, ClientMessage, MessageId};
+    use crate::message::{ClientId, ClientMessage, MessageId, MessageTimestamp};
 
     use super::{ClientMessagePacket, ClientMessagePacketHeader};
 
@@ -27,7 +43,7 @@ mod test {
     fn test_serialization_format() {
         let client_packet = ClientMessagePacket {
             header: ClientMessagePacketHeader {
-                timestamp: 0,
+                timestamp: MessageTimestamp::from_cycles(0),
                 client_id: ClientId(0),
                 number_of_messages: 1,
                 starting_message_id: MessageId(0),
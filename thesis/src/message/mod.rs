@@ -5,7 +5,8 @@ use crate::log::{LogKey, LogValue};
 pub mod message_packet;
 
 pub type MessageCount = u16;
-pub type MessageTimestamp = u64;
+/// TSC time the client sent the packet at, shared with the rest of the pipeline.
+pub type MessageTimestamp = dpdk::time::TscInstant;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct ClientId(pub u32);
@@ -3,21 +3,19 @@ pub mod eth;
 
 use crate::TX_ADAPTER_INPUT_QUEUE_ID;
 
-use crossbeam_channel::{Receiver, Sender};
 use dpdk::{
     self,
-    device::event::event_interface::{dequeue_events, enqueue_new_events},
-    eal::current_lcore_id,
+    device::{
+        eth::{rx::RxQueue, tx::TxQueue},
+        event::event_interface::{dequeue_events, enqueue_new_events},
+    },
+    eal::{current_lcore_id, LCoreId, LaunchScope, LcoreJoinHandle},
+    error::DpdkError,
+    time::dequeue_timeout_ticks,
     raw::{rte_event, rte_mbuf, RTE_EVENT_OP_RELEASE},
 };
-use libc::{c_int, c_void};
-use parking_lot::{Mutex, Once};
 
-use std::{
-    cell::OnceCell,
-    mem::MaybeUninit,
-    sync::atomic::{fence, Ordering},
-};
+use std::{cell::OnceCell, mem::MaybeUninit, time::Duration};
 
 use crate::{EVENTDEV_DEVICE_ID, EVENT_HANDLER_PORT_ID, RUNNING, TERMINATE};
 
@@ -40,38 +38,27 @@ fn buffer_init_helper() {
 }
 
 const BUFFER_SIZE: usize = 1024;
-const IO_GUARD: Once = Once::new();
-static INPUT: Mutex<Option<Sender<&mut rte_mbuf>>> = Mutex::new(None);
-static OUTPUT: Mutex<Option<Receiver<&mut rte_mbuf>>> = Mutex::new(None);
-
-fn channel_init_helper() {
-    IO_GUARD.call_once(|| {
-        let (send, recv) = crossbeam_channel::bounded::<&mut rte_mbuf>(BUFFER_SIZE);
-        let mut input_lock = INPUT.lock();
-        let mut output_lock = OUTPUT.lock();
-        input_lock.replace(send);
-        output_lock.replace(recv);
-    });
-}
 
-pub extern "C" fn lcore_init(_unused: *mut c_void) -> c_int {
-    // buffer_init_helper();
-    channel_init_helper();
-    fence(Ordering::SeqCst);
-    let lcore = current_lcore_id();
-    match lcore {
-        // 0 | 1 => {}
-        2 => read_packets_from_nic_port_0_into_channel::<RX_RING_BUFFER_SIZE>(
-            INPUT.lock().take().unwrap()
-        ),
-        3 => write_packets_to_nic_port_0::<RX_RING_BUFFER_SIZE>(
-            OUTPUT.lock().take().unwrap()
-        ),
-        // 4 => handle_events(),
-        _ => println!("Extra lcore {lcore} exiting"),
-    }
-
-    return 0;
+const RX_LCORE: LCoreId = 2;
+const TX_LCORE: LCoreId = 3;
+
+/// Starts the rx and tx loops on their lcores, connected by a channel owned by the two closures.
+/// Each loop takes its queue handle along, the scope keeps the port alive until they return.
+pub fn launch_workers<'scope>(
+    scope: &'scope LaunchScope<'scope, '_>,
+    rx_queue: RxQueue<'scope>,
+    tx_queue: TxQueue<'scope>,
+) -> Result<Vec<LcoreJoinHandle<()>>, DpdkError> {
+    let (input, output) = crossbeam_channel::bounded::<&'static mut rte_mbuf>(BUFFER_SIZE);
+
+    let rx = scope.launch_on(RX_LCORE, move || {
+        read_packets_from_nic_port_0_into_channel::<RX_RING_BUFFER_SIZE>(rx_queue, input)
+    })?;
+    let tx = scope.launch_on(TX_LCORE, move || {
+        write_packets_to_nic_port_0::<RX_RING_BUFFER_SIZE>(tx_queue, output)
+    })?;
+
+    Ok(vec![rx, tx])
 }
 
 fn handle_events() {
@@ -85,13 +72,15 @@ fn handle_events() {
 
         let mut recv_counter: u64 = 1;
         let mut send_counter: u64 = 1;
+        let dequeue_timeout =
+            dequeue_timeout_ticks(EVENTDEV_DEVICE_ID, Duration::from_nanos(100)).unwrap_or(0);
 
         loop {
             let length = dequeue_events(
                 EVENTDEV_DEVICE_ID,
                 EVENT_HANDLER_PORT_ID,
                 &mut event_buf,
-                100,
+                dequeue_timeout,
             );
             recv_counter += length as u64;
             if length > 0 {